@@ -742,6 +742,8 @@ fn typify(
                 bundler::schemalet::SchemaletValue::String {
                     pattern: None,
                     format: None,
+                    min_length: None,
+                    max_length: None,
                 },
             ),
         },
@@ -759,9 +761,11 @@ fn typify(
     // Not sure this is better, but making a translation here from schema ref
     // to id.
 
-    let mut converter = bundler::convert::Converter::new(canonical);
+    let formats = bundler::convert::FormatTable::default();
+    let replacements = bundler::convert::ReplacementTable::default();
+    let mut converter = bundler::convert::Converter::new(&canonical, &formats, &replacements);
 
-    converter.set_name(root_id.clone(), "SchemaRoot".to_string());
+    converter.set_fallback_name(root_id.clone(), "SchemaRoot".to_string());
 
     let mut work = VecDeque::from([root_id]);
 
@@ -784,7 +788,10 @@ fn typify(
             let url = Url::parse(path).unwrap();
 
             if let Some(fragment) = url.fragment() {
-                if let Some(name) = fragment.strip_prefix("/$defs/") {
+                if let Some(name) = fragment
+                    .strip_prefix("/$defs/")
+                    .filter(|name| !name.contains('/'))
+                {
                     converter.set_name(id.clone(), name.to_string());
                 }
             }
@@ -818,7 +825,7 @@ fn cargo_typify(bundle: Bundle, context: bundler::Context) {
         .add_type_by_id(&context.location.to_string())
         .unwrap();
 
-    let typespace = typify.into_typespace();
+    let typespace = typify.into_typespace().unwrap();
 
     let out = typespace.render();
     println!("file\n{out}");
@@ -40,6 +40,10 @@ pub struct Schema {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    examples: Option<Vec<serde_json::Value>>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     r#type: Option<Type>,
@@ -47,6 +51,8 @@ pub struct Schema {
     // Objects
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     properties: BTreeMap<String, SchemaOrBool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    required: Vec<String>,
     #[serde(
         rename = "additionalProperties",
         skip_serializing_if = "Option::is_none"
@@ -54,12 +60,27 @@ pub struct Schema {
     additional_properties: Option<SchemaOrBool>,
     #[serde(rename = "propertyNames", skip_serializing_if = "Option::is_none")]
     property_names: Option<SchemaOrBool>,
+    #[serde(
+        rename = "patternProperties",
+        default,
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pattern_properties: BTreeMap<String, SchemaOrBool>,
+    #[serde(
+        rename = "unevaluatedProperties",
+        skip_serializing_if = "Option::is_none"
+    )]
+    unevaluated_properties: Option<SchemaOrBool>,
 
     // Arrays
     #[serde(skip_serializing_if = "Option::is_none")]
     items: Option<SchemaOrBool>,
+    #[serde(rename = "prefixItems", skip_serializing_if = "Option::is_none")]
+    prefix_items: Option<NonEmpty<Vec<SchemaOrBool>>>,
     #[serde(rename = "minItems", skip_serializing_if = "Option::is_none")]
     min_items: Option<u64>,
+    #[serde(rename = "maxItems", skip_serializing_if = "Option::is_none")]
+    max_items: Option<u64>,
     #[serde(rename = "uniqueItems", skip_serializing_if = "Option::is_none")]
     unique_items: Option<bool>,
 
@@ -68,6 +89,8 @@ pub struct Schema {
     all_of: Option<NonEmpty<Vec<SchemaOrBool>>>,
     #[serde(rename = "anyOf", skip_serializing_if = "Option::is_none")]
     any_of: Option<NonEmpty<Vec<SchemaOrBool>>>,
+    #[serde(rename = "oneOf", skip_serializing_if = "Option::is_none")]
+    one_of: Option<NonEmpty<Vec<SchemaOrBool>>>,
 
     // Strings
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -75,20 +98,63 @@ pub struct Schema {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     format: Option<String>,
+    #[serde(rename = "minLength", skip_serializing_if = "Option::is_none")]
+    min_length: Option<u64>,
+    #[serde(rename = "maxLength", skip_serializing_if = "Option::is_none")]
+    max_length: Option<u64>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     deprecated: Option<bool>,
+    #[serde(rename = "readOnly", skip_serializing_if = "Option::is_none")]
+    read_only: Option<bool>,
+    #[serde(rename = "writeOnly", skip_serializing_if = "Option::is_none")]
+    write_only: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     default: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     r#enum: Option<Vec<serde_json::Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    r#const: Option<serde_json::Value>,
 
-    // In the real schema this probably needs to be something that can handle
-    // integers and floats, but a u64 here is fine for the bootstrap schema.
+    // Bounds may be fractional or exceed the range of i64 (e.g. u64::MAX), so
+    // we keep them as JSON numbers and interpret them during conversion.
     #[serde(skip_serializing_if = "Option::is_none")]
-    minimum: Option<i64>,
+    minimum: Option<serde_json::Number>,
     #[serde(rename = "exclusiveMinimum", skip_serializing_if = "Option::is_none")]
-    exclusive_minimum: Option<i64>,
+    exclusive_minimum: Option<serde_json::Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    maximum: Option<serde_json::Number>,
+    #[serde(rename = "exclusiveMaximum", skip_serializing_if = "Option::is_none")]
+    exclusive_maximum: Option<serde_json::Number>,
+
+    // OpenAPI 3.0's way of saying "or null".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nullable: Option<bool>,
+
+    // Extensions
+    #[serde(rename = "x-rust-type", skip_serializing_if = "Option::is_none")]
+    rust_type: Option<RustType>,
+}
+
+/// The Rust type for a schema: either a path or, as with typify, an object
+/// with a `path` (and other details we don't use).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+enum RustType {
+    Path(String),
+    Object {
+        path: String,
+        #[serde(flatten)]
+        rest: BTreeMap<String, serde_json::Value>,
+    },
+}
+
+impl RustType {
+    fn path(&self) -> &String {
+        match self {
+            RustType::Path(path) | RustType::Object { path, .. } => path,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -260,8 +326,13 @@ impl Schema {
             properties,
             all_of,
             any_of,
+            one_of,
             items,
+            prefix_items,
             additional_properties,
+            property_names,
+            pattern_properties,
+            unevaluated_properties,
             ..
         } = self;
         let mut out = Vec::new();
@@ -282,12 +353,37 @@ impl Schema {
                 let path = format!("{path}/anyOf/{ii}");
                 out.extend(schema.iter_schema_impl(path))
             });
+        one_of
+            .iter()
+            .flat_map(|x| x.as_inner().iter())
+            .enumerate()
+            .for_each(|(ii, schema)| {
+                let path = format!("{path}/oneOf/{ii}");
+                out.extend(schema.iter_schema_impl(path))
+            });
         items.iter().for_each(|schema| {
             out.extend(schema.iter_schema_impl(format!("{path}/items")));
         });
+        prefix_items
+            .iter()
+            .flat_map(|x| x.as_inner().iter())
+            .enumerate()
+            .for_each(|(ii, schema)| {
+                let path = format!("{path}/prefixItems/{ii}");
+                out.extend(schema.iter_schema_impl(path))
+            });
         additional_properties.iter().for_each(|schema| {
             out.extend(schema.iter_schema_impl(format!("{path}/additionalProperties")));
         });
+        property_names.iter().for_each(|schema| {
+            out.extend(schema.iter_schema_impl(format!("{path}/propertyNames")));
+        });
+        pattern_properties.iter().for_each(|(pattern, schema)| {
+            out.extend(schema.iter_schema_impl(format!("{path}/patternProperties/{pattern}")));
+        });
+        unevaluated_properties.iter().for_each(|schema| {
+            out.extend(schema.iter_schema_impl(format!("{path}/unevaluatedProperties")));
+        });
         properties.iter().for_each(|(name, schema)| {
             out.extend(schema.iter_schema_impl(format!("{path}/properties/{name}")));
         });
@@ -305,6 +401,7 @@ impl Schema {
             properties,
             all_of,
             any_of,
+            one_of,
             items,
             additional_properties,
             ..
@@ -335,6 +432,13 @@ impl Schema {
             .for_each(|(ii, schema)| {
                 schema.walk_schemas(format!("{path}/anyOf/{ii}"));
             });
+        one_of
+            .iter()
+            .flat_map(|x| x.as_inner().iter())
+            .enumerate()
+            .for_each(|(ii, schema)| {
+                schema.walk_schemas(format!("{path}/oneOf/{ii}"));
+            });
         items.iter().for_each(|schema| {
             schema.walk_schemas(format!("{path}/items"));
         });
@@ -875,22 +979,39 @@ impl Schema {
             comment: _,
             defs: _,
             title: _,
+            description: _,
+            examples: _,
             r#type,
             properties: _,
+            required: _,
             additional_properties: _,
             property_names: _,
+            pattern_properties: _,
+            unevaluated_properties: _,
             items: _,
+            prefix_items: _,
             min_items: _,
+            max_items: _,
             unique_items: _,
             all_of,
             any_of,
+            one_of: _,
             pattern: _,
             format: _,
+            min_length: _,
+            max_length: _,
             deprecated: _,
+            read_only: _,
+            write_only: _,
             default: _,
             r#enum,
+            r#const: _,
             minimum: _,
             exclusive_minimum: _,
+            maximum: _,
+            exclusive_maximum: _,
+            nullable: _,
+            rust_type: _,
         } = self;
 
         let value = match r#type {
@@ -1035,8 +1156,11 @@ impl Schema {
             SimpleType::Integer => {
                 let schema_ref = ir2::SchemaRef::Partial(id.clone(), "integer".to_string());
                 let ir = ir2::Schema::Value(ir2::SchemaValue::Integer {
-                    minimum: self.minimum,
-                    exclusive_minimum: self.exclusive_minimum,
+                    minimum: self.minimum.as_ref().and_then(serde_json::Number::as_i64),
+                    exclusive_minimum: self
+                        .exclusive_minimum
+                        .as_ref()
+                        .and_then(serde_json::Number::as_i64),
                 });
                 Ok((schema_ref, ir))
             }
@@ -1044,8 +1168,11 @@ impl Schema {
             SimpleType::Number => {
                 let schema_ref = ir2::SchemaRef::Partial(id.clone(), "number".to_string());
                 let ir = ir2::Schema::Value(ir2::SchemaValue::Number {
-                    minimum: self.minimum,
-                    exclusive_minimum: self.exclusive_minimum,
+                    minimum: self.minimum.as_ref().and_then(serde_json::Number::as_i64),
+                    exclusive_minimum: self
+                        .exclusive_minimum
+                        .as_ref()
+                        .and_then(serde_json::Number::as_i64),
                 });
                 Ok((schema_ref, ir))
             }
@@ -1160,25 +1287,42 @@ impl Schema {
             dynamic_ref,
             r#ref,
             vocabulary: _,
-            comment: _,
+            comment,
             defs: _,
             title,
+            description,
+            examples,
             r#type,
             properties: _,
+            required: _,
             additional_properties: _,
             property_names: _,
+            pattern_properties: _,
+            unevaluated_properties,
             items: _,
+            prefix_items: _,
             min_items: _,
+            max_items: _,
             unique_items: _,
             all_of,
             any_of,
+            one_of,
             pattern: _,
             format: _,
-            deprecated: _,
-            default: _,
+            min_length: _,
+            max_length: _,
+            deprecated,
+            read_only,
+            write_only,
+            default,
             r#enum,
+            r#const,
             minimum: _,
             exclusive_minimum: _,
+            maximum: _,
+            exclusive_maximum: _,
+            nullable,
+            rust_type,
         } = self;
 
         let concrete_value = match r#type {
@@ -1216,6 +1360,13 @@ impl Schema {
             schemalet::SchemaletDetails::AnyOf,
             any_of.as_ref(),
         );
+        let one_of = Self::to_schemalet_subschemas(
+            work,
+            &id,
+            "oneOf",
+            schemalet::SchemaletDetails::OneOf,
+            one_of.as_ref(),
+        );
 
         let subref = r#ref.as_ref().map(|raw_ref| {
             let value_id = id.partial("$ref");
@@ -1248,14 +1399,55 @@ impl Schema {
             (enum_id, value)
         });
 
-        let everything = [concrete_value, all_of, any_of, subref, dynref, enum_values]
-            .into_iter()
-            .flatten()
-            .collect::<Vec<_>>();
+        let const_value = r#const.as_ref().map(|value| {
+            let value_id = id.partial("const");
+            let value = schemalet::SchemaletDetails::Constant(value.clone());
+            (value_id, value)
+        });
+
+        // TODO 10/19/2026
+        // Absent a type, `unevaluatedProperties` (usually alongside an
+        // `allOf`) is applied as though this were an object; strictly it
+        // constrains only those values that are objects.
+        let unevaluated = unevaluated_properties
+            .as_ref()
+            .filter(|_| r#type.is_none())
+            .map(|_| {
+                let value_id = id.partial("unevaluatedProperties");
+                let value = schemalet::SchemaletDetails::Value(schemalet::SchemaletValue::Object(
+                    schemalet::SchemaletValueObject {
+                        unevaluated_properties: self.unevaluated_properties_id(work, &id),
+                        ..Default::default()
+                    },
+                ));
+                (value_id, value)
+            });
+
+        let everything = [
+            concrete_value,
+            all_of,
+            any_of,
+            one_of,
+            subref,
+            dynref,
+            enum_values,
+            const_value,
+            unevaluated,
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
 
         let metadata = schemalet::SchemaletMetadata {
             title: title.clone(),
-            ..Default::default()
+            description: description.clone(),
+            default: default.clone(),
+            examples: examples.clone().unwrap_or_default(),
+            deprecated: deprecated.unwrap_or(false),
+            read_only: read_only.unwrap_or(false),
+            write_only: write_only.unwrap_or(false),
+            comment: comment.clone(),
+            rust_type: rust_type.as_ref().map(RustType::path).cloned(),
         };
 
         let details = match everything.len() {
@@ -1275,12 +1467,44 @@ impl Schema {
                 schemalet::SchemaletDetails::AllOf(subs)
             }
         };
+        // With `nullable: true`, the schema is the "T or null" of what we
+        // have so far.
+        let details = if nullable.unwrap_or(false) {
+            let value_id = id.partial("nonNull");
+            let null_id = id.partial("nullable");
+            work.done(
+                value_id.clone(),
+                schemalet::Schemalet::from_details(details),
+            );
+            work.done(
+                null_id.clone(),
+                schemalet::Schemalet::from_details(schemalet::SchemaletDetails::Value(
+                    schemalet::SchemaletValue::Null,
+                )),
+            );
+            schemalet::SchemaletDetails::ExclusiveOneOf(vec![value_id, null_id])
+        } else {
+            details
+        };
+
         let value = schemalet::Schemalet::new(details, metadata);
 
         work.done(id, value);
         Ok(())
     }
 
+    fn unevaluated_properties_id<'a>(
+        &'a self,
+        work: &mut WorkQueue<'a, schemalet::SchemaRef, schemalet::Schemalet>,
+        id: &schemalet::SchemaRef,
+    ) -> Option<schemalet::SchemaRef> {
+        self.unevaluated_properties.as_ref().map(|up_schema| {
+            let up_id = id.append("unevaluatedProperties");
+            work.push(up_id.id(), up_schema);
+            up_id
+        })
+    }
+
     fn to_schemalet_for_type<'a>(
         &'a self,
         work: &mut WorkQueue<'a, schemalet::SchemaRef, schemalet::Schemalet>,
@@ -1297,13 +1521,26 @@ impl Schema {
                     }
                     None => None,
                 };
+                let prefix_items = self.prefix_items.as_ref().map(|prefix_items| {
+                    let prefix_id = id.append("prefixItems");
+                    prefix_items
+                        .iter()
+                        .enumerate()
+                        .map(|(ii, item_schema)| {
+                            let sub_id = prefix_id.append(&ii.to_string());
+                            work.push(sub_id.id(), item_schema);
+                            sub_id
+                        })
+                        .collect()
+                });
                 let schema_ref = id.partial("array");
                 let ir = schemalet::SchemaletDetails::Value(schemalet::SchemaletValue::Array(
                     SchemaletValueArray {
                         items,
+                        prefix_items,
                         min_items: self.min_items,
+                        max_items: self.max_items,
                         unique_items: self.unique_items,
-                        ..Default::default()
                     },
                 ));
                 Ok((schema_ref, ir))
@@ -1317,17 +1554,26 @@ impl Schema {
             SimpleType::Integer => {
                 let schema_ref = id.partial("integer");
                 let ir = schemalet::SchemaletDetails::Value(schemalet::SchemaletValue::Integer {
-                    minimum: self.minimum,
-                    exclusive_minimum: self.exclusive_minimum,
+                    minimum: self.minimum.clone(),
+                    exclusive_minimum: self.exclusive_minimum.clone(),
+                    maximum: self.maximum.clone(),
+                    exclusive_maximum: self.exclusive_maximum.clone(),
+                    format: self.format.clone(),
                 });
                 Ok((schema_ref, ir))
             }
-            SimpleType::Null => todo!(),
+            SimpleType::Null => {
+                let schema_ref = id.partial("null");
+                let details = schemalet::SchemaletDetails::Value(schemalet::SchemaletValue::Null);
+                Ok((schema_ref, details))
+            }
             SimpleType::Number => {
                 let schema_ref = id.partial("number");
                 let ir = schemalet::SchemaletDetails::Value(schemalet::SchemaletValue::Number {
-                    minimum: self.minimum,
-                    exclusive_minimum: self.exclusive_minimum,
+                    minimum: self.minimum.clone(),
+                    exclusive_minimum: self.exclusive_minimum.clone(),
+                    maximum: self.maximum.clone(),
+                    exclusive_maximum: self.exclusive_maximum.clone(),
                 });
                 Ok((schema_ref, ir))
             }
@@ -1349,11 +1595,66 @@ impl Schema {
                     ap_id
                 });
 
+                // Property names are always strings, but `propertyNames`
+                // needn't say so (e.g. `{ "format": "uuid" }`) in which case
+                // its string constraints would be lost. We merge it with a
+                // string schema that carries them.
+                let property_names = self.property_names.as_ref().map(|pn_schema| {
+                    let pn_id = id.append("propertyNames");
+                    work.push(pn_id.id(), pn_schema);
+
+                    let string_id = pn_id.partial("string");
+                    let string = match pn_schema {
+                        ObjectOrBool::Object(schema) => schemalet::SchemaletValue::String {
+                            pattern: schema.pattern.clone(),
+                            format: schema.format.clone(),
+                            min_length: schema.min_length,
+                            max_length: schema.max_length,
+                        },
+                        ObjectOrBool::Bool(_) => schemalet::SchemaletValue::String {
+                            pattern: None,
+                            format: None,
+                            min_length: None,
+                            max_length: None,
+                        },
+                    };
+                    work.done(
+                        string_id.clone(),
+                        schemalet::Schemalet::from_details(schemalet::SchemaletDetails::Value(
+                            string,
+                        )),
+                    );
+
+                    let names_id = id.partial("propertyNames");
+                    work.done(
+                        names_id.clone(),
+                        schemalet::Schemalet::from_details(schemalet::SchemaletDetails::Merge(
+                            vec![pn_id, string_id],
+                        )),
+                    );
+                    names_id
+                });
+
+                let pattern_properties = (!self.pattern_properties.is_empty()).then(|| {
+                    let pp_id = id.append("patternProperties");
+                    self.pattern_properties
+                        .iter()
+                        .map(|(pattern, pp_schema)| {
+                            let pattern_id = pp_id.append(pattern);
+                            work.push(pattern_id.id(), pp_schema);
+                            (pattern.clone(), pattern_id)
+                        })
+                        .collect()
+                });
+
                 let details = schemalet::SchemaletDetails::Value(
                     schemalet::SchemaletValue::Object(schemalet::SchemaletValueObject {
                         properties,
+                        required: self.required.clone(),
                         additional_properties,
-                        ..Default::default()
+                        property_names,
+                        pattern_properties,
+                        unevaluated_properties: self.unevaluated_properties_id(work, id),
                     }),
                 );
                 let sref = id.partial("object");
@@ -1364,6 +1665,8 @@ impl Schema {
                 let ir = schemalet::SchemaletDetails::Value(schemalet::SchemaletValue::String {
                     pattern: self.pattern.clone(),
                     format: self.format.clone(),
+                    min_length: self.min_length,
+                    max_length: self.max_length,
                 });
                 Ok((schema_ref, ir))
             }
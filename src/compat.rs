@@ -0,0 +1,1323 @@
+//! Compatibility checking between two versions of a schema.
+//!
+//! We compare the normalized (canonical) graphs of the old and new schema and
+//! report each difference along with its location as a JSON pointer into the
+//! schema. Each change is classified by its effect on the set of valid
+//! instances: a change that admits new values (e.g. a widened type) breaks
+//! readers who were written against the old schema; a change that rejects
+//! previously valid values (e.g. a new required property or a narrowed enum)
+//! breaks writers.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use crate::schemalet::{
+    resolve, CanonicalSchemalet, CanonicalSchemaletDetails, SchemaRef, SchemaletType,
+    SchemaletValue, SchemaletValueArray, SchemaletValueObject,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    Compatible,
+    Breaking,
+}
+
+/// How a change affects the set of instances that the schema accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// The new schema accepts values the old one rejected.
+    Widened,
+    /// The new schema rejects values the old one accepted.
+    Narrowed,
+    /// Both of the above.
+    Changed,
+    /// Neither of the above.
+    Neutral,
+}
+
+impl Direction {
+    fn of(narrowed: bool, widened: bool) -> Self {
+        match (narrowed, widened) {
+            (false, false) => Direction::Neutral,
+            (true, false) => Direction::Narrowed,
+            (false, true) => Direction::Widened,
+            (true, true) => Direction::Changed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    PropertyAdded {
+        name: String,
+        required: bool,
+    },
+    PropertyRemoved {
+        name: String,
+    },
+    PropertyNowRequired {
+        name: String,
+    },
+    PropertyNowOptional {
+        name: String,
+    },
+    TypesAdded(Vec<SchemaletType>),
+    TypesRemoved(Vec<SchemaletType>),
+    ValuesAdded(Vec<serde_json::Value>),
+    ValuesRemoved(Vec<serde_json::Value>),
+    /// A schema that permitted anything now has constraints.
+    Constrained,
+    /// A schema with constraints now permits anything.
+    Unconstrained,
+    /// A type that permitted any value of its type is now an enumeration.
+    NowEnumerated(SchemaletType),
+    /// An enumeration now permits any value of its type.
+    NoLongerEnumerated(SchemaletType),
+    /// A validation keyword was added, removed, or modified.
+    Constraint {
+        keyword: &'static str,
+        old: Option<serde_json::Value>,
+        new: Option<serde_json::Value>,
+    },
+    /// We couldn't match up the variants of a `oneOf` so we just note that
+    /// something changed.
+    Variants(SchemaletType),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// JSON pointer to the changed location, relative to the schema root.
+    pub location: String,
+    pub kind: ChangeKind,
+    pub direction: Direction,
+    /// Effect on consumers of instances built against the old schema.
+    pub readers: Compatibility,
+    /// Effect on producers of instances built against the old schema.
+    pub writers: Compatibility,
+}
+
+impl Change {
+    fn new(location: &str, kind: ChangeKind, direction: Direction) -> Self {
+        let (readers, writers) = match direction {
+            Direction::Widened => (Compatibility::Breaking, Compatibility::Compatible),
+            Direction::Narrowed => (Compatibility::Compatible, Compatibility::Breaking),
+            Direction::Changed => (Compatibility::Breaking, Compatibility::Breaking),
+            Direction::Neutral => (Compatibility::Compatible, Compatibility::Compatible),
+        };
+        Self {
+            location: location.to_string(),
+            kind,
+            direction,
+            readers,
+            writers,
+        }
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.readers == Compatibility::Breaking || self.writers == Compatibility::Breaking
+    }
+}
+
+impl Display for Compatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Compatibility::Compatible => f.write_str("compatible"),
+            Compatibility::Breaking => f.write_str("breaking"),
+        }
+    }
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let values = |values: &[serde_json::Value]| {
+            values
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            ChangeKind::PropertyAdded {
+                name,
+                required: true,
+            } => write!(f, "required property \"{name}\" added"),
+            ChangeKind::PropertyAdded {
+                name,
+                required: false,
+            } => write!(f, "optional property \"{name}\" added"),
+            ChangeKind::PropertyRemoved { name } => write!(f, "property \"{name}\" removed"),
+            ChangeKind::PropertyNowRequired { name } => {
+                write!(f, "property \"{name}\" is now required")
+            }
+            ChangeKind::PropertyNowOptional { name } => {
+                write!(f, "property \"{name}\" is now optional")
+            }
+            ChangeKind::Constrained => f.write_str("now constrained"),
+            ChangeKind::Unconstrained => f.write_str("now unconstrained"),
+            ChangeKind::TypesAdded(types) => write!(f, "types added: {types:?}"),
+            ChangeKind::TypesRemoved(types) => write!(f, "types removed: {types:?}"),
+            ChangeKind::ValuesAdded(v) => write!(f, "values added: {}", values(v)),
+            ChangeKind::ValuesRemoved(v) => write!(f, "values removed: {}", values(v)),
+            ChangeKind::NowEnumerated(typ) => write!(f, "{typ:?} is now enumerated"),
+            ChangeKind::NoLongerEnumerated(typ) => {
+                write!(f, "{typ:?} is no longer enumerated")
+            }
+            ChangeKind::Constraint { keyword, old, new } => {
+                let show = |v: &Option<serde_json::Value>| match v {
+                    Some(v) => v.to_string(),
+                    None => "(none)".to_string(),
+                };
+                write!(f, "{keyword} changed from {} to {}", show(old), show(new))
+            }
+            ChangeKind::Variants(typ) => write!(f, "{typ:?} variants changed"),
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let location = if self.location.is_empty() {
+            "(root)"
+        } else {
+            &self.location
+        };
+        write!(
+            f,
+            "{location}: {} (readers: {}, writers: {})",
+            self.kind, self.readers, self.writers,
+        )
+    }
+}
+
+/// Compare the schema rooted at `old_root` in `old` with the schema rooted at
+/// `new_root` in `new`. The graphs may be the same (e.g. when comparing two
+/// documents from a single bundle).
+pub fn diff(
+    old: &BTreeMap<SchemaRef, CanonicalSchemalet>,
+    old_root: &SchemaRef,
+    new: &BTreeMap<SchemaRef, CanonicalSchemalet>,
+    new_root: &SchemaRef,
+) -> Vec<Change> {
+    let mut differ = Differ {
+        old,
+        new,
+        visited: Default::default(),
+        changes: Default::default(),
+    };
+    differ.compare(old_root, new_root, String::new());
+    differ.changes
+}
+
+struct Differ<'a> {
+    old: &'a BTreeMap<SchemaRef, CanonicalSchemalet>,
+    new: &'a BTreeMap<SchemaRef, CanonicalSchemalet>,
+    /// Pairs we've already compared; this is how we deal with cycles.
+    visited: BTreeSet<(SchemaRef, SchemaRef)>,
+    changes: Vec<Change>,
+}
+
+/// The values a schema accepts, grouped by JSON type. Each value is paired
+/// with the id of the schemalet from which it came.
+#[derive(Default)]
+struct Alternatives<'a> {
+    anything: bool,
+    values: BTreeMap<SchemaletType, Vec<(SchemaRef, &'a SchemaletValue)>>,
+    constants: BTreeMap<SchemaletType, Vec<&'a serde_json::Value>>,
+}
+
+impl Alternatives<'_> {
+    fn types(&self) -> BTreeSet<SchemaletType> {
+        self.values
+            .keys()
+            .chain(self.constants.keys())
+            .cloned()
+            .collect()
+    }
+}
+
+fn alternatives<'a>(
+    graph: &'a BTreeMap<SchemaRef, CanonicalSchemalet>,
+    id: &SchemaRef,
+    out: &mut Alternatives<'a>,
+) {
+    let Some((resolved_id, schemalet)) = resolve(graph, id) else {
+        return;
+    };
+    match &schemalet.details {
+        CanonicalSchemaletDetails::Anything => out.anything = true,
+        CanonicalSchemaletDetails::Nothing => (),
+        CanonicalSchemaletDetails::Constant(value) => out
+            .constants
+            .entry(value_type(value))
+            .or_default()
+            .push(value),
+        CanonicalSchemaletDetails::ExclusiveOneOf { subschemas, .. } => {
+            for subschema in subschemas {
+                alternatives(graph, subschema, out);
+            }
+        }
+        CanonicalSchemaletDetails::Value(value) => out
+            .values
+            .entry(schemalet.get_type().unwrap())
+            .or_default()
+            .push((resolved_id, value)),
+        CanonicalSchemaletDetails::Reference(_) | CanonicalSchemaletDetails::Note(_) => {
+            unreachable!("resolved above")
+        }
+    }
+}
+
+fn value_type(value: &serde_json::Value) -> SchemaletType {
+    match value {
+        serde_json::Value::Null => SchemaletType::Null,
+        serde_json::Value::Bool(_) => SchemaletType::Boolean,
+        serde_json::Value::Number(n) if n.is_i64() || n.is_u64() => SchemaletType::Integer,
+        serde_json::Value::Number(_) => SchemaletType::Number,
+        serde_json::Value::String(_) => SchemaletType::String,
+        serde_json::Value::Array(_) => SchemaletType::Array,
+        serde_json::Value::Object(_) => SchemaletType::Object,
+    }
+}
+
+/// The shape of a value independent of the ids of its subschemas; for
+/// objects, this includes the values of constant properties (i.e. tags) that
+/// typically distinguish the variants of a `oneOf`.
+fn value_shape(graph: &BTreeMap<SchemaRef, CanonicalSchemalet>, value: &SchemaletValue) -> String {
+    match value {
+        SchemaletValue::Object(object) => {
+            let properties = object
+                .properties
+                .iter()
+                .map(|(name, id)| {
+                    let constant =
+                        resolve(graph, id).and_then(|(_, schemalet)| match &schemalet.details {
+                            CanonicalSchemaletDetails::Constant(value) => Some(value),
+                            _ => None,
+                        });
+                    (name, constant)
+                })
+                .collect::<BTreeMap<_, _>>();
+            serde_json::json!({
+                "properties": properties,
+                "required": object.required,
+            })
+            .to_string()
+        }
+        value => {
+            let mut details = CanonicalSchemaletDetails::Value(value.clone());
+            for child in details.children_mut() {
+                *child = SchemaRef::Internal(String::new());
+            }
+            serde_json::to_string(&details).unwrap()
+        }
+    }
+}
+
+fn shared_properties(old: &SchemaletValue, new: &SchemaletValue) -> usize {
+    match (old, new) {
+        (SchemaletValue::Object(old), SchemaletValue::Object(new)) => old
+            .properties
+            .keys()
+            .filter(|name| new.properties.contains_key(*name))
+            .count(),
+        _ => 0,
+    }
+}
+
+/// The schema for values of a property that `object` doesn't list: that of
+/// the first pattern that matches its name, otherwise that for additional (or
+/// unevaluated) properties; `None` permits anything.
+fn unlisted_property<'a>(object: &'a SchemaletValueObject, name: &str) -> Option<&'a SchemaRef> {
+    let pattern = object
+        .pattern_properties
+        .iter()
+        .flatten()
+        .find(|(pattern, _)| regex::Regex::new(pattern).is_ok_and(|re| re.is_match(name)))
+        .map(|(_, id)| id);
+    pattern
+        .or(object.additional_properties.as_ref())
+        .or(object.unevaluated_properties.as_ref())
+}
+
+/// Append a JSON pointer segment, escaping as per RFC 6901.
+fn pointer(location: &str, segment: &str) -> String {
+    format!(
+        "{location}/{}",
+        segment.replace('~', "~0").replace('/', "~1")
+    )
+}
+
+impl Differ<'_> {
+    fn push(&mut self, location: &str, kind: ChangeKind, direction: Direction) {
+        self.changes.push(Change::new(location, kind, direction));
+    }
+
+    fn compare(&mut self, old_id: &SchemaRef, new_id: &SchemaRef, location: String) {
+        if !self.visited.insert((old_id.clone(), new_id.clone())) {
+            return;
+        }
+
+        let mut old = Alternatives::default();
+        alternatives(self.old, old_id, &mut old);
+        let mut new = Alternatives::default();
+        alternatives(self.new, new_id, &mut new);
+
+        match (old.anything, new.anything) {
+            (true, true) => return,
+            (true, false) => {
+                self.push(&location, ChangeKind::Constrained, Direction::Narrowed);
+                return;
+            }
+            (false, true) => {
+                self.push(&location, ChangeKind::Unconstrained, Direction::Widened);
+                return;
+            }
+            (false, false) => (),
+        }
+
+        let old_types = old.types();
+        let new_types = new.types();
+
+        let removed = old_types
+            .difference(&new_types)
+            .cloned()
+            .collect::<Vec<_>>();
+        let added = new_types
+            .difference(&old_types)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // Integers are a subset of numbers so going from one to the other is
+        // merely a widening or narrowing rather than a total change.
+        let number_widened =
+            removed.contains(&SchemaletType::Integer) && added.contains(&SchemaletType::Number);
+        let number_narrowed =
+            removed.contains(&SchemaletType::Number) && added.contains(&SchemaletType::Integer);
+        let (removed, added) = if number_widened || number_narrowed {
+            let numeric = |typ: &SchemaletType| {
+                !matches!(typ, SchemaletType::Integer | SchemaletType::Number)
+            };
+            let direction = if number_widened {
+                Direction::Widened
+            } else {
+                Direction::Narrowed
+            };
+            let (from, to) = if number_widened {
+                (SchemaletType::Integer, SchemaletType::Number)
+            } else {
+                (SchemaletType::Number, SchemaletType::Integer)
+            };
+            self.push(
+                &location,
+                ChangeKind::Constraint {
+                    keyword: "type",
+                    old: Some(serde_json::to_value(&from).unwrap()),
+                    new: Some(serde_json::to_value(&to).unwrap()),
+                },
+                direction,
+            );
+            (
+                removed.into_iter().filter(numeric).collect::<Vec<_>>(),
+                added.into_iter().filter(numeric).collect::<Vec<_>>(),
+            )
+        } else {
+            (removed, added)
+        };
+
+        if !removed.is_empty() {
+            self.push(
+                &location,
+                ChangeKind::TypesRemoved(removed),
+                Direction::Narrowed,
+            );
+        }
+        if !added.is_empty() {
+            self.push(&location, ChangeKind::TypesAdded(added), Direction::Widened);
+        }
+
+        for typ in old_types.intersection(&new_types) {
+            self.compare_type(typ, &old, &new, &location);
+        }
+    }
+
+    fn compare_type(
+        &mut self,
+        typ: &SchemaletType,
+        old: &Alternatives<'_>,
+        new: &Alternatives<'_>,
+        location: &str,
+    ) {
+        let old_values = old.values.get(typ);
+        let new_values = new.values.get(typ);
+        let old_constants = old.constants.get(typ);
+        let new_constants = new.constants.get(typ);
+
+        match (old_values, new_values) {
+            (Some(_), None) => self.push(
+                location,
+                ChangeKind::NowEnumerated(typ.clone()),
+                Direction::Narrowed,
+            ),
+            (None, Some(_)) => self.push(
+                location,
+                ChangeKind::NoLongerEnumerated(typ.clone()),
+                Direction::Widened,
+            ),
+            (Some(old_values), Some(new_values)) => {
+                let (pairs, old_unmatched, new_unmatched) =
+                    self.match_variants(old_values, new_values);
+                for (old_value, new_value) in pairs {
+                    self.compare_value(old_value, new_value, location);
+                }
+                let direction = match (old_unmatched, new_unmatched) {
+                    (false, false) => None,
+                    (true, false) => Some(Direction::Narrowed),
+                    (false, true) => Some(Direction::Widened),
+                    (true, true) => Some(Direction::Changed),
+                };
+                if let Some(direction) = direction {
+                    self.push(location, ChangeKind::Variants(typ.clone()), direction)
+                }
+            }
+            (None, None) => {
+                // Both are enumerations; compare the values.
+                let old_constants = old_constants.map(Vec::as_slice).unwrap_or_default();
+                let new_constants = new_constants.map(Vec::as_slice).unwrap_or_default();
+                let removed = old_constants
+                    .iter()
+                    .filter(|value| !new_constants.contains(value))
+                    .map(|value| (*value).clone())
+                    .collect::<Vec<_>>();
+                let added = new_constants
+                    .iter()
+                    .filter(|value| !old_constants.contains(value))
+                    .map(|value| (*value).clone())
+                    .collect::<Vec<_>>();
+                if !removed.is_empty() {
+                    self.push(
+                        &pointer(location, "enum"),
+                        ChangeKind::ValuesRemoved(removed),
+                        Direction::Narrowed,
+                    );
+                }
+                if !added.is_empty() {
+                    self.push(
+                        &pointer(location, "enum"),
+                        ChangeKind::ValuesAdded(added),
+                        Direction::Widened,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Pair up the variants of a single type: first those that are the very
+    /// same schemalet, then those of the same shape, and then those that
+    /// share the most properties; any that remain are paired in order. We
+    /// don't pair by position alone so that reordering the variants of a
+    /// `oneOf` isn't a change. Also returns whether any old or new variants
+    /// were left unpaired.
+    fn match_variants<'v>(
+        &self,
+        old: &'v [(SchemaRef, &'v SchemaletValue)],
+        new: &'v [(SchemaRef, &'v SchemaletValue)],
+    ) -> (Vec<(&'v SchemaletValue, &'v SchemaletValue)>, bool, bool) {
+        let mut old_left = (0..old.len()).collect::<Vec<_>>();
+        let mut new_left = (0..new.len()).collect::<Vec<_>>();
+        let mut pairs = Vec::new();
+
+        let old_shapes = old
+            .iter()
+            .map(|(_, value)| value_shape(self.old, value))
+            .collect::<Vec<_>>();
+        let new_shapes = new
+            .iter()
+            .map(|(_, value)| value_shape(self.new, value))
+            .collect::<Vec<_>>();
+
+        let mut pair_by = |matches: &dyn Fn(usize, usize) -> bool| {
+            old_left.retain(|&ii| {
+                let Some(pos) = new_left.iter().position(|&jj| matches(ii, jj)) else {
+                    return true;
+                };
+                pairs.push((ii, new_left.remove(pos)));
+                false
+            });
+        };
+        pair_by(&|ii, jj| old[ii].0 == new[jj].0);
+        pair_by(&|ii, jj| old_shapes[ii] == new_shapes[jj]);
+
+        // Greedily pair the variants with the most properties in common.
+        loop {
+            let best = old_left
+                .iter()
+                .flat_map(|&ii| new_left.iter().map(move |&jj| (ii, jj)))
+                .map(|(ii, jj)| (shared_properties(old[ii].1, new[jj].1), ii, jj))
+                .filter(|(shared, _, _)| *shared > 0)
+                .max_by_key(|(shared, ii, jj)| (*shared, std::cmp::Reverse((*ii, *jj))));
+            let Some((_, ii, jj)) = best else {
+                break;
+            };
+            old_left.retain(|&kk| kk != ii);
+            new_left.retain(|&kk| kk != jj);
+            pairs.push((ii, jj));
+        }
+
+        let rest = old_left.len().min(new_left.len());
+        pairs.extend(old_left.drain(..rest).zip(new_left.drain(..rest)));
+        pairs.sort();
+
+        let pairs = pairs
+            .into_iter()
+            .map(|(ii, jj)| (old[ii].1, new[jj].1))
+            .collect();
+        (pairs, !old_left.is_empty(), !new_left.is_empty())
+    }
+
+    fn compare_value(&mut self, old: &SchemaletValue, new: &SchemaletValue, location: &str) {
+        match (old, new) {
+            (SchemaletValue::Object(old), SchemaletValue::Object(new)) => {
+                self.compare_object(old, new, location)
+            }
+            (SchemaletValue::Array(old), SchemaletValue::Array(new)) => {
+                self.compare_array(old, new, location)
+            }
+            (
+                SchemaletValue::String {
+                    pattern: old_pattern,
+                    format: old_format,
+                    min_length: old_min_length,
+                    max_length: old_max_length,
+                },
+                SchemaletValue::String {
+                    pattern: new_pattern,
+                    format: new_format,
+                    min_length: new_min_length,
+                    max_length: new_max_length,
+                },
+            ) => {
+                self.constraint(location, "pattern", old_pattern, new_pattern);
+                self.constraint(location, "format", old_format, new_format);
+                self.lower_bound(location, "minLength", *old_min_length, *new_min_length);
+                self.upper_bound(location, "maxLength", *old_max_length, *new_max_length);
+            }
+            (
+                SchemaletValue::Integer {
+                    minimum: old_minimum,
+                    exclusive_minimum: old_exclusive_minimum,
+                    maximum: old_maximum,
+                    exclusive_maximum: old_exclusive_maximum,
+                    ..
+                },
+                SchemaletValue::Integer {
+                    minimum: new_minimum,
+                    exclusive_minimum: new_exclusive_minimum,
+                    maximum: new_maximum,
+                    exclusive_maximum: new_exclusive_maximum,
+                    ..
+                },
+            )
+            | (
+                SchemaletValue::Number {
+                    minimum: old_minimum,
+                    exclusive_minimum: old_exclusive_minimum,
+                    maximum: old_maximum,
+                    exclusive_maximum: old_exclusive_maximum,
+                },
+                SchemaletValue::Number {
+                    minimum: new_minimum,
+                    exclusive_minimum: new_exclusive_minimum,
+                    maximum: new_maximum,
+                    exclusive_maximum: new_exclusive_maximum,
+                },
+            ) => {
+                self.lower_bound(
+                    location,
+                    "minimum",
+                    old_minimum.as_ref().map(NumberBound),
+                    new_minimum.as_ref().map(NumberBound),
+                );
+                self.lower_bound(
+                    location,
+                    "exclusiveMinimum",
+                    old_exclusive_minimum.as_ref().map(NumberBound),
+                    new_exclusive_minimum.as_ref().map(NumberBound),
+                );
+                self.upper_bound(
+                    location,
+                    "maximum",
+                    old_maximum.as_ref().map(NumberBound),
+                    new_maximum.as_ref().map(NumberBound),
+                );
+                self.upper_bound(
+                    location,
+                    "exclusiveMaximum",
+                    old_exclusive_maximum.as_ref().map(NumberBound),
+                    new_exclusive_maximum.as_ref().map(NumberBound),
+                );
+                if let (
+                    SchemaletValue::Integer {
+                        format: old_format, ..
+                    },
+                    SchemaletValue::Integer {
+                        format: new_format, ..
+                    },
+                ) = (old, new)
+                {
+                    self.constraint(location, "format", old_format, new_format);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn compare_object(
+        &mut self,
+        old: &SchemaletValueObject,
+        new: &SchemaletValueObject,
+        location: &str,
+    ) {
+        let properties = pointer(location, "properties");
+        let names = old
+            .properties
+            .keys()
+            .chain(new.properties.keys())
+            .collect::<BTreeSet<_>>();
+
+        for name in names {
+            let prop_location = pointer(&properties, name);
+            let old_required = old.required.contains(name);
+            let new_required = new.required.contains(name);
+
+            match (old.properties.get(name), new.properties.get(name)) {
+                (None, None) => unreachable!(),
+                // The name is now governed by additionalProperties (or the
+                // like); objects without it are newly valid if it was
+                // required.
+                (Some(old_id), None) => {
+                    let (narrowed, widened) =
+                        self.values_direction(Some(old_id), unlisted_property(new, name));
+                    self.push(
+                        &prop_location,
+                        ChangeKind::PropertyRemoved { name: name.clone() },
+                        Direction::of(narrowed, widened || old_required),
+                    );
+                }
+                // The name was governed by additionalProperties (or the like);
+                // objects without it are newly invalid if it's required.
+                (None, Some(new_id)) => {
+                    let (narrowed, widened) =
+                        self.values_direction(unlisted_property(old, name), Some(new_id));
+                    let direction = Direction::of(narrowed || new_required, widened);
+                    self.push(
+                        &prop_location,
+                        ChangeKind::PropertyAdded {
+                            name: name.clone(),
+                            required: new_required,
+                        },
+                        direction,
+                    );
+                }
+                (Some(old_id), Some(new_id)) => {
+                    match (old_required, new_required) {
+                        (false, true) => self.push(
+                            &prop_location,
+                            ChangeKind::PropertyNowRequired { name: name.clone() },
+                            Direction::Narrowed,
+                        ),
+                        (true, false) => self.push(
+                            &prop_location,
+                            ChangeKind::PropertyNowOptional { name: name.clone() },
+                            Direction::Widened,
+                        ),
+                        _ => (),
+                    }
+                    self.compare(old_id, new_id, prop_location);
+                }
+            }
+        }
+
+        self.optional_subschema(
+            location,
+            "additionalProperties",
+            old.additional_properties.as_ref(),
+            new.additional_properties.as_ref(),
+        );
+        self.optional_subschema(
+            location,
+            "propertyNames",
+            old.property_names.as_ref(),
+            new.property_names.as_ref(),
+        );
+        self.optional_subschema(
+            location,
+            "unevaluatedProperties",
+            old.unevaluated_properties.as_ref(),
+            new.unevaluated_properties.as_ref(),
+        );
+
+        let empty = BTreeMap::new();
+        let old_patterns = old.pattern_properties.as_ref().unwrap_or(&empty);
+        let new_patterns = new.pattern_properties.as_ref().unwrap_or(&empty);
+        let pattern_properties = pointer(location, "patternProperties");
+        let patterns = old_patterns
+            .keys()
+            .chain(new_patterns.keys())
+            .collect::<BTreeSet<_>>();
+        for pattern in patterns {
+            self.optional_subschema(
+                &pattern_properties,
+                pattern,
+                old_patterns.get(pattern),
+                new_patterns.get(pattern),
+            );
+        }
+    }
+
+    fn compare_array(
+        &mut self,
+        old: &SchemaletValueArray,
+        new: &SchemaletValueArray,
+        location: &str,
+    ) {
+        let no_items = Vec::new();
+        let old_prefix = old.prefix_items.as_ref().unwrap_or(&no_items);
+        let new_prefix = new.prefix_items.as_ref().unwrap_or(&no_items);
+        let prefix_items = pointer(location, "prefixItems");
+        for ii in 0..old_prefix.len().max(new_prefix.len()) {
+            self.optional_subschema(
+                &prefix_items,
+                &ii.to_string(),
+                old_prefix.get(ii),
+                new_prefix.get(ii),
+            );
+        }
+
+        self.optional_subschema(location, "items", old.items.as_ref(), new.items.as_ref());
+
+        self.lower_bound(location, "minItems", old.min_items, new.min_items);
+        self.upper_bound(location, "maxItems", old.max_items, new.max_items);
+        let old_unique = old.unique_items.unwrap_or(false);
+        let new_unique = new.unique_items.unwrap_or(false);
+        self.lower_bound(location, "uniqueItems", Some(old_unique), Some(new_unique));
+    }
+
+    /// Whether the values permitted by `new` exclude some of those permitted
+    /// by `old` (narrowed) and whether they include others (widened); an
+    /// absent schema permits anything.
+    fn values_direction(&self, old: Option<&SchemaRef>, new: Option<&SchemaRef>) -> (bool, bool) {
+        let mut differ = Differ {
+            old: self.old,
+            new: self.new,
+            visited: Default::default(),
+            changes: Default::default(),
+        };
+        match (old, new) {
+            (None, None) => (),
+            (Some(old), None) => differ.compare_to_anything(self.old, old, Direction::Widened),
+            (None, Some(new)) => differ.compare_to_anything(self.new, new, Direction::Narrowed),
+            (Some(old), Some(new)) => differ.compare(old, new, String::new()),
+        }
+        differ
+            .changes
+            .iter()
+            .fold((false, false), |(narrowed, widened), change| {
+                match change.direction {
+                    Direction::Widened => (narrowed, true),
+                    Direction::Narrowed => (true, widened),
+                    Direction::Changed => (true, true),
+                    Direction::Neutral => (narrowed, widened),
+                }
+            })
+    }
+
+    /// Note the change between a schema and one that permits anything.
+    fn compare_to_anything(
+        &mut self,
+        graph: &BTreeMap<SchemaRef, CanonicalSchemalet>,
+        id: &SchemaRef,
+        direction: Direction,
+    ) {
+        let mut alts = Alternatives::default();
+        alternatives(graph, id, &mut alts);
+        if !alts.anything {
+            self.push("", ChangeKind::Unconstrained, direction);
+        }
+    }
+
+    /// Compare subschemas that may be absent; an absent subschema permits
+    /// anything.
+    fn optional_subschema(
+        &mut self,
+        location: &str,
+        keyword: &str,
+        old: Option<&SchemaRef>,
+        new: Option<&SchemaRef>,
+    ) {
+        let location = pointer(location, keyword);
+        match (old, new) {
+            (None, None) => (),
+            (Some(_), None) => self.push(&location, ChangeKind::Unconstrained, Direction::Widened),
+            (None, Some(_)) => self.push(&location, ChangeKind::Constrained, Direction::Narrowed),
+            (Some(old), Some(new)) => self.compare(old, new, location),
+        }
+    }
+
+    /// Compare constraints that are either present or absent and that are
+    /// otherwise incomparable. Changes are reported at the keyword.
+    fn constraint<T>(
+        &mut self,
+        location: &str,
+        keyword: &'static str,
+        old: &Option<T>,
+        new: &Option<T>,
+    ) where
+        T: PartialEq + serde::Serialize,
+    {
+        let direction = match (old, new) {
+            (None, None) => return,
+            (Some(a), Some(b)) if a == b => return,
+            (Some(_), Some(_)) => Direction::Changed,
+            (None, Some(_)) => Direction::Narrowed,
+            (Some(_), None) => Direction::Widened,
+        };
+        self.push(
+            &pointer(location, keyword),
+            ChangeKind::Constraint {
+                keyword,
+                old: old.as_ref().map(|v| serde_json::to_value(v).unwrap()),
+                new: new.as_ref().map(|v| serde_json::to_value(v).unwrap()),
+            },
+            direction,
+        );
+    }
+
+    /// Compare constraints where a higher value is more restrictive.
+    fn lower_bound<T>(
+        &mut self,
+        location: &str,
+        keyword: &'static str,
+        old: Option<T>,
+        new: Option<T>,
+    ) where
+        T: PartialOrd + serde::Serialize,
+    {
+        self.bound(location, keyword, old, new, true)
+    }
+
+    /// Compare constraints where a lower value is more restrictive.
+    fn upper_bound<T>(
+        &mut self,
+        location: &str,
+        keyword: &'static str,
+        old: Option<T>,
+        new: Option<T>,
+    ) where
+        T: PartialOrd + serde::Serialize,
+    {
+        self.bound(location, keyword, old, new, false)
+    }
+
+    /// Report a change to a bound at its keyword so that changes to several
+    /// bounds of one schema can be told apart.
+    fn bound<T>(
+        &mut self,
+        location: &str,
+        keyword: &'static str,
+        old: Option<T>,
+        new: Option<T>,
+        lower: bool,
+    ) where
+        T: PartialOrd + serde::Serialize,
+    {
+        let direction = match (&old, &new) {
+            (None, None) => return,
+            (Some(a), Some(b)) if a == b => return,
+            (Some(a), Some(b)) if (a < b) == lower => Direction::Narrowed,
+            (Some(_), Some(_)) => Direction::Widened,
+            (None, Some(_)) => Direction::Narrowed,
+            (Some(_), None) => Direction::Widened,
+        };
+        self.push(
+            &pointer(location, keyword),
+            ChangeKind::Constraint {
+                keyword,
+                old: old.map(|v| serde_json::to_value(v).unwrap()),
+                new: new.map(|v| serde_json::to_value(v).unwrap()),
+            },
+            direction,
+        );
+    }
+}
+
+/// A numeric bound compared by value: integers exactly (including those
+/// beyond the range of i64), everything else as floating point.
+#[derive(serde::Serialize)]
+#[serde(transparent)]
+struct NumberBound<'a>(&'a serde_json::Number);
+
+impl NumberBound<'_> {
+    fn as_i128(&self) -> Option<i128> {
+        self.0
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| self.0.as_u64().map(i128::from))
+    }
+}
+
+impl PartialEq for NumberBound<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(std::cmp::Ordering::Equal)
+    }
+}
+
+impl PartialOrd for NumberBound<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self.as_i128(), other.as_i128()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            _ => self.0.as_f64()?.partial_cmp(&other.0.as_f64()?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{typify::Typify, Bundle};
+
+    use super::{diff, ChangeKind, Compatibility, Direction};
+
+    #[test]
+    fn test_diff_versions() {
+        let v1 = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://example.com/v1",
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "kind": { "enum": ["a", "b", "c"] },
+                "age": { "type": "integer" }
+            },
+            "required": ["name"]
+        });
+        let v2 = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://example.com/v2",
+            "type": "object",
+            "properties": {
+                "name": { "type": ["string", "null"] },
+                "kind": { "enum": ["a", "b"] },
+                "id": { "type": "string" }
+            },
+            "required": ["name", "id"]
+        });
+
+        let mut bundle = Bundle::default();
+        bundle.add_content(&v1.to_string()).unwrap();
+        bundle.add_content(&v2.to_string()).unwrap();
+        let mut typify = Typify::new_with_bundle(bundle);
+        let old = typify.normalize_by_id("https://example.com/v1").unwrap();
+        let new = typify.normalize_by_id("https://example.com/v2").unwrap();
+        let graph = typify.canonical_graph();
+
+        let changes = diff(graph, &old, graph, &new)
+            .into_iter()
+            .map(|change| {
+                (
+                    change.location.clone(),
+                    change.kind.clone(),
+                    change.readers,
+                    change.writers,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "/properties/age".to_string(),
+                    ChangeKind::PropertyRemoved {
+                        name: "age".to_string()
+                    },
+                    Compatibility::Breaking,
+                    Compatibility::Compatible,
+                ),
+                (
+                    "/properties/id".to_string(),
+                    ChangeKind::PropertyAdded {
+                        name: "id".to_string(),
+                        required: true,
+                    },
+                    Compatibility::Compatible,
+                    Compatibility::Breaking,
+                ),
+                (
+                    "/properties/kind/enum".to_string(),
+                    ChangeKind::ValuesRemoved(vec![json!("c")]),
+                    Compatibility::Compatible,
+                    Compatibility::Breaking,
+                ),
+                (
+                    "/properties/name".to_string(),
+                    ChangeKind::TypesAdded(vec![crate::schemalet::SchemaletType::Null]),
+                    Compatibility::Breaking,
+                    Compatibility::Compatible,
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_reordered_variants() {
+        let circle = json!({
+            "type": "object",
+            "properties": {
+                "kind": { "const": "circle" },
+                "radius": { "type": "integer" }
+            },
+            "required": ["kind"]
+        });
+        let square = |required: &[&str]| {
+            json!({
+                "type": "object",
+                "properties": {
+                    "kind": { "const": "square" },
+                    "side": { "type": "integer" }
+                },
+                "required": required
+            })
+        };
+        let v1 = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://example.com/v1",
+            "oneOf": [circle, square(&["kind"])]
+        });
+        let v2 = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://example.com/v2",
+            "oneOf": [square(&["kind", "side"]), circle]
+        });
+        let v3 = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://example.com/v3",
+            "oneOf": [square(&["kind"]), circle]
+        });
+
+        let mut bundle = Bundle::default();
+        bundle.add_content(v1.to_string()).unwrap();
+        bundle.add_content(v2.to_string()).unwrap();
+        bundle.add_content(v3.to_string()).unwrap();
+        let mut typify = Typify::new_with_bundle(bundle);
+        let v1 = typify.normalize_by_id("https://example.com/v1").unwrap();
+        let v2 = typify.normalize_by_id("https://example.com/v2").unwrap();
+        let v3 = typify.normalize_by_id("https://example.com/v3").unwrap();
+        let graph = typify.canonical_graph();
+
+        // Merely reordering the variants is no change at all.
+        assert_eq!(diff(graph, &v1, graph, &v3), vec![]);
+
+        // A change to one variant is reported as such.
+        let changes = diff(graph, &v1, graph, &v2)
+            .into_iter()
+            .map(|change| (change.location, change.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![(
+                "/properties/side".to_string(),
+                ChangeKind::PropertyNowRequired {
+                    name: "side".to_string()
+                },
+            )]
+        );
+    }
+
+    #[test]
+    fn test_diff_wide_and_fractional_bounds() {
+        let v1 = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://example.com/v1",
+            "type": "object",
+            "properties": {
+                "count": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 18446744073709551615u64
+                },
+                "ratio": { "type": "number", "exclusiveMinimum": 0.5 }
+            }
+        });
+        let v2 = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://example.com/v2",
+            "type": "object",
+            "properties": {
+                "count": { "type": "integer", "minimum": 1, "maximum": 255 },
+                "ratio": { "type": "number", "exclusiveMinimum": 0.25 }
+            }
+        });
+
+        let mut bundle = Bundle::default();
+        bundle.add_content(v1.to_string()).unwrap();
+        bundle.add_content(v2.to_string()).unwrap();
+        let mut typify = Typify::new_with_bundle(bundle);
+        let old = typify.normalize_by_id("https://example.com/v1").unwrap();
+        let new = typify.normalize_by_id("https://example.com/v2").unwrap();
+        let graph = typify.canonical_graph();
+
+        let changes = diff(graph, &old, graph, &new)
+            .into_iter()
+            .map(|change| (change.location, change.kind, change.direction))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "/properties/count/minimum".to_string(),
+                    ChangeKind::Constraint {
+                        keyword: "minimum",
+                        old: Some(json!(0)),
+                        new: Some(json!(1)),
+                    },
+                    Direction::Narrowed,
+                ),
+                (
+                    "/properties/count/maximum".to_string(),
+                    ChangeKind::Constraint {
+                        keyword: "maximum",
+                        old: Some(json!(18446744073709551615u64)),
+                        new: Some(json!(255)),
+                    },
+                    Direction::Narrowed,
+                ),
+                (
+                    "/properties/ratio/exclusiveMinimum".to_string(),
+                    ChangeKind::Constraint {
+                        keyword: "exclusiveMinimum",
+                        old: Some(json!(0.5)),
+                        new: Some(json!(0.25)),
+                    },
+                    Direction::Widened,
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_closed_and_open_objects() {
+        let version =
+            |id: &str, properties: serde_json::Value, additional: Option<serde_json::Value>| {
+                let mut schema = json!({
+                    "$schema": "https://json-schema.org/draft/2020-12/schema",
+                    "$id": format!("https://example.com/{id}"),
+                    "type": "object",
+                    "properties": properties,
+                    "required": ["name"]
+                });
+                if let Some(additional) = additional {
+                    schema["additionalProperties"] = additional;
+                }
+                schema
+            };
+        let name = json!({ "type": "string" });
+        let nickname = json!({ "type": "string" });
+        let age = json!({ "type": "integer" });
+
+        let mut bundle = Bundle::default();
+        for schema in [
+            version(
+                "closed",
+                json!({ "name": name, "nickname": nickname }),
+                Some(json!(false)),
+            ),
+            version(
+                "closed-without",
+                json!({ "name": name }),
+                Some(json!(false)),
+            ),
+            version(
+                "closed-with-age",
+                json!({ "name": name, "nickname": nickname, "age": age }),
+                Some(json!(false)),
+            ),
+            version("open", json!({ "name": name, "nickname": nickname }), None),
+            version("open-without", json!({ "name": name }), None),
+            version(
+                "open-with-age",
+                json!({ "name": name, "nickname": nickname, "age": age }),
+                None,
+            ),
+            version(
+                "strings",
+                json!({ "name": name }),
+                Some(json!({ "type": "string" })),
+            ),
+        ] {
+            bundle.add_content(schema.to_string()).unwrap();
+        }
+        let mut typify = Typify::new_with_bundle(bundle);
+        let mut roots = std::collections::BTreeMap::new();
+        for id in [
+            "closed",
+            "closed-without",
+            "closed-with-age",
+            "open",
+            "open-without",
+            "open-with-age",
+            "strings",
+        ] {
+            let root = typify
+                .normalize_by_id(format!("https://example.com/{id}"))
+                .unwrap();
+            roots.insert(id, root);
+        }
+        let graph = typify.canonical_graph();
+
+        let property_changes = |old: &str, new: &str| {
+            diff(graph, &roots[old], graph, &roots[new])
+                .into_iter()
+                .filter(|change| {
+                    matches!(
+                        change.kind,
+                        ChangeKind::PropertyAdded { .. } | ChangeKind::PropertyRemoved { .. }
+                    )
+                })
+                .map(|change| (change.location, change.direction))
+                .collect::<Vec<_>>()
+        };
+        let nickname = || "/properties/nickname".to_string();
+        let age = || "/properties/age".to_string();
+
+        // From a closed object, removing a property rejects objects that
+        // have it; adding one accepts objects that have it.
+        assert_eq!(
+            property_changes("closed", "closed-without"),
+            [(nickname(), Direction::Narrowed)]
+        );
+        assert_eq!(
+            property_changes("closed", "closed-with-age"),
+            [(age(), Direction::Widened)]
+        );
+
+        // From an open object, removing a property accepts any value for it;
+        // adding one rejects values of other types.
+        assert_eq!(
+            property_changes("open", "open-without"),
+            [(nickname(), Direction::Widened)]
+        );
+        assert_eq!(
+            property_changes("open", "open-with-age"),
+            [(age(), Direction::Narrowed)]
+        );
+
+        // Additional properties that accept what the property did.
+        assert_eq!(
+            property_changes("closed", "strings"),
+            [(nickname(), Direction::Neutral)]
+        );
+    }
+}
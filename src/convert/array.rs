@@ -1,36 +1,73 @@
 use crate::{
     convert::{Converter, GottenStuff},
-    schemalet::{SchemaRef, SchemaletValueArray},
-    typespace::{NameBuilder, Type},
+    schemalet::{SchemaRef, SchemaletMetadata, SchemaletValueArray},
+    typespace::{NameBuilder, Type, TypeTupleStruct},
 };
 
-impl Converter {
+/// Serde only implements its traits for arrays up to this length; longer
+/// arrays become a Vec.
+const MAX_ARRAY_LEN: u64 = 32;
+
+impl Converter<'_> {
     pub(crate) fn convert_array(
         &self,
         name: NameBuilder,
-        metadata: &crate::schemalet::SchemaletMetadata,
+        metadata: &SchemaletMetadata,
         array: &SchemaletValueArray,
     ) -> Type {
-        match array {
-            SchemaletValueArray {
+        // `items: false` means that there are no items (beyond the prefix).
+        let items = array.items.as_ref().filter(|items| !self.is_nothing(items));
+
+        match (array, items) {
+            // Note that the draft-07 array form of `items` (along with
+            // `additionalItems`) has the same meaning as `prefixItems` (along
+            // with `items`) so we handle them identically.
+            (
+                SchemaletValueArray {
+                    prefix_items: Some(prefix_items),
+                    max_items,
+                    ..
+                },
                 items,
-                prefix_items,
-                max_items: Some(max_items),
-                min_items: Some(min_items),
-                unique_items: None,
-            } if max_items == min_items && *max_items > 0 => {
-                // TODO
-                // This is a tuple type
-                todo!()
+            ) if !prefix_items.is_empty() => {
+                self.convert_tuple(name, metadata, prefix_items, items, *max_items)
+            }
+
+            (
+                SchemaletValueArray {
+                    prefix_items: None,
+                    max_items: Some(max_items),
+                    min_items: Some(min_items),
+                    unique_items: None | Some(false),
+                    ..
+                },
+                Some(items),
+            ) if max_items == min_items && *max_items > 0 && *max_items <= MAX_ARRAY_LEN => {
+                let GottenStuff { id, .. } = self.resolve_and_get_stuff(items);
+                Type::Array(id.clone(), *max_items as usize)
+            }
+
+            // Whether this is a BTreeSet, a HashSet, or a Vec that checks for
+            // uniqueness depends on the traits that the item type implements,
+            // which we'll only know when the typespace is finalized.
+            (
+                SchemaletValueArray {
+                    prefix_items: None,
+                    unique_items: Some(true),
+                    ..
+                },
+                Some(items),
+            ) => {
+                let GottenStuff { id, .. } = self.resolve_and_get_stuff(items);
+                Type::Set(id.clone())
             }
 
-            SchemaletValueArray {
-                items: Some(items),
-                prefix_items: None,
-                max_items,
-                min_items,
-                unique_items,
-            } => {
+            (
+                SchemaletValueArray {
+                    prefix_items: None, ..
+                },
+                Some(items),
+            ) => {
                 let GottenStuff { id, .. } = self.resolve_and_get_stuff(items);
                 Type::Vec(id.clone())
             }
@@ -43,4 +80,39 @@ impl Converter {
             }
         }
     }
+
+    fn convert_tuple(
+        &self,
+        name: NameBuilder,
+        metadata: &SchemaletMetadata,
+        prefix_items: &[SchemaRef],
+        items: Option<&SchemaRef>,
+        max_items: Option<u64>,
+    ) -> Type {
+        let prefix = prefix_items
+            .iter()
+            .map(|item| self.resolve_and_get_stuff(item).id.clone())
+            .collect::<Vec<_>>();
+
+        // TODO 10/19/2026
+        // Absent `items`, JSON Schema permits any trailing items; we ignore
+        // them (as we do `minItems` less than the prefix length) since a
+        // plain tuple is almost always what the author intended.
+        let rest = items
+            .filter(|_| max_items.is_none_or(|max_items| max_items > prefix.len() as u64))
+            .map(|items| self.resolve_and_get_stuff(items).id.clone());
+
+        match rest {
+            None => Type::Tuple(prefix),
+            // If every item is of the same type, the prefix is no different
+            // from the trailing items.
+            Some(rest) if prefix.iter().all(|id| *id == rest) => Type::Vec(rest),
+            Some(rest) => Type::TupleStruct(TypeTupleStruct::new(
+                name,
+                metadata.description.clone(),
+                prefix,
+                rest,
+            )),
+        }
+    }
 }
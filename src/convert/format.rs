@@ -0,0 +1,358 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    convert::Converter,
+    schemalet::SchemaletMetadata,
+    typespace::{
+        NameBuilder, StringFormatCheck, Type, TypeConstrainedString, TypeNative, TypeTrait,
+        TypeValidatedString,
+    },
+};
+
+/// A check, run when generating code, that a string (e.g. a default value)
+/// is valid for a format.
+pub type FormatCheck = fn(&str) -> bool;
+
+/// The Rust type to use for strings with a particular `format`.
+#[derive(Debug, Clone)]
+pub enum FormatType {
+    /// An existing type such as `::uuid::Uuid` that serializes to and
+    /// deserializes from a string. We assume it implements the comparison
+    /// traits along with `Clone` and `Debug`, but not `Copy` or `Default`.
+    /// Without a `check`, default values are left to the type's
+    /// `Deserialize` impl.
+    Native {
+        path: String,
+        check: Option<FormatCheck>,
+    },
+    /// A newtype around `String` whose values are checked by `validate`, a
+    /// Rust expression of type `fn(&str) -> bool` such as a path to a
+    /// function or a non-capturing closure. `check` must agree with it;
+    /// the built-in validators are written once for both.
+    Validated {
+        name: String,
+        validate: String,
+        check: FormatCheck,
+    },
+}
+
+/// Mapping from string formats to Rust types. Strings with formats that
+/// aren't in the table are just `String`.
+#[derive(Debug, Clone)]
+pub struct FormatTable {
+    formats: BTreeMap<String, FormatType>,
+}
+
+/// A validator for [`FormatType::Validated`] written once: the source of the
+/// closure for generated code along with the same closure to check values
+/// when generating it.
+macro_rules! validator {
+    (|$value:ident| $body:expr) => {
+        (
+            concat!("|", stringify!($value), ": &str| ", stringify!($body)),
+            (|$value: &str| $body) as FormatCheck,
+        )
+    };
+}
+
+impl Default for FormatTable {
+    fn default() -> Self {
+        let native = |format: &str, path: &str, check: FormatCheck| {
+            (
+                format.to_string(),
+                FormatType::Native {
+                    path: path.to_string(),
+                    check: Some(check),
+                },
+            )
+        };
+        let validated = |format: &str, name: &str, (validate, check): (&str, FormatCheck)| {
+            (
+                format.to_string(),
+                FormatType::Validated {
+                    name: name.to_string(),
+                    validate: validate.to_string(),
+                    check,
+                },
+            )
+        };
+
+        let formats = [
+            native("uuid", "::uuid::Uuid", is_uuid),
+            native(
+                "date-time",
+                "::chrono::DateTime<::chrono::offset::Utc>",
+                is_date_time,
+            ),
+            native("date", "::chrono::naive::NaiveDate", is_date),
+            native("time", "::chrono::naive::NaiveTime", is_time),
+            native("ipv4", "::std::net::Ipv4Addr", |value| {
+                value.parse::<std::net::Ipv4Addr>().is_ok()
+            }),
+            native("ipv6", "::std::net::Ipv6Addr", |value| {
+                value.parse::<std::net::Ipv6Addr>().is_ok()
+            }),
+            native("ip", "::std::net::IpAddr", |value| {
+                value.parse::<std::net::IpAddr>().is_ok()
+            }),
+            native("uri", "::url::Url", |value| url::Url::parse(value).is_ok()),
+            // This is a loose check; we just want to catch values that are
+            // obviously wrong.
+            validated(
+                "email",
+                "Email",
+                validator!(|value| matches!(
+                    value.split_once('@'),
+                    Some((local, domain))
+                        if !local.is_empty() && !domain.is_empty() && !domain.contains('@')
+                )),
+            ),
+            validated(
+                "hostname",
+                "Hostname",
+                validator!(|value| value.len() <= 253
+                    && value.split('.').all(|label| {
+                        !label.is_empty()
+                            && label.len() <= 63
+                            && !label.starts_with('-')
+                            && !label.ends_with('-')
+                            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                    })),
+            ),
+        ]
+        .into();
+
+        Self { formats }
+    }
+}
+
+impl FormatTable {
+    /// A table with no formats at all i.e. every string is a `String`.
+    pub fn empty() -> Self {
+        Self {
+            formats: Default::default(),
+        }
+    }
+
+    /// Add or replace the type for `format`.
+    pub fn insert(&mut self, format: impl ToString, typ: FormatType) -> &mut Self {
+        self.formats.insert(format.to_string(), typ);
+        self
+    }
+
+    /// Remove `format` so that strings with that format are just `String`.
+    pub fn remove(&mut self, format: &str) -> &mut Self {
+        self.formats.remove(format);
+        self
+    }
+
+    pub fn get(&self, format: &str) -> Option<&FormatType> {
+        self.formats.get(format)
+    }
+}
+
+impl Converter<'_> {
+    pub(crate) fn convert_string(
+        &self,
+        name: NameBuilder,
+        metadata: &SchemaletMetadata,
+        pattern: Option<&String>,
+        format: Option<&String>,
+        min_length: Option<u64>,
+        max_length: Option<u64>,
+    ) -> Type {
+        let constrained = pattern.is_some() || min_length.is_some() || max_length.is_some();
+
+        // A known format maps to its type unless there are other
+        // constraints, in which case we check the format along with them.
+        match (
+            format.and_then(|format| self.formats.get(format)),
+            constrained,
+        ) {
+            (Some(FormatType::Native { path, check }), false) => Type::Native(TypeNative {
+                check: *check,
+                ..TypeNative::new(
+                    path,
+                    TypeTrait::ALL.into_iter().filter(|type_trait| {
+                        !matches!(type_trait, TypeTrait::Copy | TypeTrait::Default)
+                    }),
+                )
+            }),
+            (
+                Some(FormatType::Validated {
+                    name,
+                    validate,
+                    check,
+                }),
+                false,
+            ) => Type::ValidatedString(TypeValidatedString::new(name, validate, *check)),
+            (format_type, true) => {
+                let format = format_type.map(|format_type| match format_type {
+                    // Native types deserialize from strings so that's how we
+                    // check them.
+                    FormatType::Native { path, check } => StringFormatCheck {
+                        format: format.unwrap().clone(),
+                        validate: format!(
+                            "|value: &str| <{path} as ::serde::Deserialize>::deserialize(\
+                             ::serde::de::value::StrDeserializer::<\
+                             ::serde::de::value::Error>::new(value)).is_ok()"
+                        ),
+                        check: *check,
+                    },
+                    FormatType::Validated {
+                        validate, check, ..
+                    } => StringFormatCheck {
+                        format: format.unwrap().clone(),
+                        validate: validate.clone(),
+                        check: Some(*check),
+                    },
+                });
+                Type::ConstrainedString(TypeConstrainedString::new(
+                    name,
+                    metadata.description.clone(),
+                    pattern.cloned(),
+                    min_length,
+                    max_length,
+                    format,
+                ))
+            }
+            (None, false) => Type::String,
+        }
+    }
+}
+
+/// e.g. `67e55044-10b1-426f-9247-bb680e5fe0c8`, in any of the forms that
+/// `uuid` parses: simple, hyphenated, braced or as a URN.
+fn is_uuid(value: &str) -> bool {
+    let value = value
+        .strip_prefix("urn:uuid:")
+        .or_else(|| value.strip_prefix('{')?.strip_suffix('}'))
+        .unwrap_or(value);
+    let groups = value.split('-').map(str::len).collect::<Vec<_>>();
+    (groups == [32] || groups == [8, 4, 4, 4, 12])
+        && value.chars().all(|c| c == '-' || c.is_ascii_hexdigit())
+}
+
+/// An RFC 3339 date-time e.g. `1985-04-12T23:20:50.52Z`.
+fn is_date_time(value: &str) -> bool {
+    let Some((date, time)) = value.split_once(['T', 't', ' ']) else {
+        return false;
+    };
+    let (time, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(ii) => time.split_at(ii),
+        None => return false,
+    };
+    let offset_ok = matches!(offset, "Z" | "z")
+        || offset[1..]
+            .split_once(':')
+            .is_some_and(|(hh, mm)| is_number(hh, 2, 0, 23) && is_number(mm, 2, 0, 59));
+    is_date(date) && is_time(time) && offset_ok
+}
+
+/// A full date e.g. `1985-04-12`.
+fn is_date(value: &str) -> bool {
+    let mut parts = value.split('-');
+    let (Some(year), Some(month), Some(day), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    if !is_number(year, 4, 0, 9999) || !is_number(month, 2, 1, 12) {
+        return false;
+    }
+    let year = year.parse::<u32>().unwrap();
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month.parse::<u32>().unwrap() {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    is_number(day, 2, 1, days)
+}
+
+/// A time without an offset e.g. `23:20:50.52`.
+fn is_time(value: &str) -> bool {
+    let (value, fraction) = value.split_once('.').unwrap_or((value, "0"));
+    let mut parts = value.split(':');
+    let (Some(hour), Some(minute), Some(second), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    // Allow for a leap second.
+    is_number(hour, 2, 0, 23)
+        && is_number(minute, 2, 0, 59)
+        && is_number(second, 2, 0, 60)
+        && !fraction.is_empty()
+        && fraction.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Exactly `width` digits in the given range.
+fn is_number(value: &str, width: usize, min: u32, max: u32) -> bool {
+    value.len() == width
+        && value.chars().all(|c| c.is_ascii_digit())
+        && (min..=max).contains(&value.parse().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_date, is_date_time, is_time, is_uuid, FormatTable, FormatType};
+
+    #[test]
+    fn test_format_checks() {
+        assert!(is_uuid("67e55044-10b1-426f-9247-bb680e5fe0c8"));
+        assert!(is_uuid("67e5504410b1426f9247bb680e5fe0c8"));
+        assert!(is_uuid("{67e55044-10b1-426f-9247-bb680e5fe0c8}"));
+        assert!(is_uuid("urn:uuid:67e55044-10b1-426f-9247-bb680e5fe0c8"));
+        assert!(!is_uuid("67e55044-10b1-426f-9247-bb680e5fe0c"));
+        assert!(!is_uuid("67e55044-10b1-426f-9247-bb680e5fe0cg"));
+
+        assert!(is_date("2024-02-29"));
+        assert!(!is_date("2023-02-29"));
+        assert!(!is_date("2024-13-01"));
+        assert!(!is_date("24-01-01"));
+        assert!(!is_date("2024-0002-01"));
+        assert!(!is_date("2024-02-0001"));
+
+        assert!(is_time("23:20:50"));
+        assert!(is_time("23:20:50.52"));
+        assert!(!is_time("24:00:00"));
+        assert!(!is_time("23:20"));
+        assert!(!is_time("0023:20:50"));
+        assert!(!is_time("23:0020:50"));
+
+        assert!(is_date_time("1985-04-12T23:20:50.52Z"));
+        assert!(is_date_time("1996-12-19T16:39:57-08:00"));
+        assert!(!is_date_time("1985-04-12T23:20:50"));
+        assert!(!is_date_time("1985-04-12"));
+        assert!(!is_date_time("1996-12-19T16:39:57-0008:00"));
+        assert!(!is_date_time("1996-12-19T16:39:57-08:0000"));
+
+        let formats = FormatTable::default();
+        let check = |format: &str, value: &str| match formats.get(format).unwrap() {
+            FormatType::Native { check, .. } => check.unwrap()(value),
+            FormatType::Validated { check, .. } => check(value),
+        };
+        assert!(check("email", "me@example.com"));
+        assert!(!check("email", "me@example@com"));
+        assert!(check("hostname", "example.com"));
+        assert!(!check("hostname", "-example.com"));
+        assert!(check("ipv4", "127.0.0.1"));
+        assert!(!check("ipv4", "::1"));
+        assert!(check("uri", "https://example.com/"));
+        assert!(!check("uri", "example"));
+
+        // The source for generated code is that of the same closures.
+        for format in ["email", "hostname"] {
+            let Some(FormatType::Validated { validate, .. }) = formats.get(format) else {
+                panic!("{format} isn't validated");
+            };
+            assert!(validate.starts_with("|value: &str| "), "{validate}");
+            assert!(
+                syn::parse_str::<syn::ExprClosure>(validate).is_ok(),
+                "{validate}"
+            );
+        }
+    }
+}
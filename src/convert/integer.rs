@@ -0,0 +1,187 @@
+use crate::{convert::Converter, typespace::Type};
+
+/// Integer types from narrowest to widest along with their bounds. We prefer
+/// unsigned types when the value can't be negative.
+const UNSIGNED: [(&str, i128, i128); 4] = [
+    ("u8", u8::MIN as i128, u8::MAX as i128),
+    ("u16", u16::MIN as i128, u16::MAX as i128),
+    ("u32", u32::MIN as i128, u32::MAX as i128),
+    ("u64", u64::MIN as i128, u64::MAX as i128),
+];
+const SIGNED: [(&str, i128, i128); 4] = [
+    ("i8", i8::MIN as i128, i8::MAX as i128),
+    ("i16", i16::MIN as i128, i16::MAX as i128),
+    ("i32", i32::MIN as i128, i32::MAX as i128),
+    ("i64", i64::MIN as i128, i64::MAX as i128),
+];
+
+impl Converter<'_> {
+    pub(crate) fn convert_integer(
+        &self,
+        minimum: Option<&serde_json::Number>,
+        exclusive_minimum: Option<&serde_json::Number>,
+        maximum: Option<&serde_json::Number>,
+        exclusive_maximum: Option<&serde_json::Number>,
+        format: Option<&String>,
+    ) -> Type {
+        let (lo, hi) = integer_range(minimum, exclusive_minimum, maximum, exclusive_maximum);
+        Type::Integer(integer_type(lo, hi, format.map(String::as_str)))
+    }
+}
+
+/// Fold the inclusive and exclusive bounds into the inclusive range of
+/// integers they admit; when both are present, the tighter one wins.
+fn integer_range(
+    minimum: Option<&serde_json::Number>,
+    exclusive_minimum: Option<&serde_json::Number>,
+    maximum: Option<&serde_json::Number>,
+    exclusive_maximum: Option<&serde_json::Number>,
+) -> (Option<i128>, Option<i128>) {
+    let lo = [
+        minimum.map(|n| integer_bound(n, true, false)),
+        exclusive_minimum.map(|n| integer_bound(n, true, true)),
+    ]
+    .into_iter()
+    .flatten()
+    .max();
+    let hi = [
+        maximum.map(|n| integer_bound(n, false, false)),
+        exclusive_maximum.map(|n| integer_bound(n, false, true)),
+    ]
+    .into_iter()
+    .flatten()
+    .min();
+    (lo, hi)
+}
+
+/// The least (for a lower bound) or greatest (for an upper bound) integer
+/// that satisfies the bound. Fractional bounds round inward, at which point
+/// exclusivity no longer matters.
+fn integer_bound(bound: &serde_json::Number, lower: bool, exclusive: bool) -> i128 {
+    let (n, integral) = if let Some(n) = bound.as_i64() {
+        (i128::from(n), true)
+    } else if let Some(n) = bound.as_u64() {
+        (i128::from(n), true)
+    } else {
+        let f = bound.as_f64().unwrap();
+        let rounded = if lower { f.ceil() } else { f.floor() };
+        (rounded as i128, rounded == f)
+    };
+    match (exclusive && integral, lower) {
+        (true, true) => n + 1,
+        (true, false) => n - 1,
+        (false, _) => n,
+    }
+}
+
+/// Pick the narrowest integer type that holds every value in `lo..=hi`. An
+/// explicit format (e.g. OpenAPI's "int32") fixes the width and sign.
+fn integer_type(lo: Option<i128>, hi: Option<i128>, format: Option<&str>) -> String {
+    let fixed = match format {
+        Some("int8") => Some("i8"),
+        Some("int16") => Some("i16"),
+        Some("int32") => Some("i32"),
+        Some("int64") => Some("i64"),
+        Some("uint8") => Some("u8"),
+        Some("uint16") => Some("u16"),
+        Some("uint32") => Some("u32"),
+        Some("uint64") => Some("u64"),
+        _ => None,
+    };
+
+    let name = fixed.unwrap_or_else(|| {
+        let (candidates, default) = match lo {
+            Some(lo) if lo >= 0 => (&UNSIGNED, "u64"),
+            _ => (&SIGNED, "i64"),
+        };
+        candidates
+            .iter()
+            .find(|(_, min, max)| {
+                lo.is_some_and(|lo| lo >= *min) && hi.is_some_and(|hi| hi <= *max)
+            })
+            .map_or(default, |(name, _, _)| name)
+    });
+
+    // If zero is out of range we can use the NonZero variant, which also
+    // gives `Option<T>` the same size as `T`.
+    let nonzero = lo.is_some_and(|lo| lo > 0) || hi.is_some_and(|hi| hi < 0);
+    if nonzero {
+        format!("::std::num::NonZero{}", name.to_uppercase())
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{integer_range, integer_type};
+
+    fn number(value: serde_json::Value) -> serde_json::Number {
+        match value {
+            serde_json::Value::Number(n) => n,
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_integer_range() {
+        let zero = number(0.into());
+        let u64_max = number(serde_json::json!(18446744073709551615u64));
+        assert_eq!(
+            integer_range(Some(&zero), None, Some(&u64_max), None),
+            (Some(0), Some(u64::MAX as i128))
+        );
+        assert_eq!(integer_type(Some(0), Some(u64::MAX as i128), None), "u64");
+
+        let half = number(serde_json::json!(0.5));
+        let nine_and_a_half = number(serde_json::json!(9.5));
+        assert_eq!(
+            integer_range(Some(&half), None, Some(&nine_and_a_half), None),
+            (Some(1), Some(9))
+        );
+        assert_eq!(
+            integer_range(None, Some(&half), None, Some(&nine_and_a_half)),
+            (Some(1), Some(9))
+        );
+
+        let ten = number(10.into());
+        let float_ten = number(serde_json::json!(10.0));
+        assert_eq!(
+            integer_range(None, Some(&zero), None, Some(&ten)),
+            (Some(1), Some(9))
+        );
+        assert_eq!(
+            integer_range(None, None, None, Some(&float_ten)),
+            (None, Some(9))
+        );
+        assert_eq!(
+            integer_range(Some(&zero), Some(&zero), Some(&ten), None),
+            (Some(1), Some(10))
+        );
+    }
+
+    #[test]
+    fn test_integer_type() {
+        assert_eq!(integer_type(None, None, None), "i64");
+        assert_eq!(integer_type(Some(0), None, None), "u64");
+        assert_eq!(integer_type(Some(0), Some(255), None), "u8");
+        assert_eq!(integer_type(Some(0), Some(256), None), "u16");
+        assert_eq!(integer_type(Some(-1), Some(100), None), "i8");
+        assert_eq!(integer_type(Some(-129), Some(0), None), "i16");
+        assert_eq!(integer_type(None, Some(10), None), "i64");
+        assert_eq!(
+            integer_type(Some(1), Some(65535), None),
+            "::std::num::NonZeroU16"
+        );
+        assert_eq!(
+            integer_type(Some(-100), Some(-1), None),
+            "::std::num::NonZeroI8"
+        );
+        assert_eq!(integer_type(None, None, Some("int32")), "i32");
+        assert_eq!(integer_type(Some(0), Some(10), Some("int64")), "i64");
+        assert_eq!(
+            integer_type(Some(1), None, Some("uint32")),
+            "::std::num::NonZeroU32"
+        );
+    }
+}
@@ -1,32 +1,105 @@
 mod array;
+mod format;
+mod integer;
 mod object;
 mod one_of;
+mod replace;
 
 use std::collections::BTreeMap;
 
 use crate::{
-    schemalet::{CanonicalSchemalet, CanonicalSchemaletDetails, SchemaRef, SchemaletValue},
-    typespace::{NameBuilder, Type},
+    schemalet::{
+        CanonicalSchemalet, CanonicalSchemaletDetails, SchemaRef, SchemaletMetadata, SchemaletValue,
+    },
+    typespace::{EnumTagType, NameBuilder, NameBuilderHint, Type, TypeConstant, TypeEnum},
 };
 
+pub use format::{FormatCheck, FormatTable, FormatType};
+pub(crate) use object::{additional_properties_map_id, pattern_key_id};
+pub(crate) use one_of::{non_null_id, non_null_subschemas};
+pub use replace::{ReplacementTable, UserType};
+
 // TODO naming?
-pub struct Converter {
-    graph: BTreeMap<SchemaRef, CanonicalSchemalet>,
+pub struct Converter<'g> {
+    graph: &'g BTreeMap<SchemaRef, CanonicalSchemalet>,
+    formats: &'g FormatTable,
+    replacements: &'g ReplacementTable,
     known_names: BTreeMap<SchemaRef, String>,
+    fallback_names: BTreeMap<SchemaRef, Vec<NameBuilderHint>>,
+    distinguish_null: bool,
 }
 
-impl Converter {
-    pub fn new(graph: BTreeMap<SchemaRef, CanonicalSchemalet>) -> Self {
+impl<'g> Converter<'g> {
+    pub fn new(
+        graph: &'g BTreeMap<SchemaRef, CanonicalSchemalet>,
+        formats: &'g FormatTable,
+        replacements: &'g ReplacementTable,
+    ) -> Self {
         Self {
             graph,
+            formats,
+            replacements,
             known_names: Default::default(),
+            fallback_names: Default::default(),
+            distinguish_null: false,
         }
     }
 
+    /// Render optional properties whose schemas admit `null` so that an
+    /// absent value can be told apart from an explicit `null`.
+    pub fn set_distinguish_null(&mut self, distinguish_null: bool) {
+        self.distinguish_null = distinguish_null;
+    }
+
+    /// The name by which a schema is known, such as its `$defs` key; it's
+    /// preferred to the schema's title.
     pub fn set_name(&mut self, id: SchemaRef, name: String) {
         self.known_names.insert(id, name);
     }
 
+    /// A name to use if the schema has no title, such as for the root of a
+    /// document.
+    pub fn set_fallback_name(&mut self, id: SchemaRef, name: String) {
+        self.fallback_names
+            .entry(id)
+            .or_default()
+            .push(NameBuilderHint::Fallback(name));
+    }
+
+    /// A type with no name of its own, such as an Option, passes the names
+    /// it was given (e.g. as a `$defs` entry) on to the types it contains as
+    /// fallbacks to their own.
+    pub fn inherit_names(&mut self, parent_id: &SchemaRef, child_id: SchemaRef) {
+        let hints = self
+            .name_hints(parent_id, &self.get(parent_id).metadata)
+            .into_iter()
+            .map(|hint| match hint {
+                NameBuilderHint::Known(name) | NameBuilderHint::Title(name) => {
+                    NameBuilderHint::Fallback(name)
+                }
+                hint => hint,
+            })
+            .collect::<Vec<_>>();
+        if hints.is_empty() {
+            return;
+        }
+        self.fallback_names
+            .entry(child_id)
+            .or_default()
+            .extend(hints);
+    }
+
+    fn name_hints(&self, id: &SchemaRef, metadata: &SchemaletMetadata) -> Vec<NameBuilderHint> {
+        let known = self
+            .known_names
+            .get(id)
+            .cloned()
+            .map(NameBuilderHint::Known);
+        let title = metadata.title.clone().map(NameBuilderHint::Title);
+        let fallback = self.fallback_names.get(id).into_iter().flatten().cloned();
+        known.into_iter().chain(title).chain(fallback).collect()
+    }
+
     fn get<'a>(&'a self, id: &SchemaRef) -> &'a CanonicalSchemalet {
         self.graph
             .get(id)
@@ -44,18 +117,47 @@ impl Converter {
         }
     }
 
+    /// Whether no value is valid for the given schema e.g. `false`.
+    fn is_nothing(&self, id: &SchemaRef) -> bool {
+        matches!(self.resolve(id).details, CanonicalSchemaletDetails::Nothing)
+    }
+
+    /// Whether `null` is a valid value for the given schema.
+    fn admits_null(&self, id: &SchemaRef) -> bool {
+        match &self.get(id).details {
+            CanonicalSchemaletDetails::Anything
+            | CanonicalSchemaletDetails::Value(SchemaletValue::Null)
+            | CanonicalSchemaletDetails::Constant(serde_json::Value::Null) => true,
+            CanonicalSchemaletDetails::Reference(next_id)
+            | CanonicalSchemaletDetails::Note(next_id) => self.admits_null(next_id),
+            CanonicalSchemaletDetails::ExclusiveOneOf { subschemas, .. } => subschemas
+                .iter()
+                .any(|subschema| self.admits_null(subschema)),
+            CanonicalSchemaletDetails::Nothing
+            | CanonicalSchemaletDetails::Constant(_)
+            | CanonicalSchemaletDetails::Value(_) => false,
+        }
+    }
+
     pub fn resolve_and_get_stuff<'a>(&'a self, mut id: &'a SchemaRef) -> GottenStuff<'a> {
         let mut title = None;
         let mut description = None;
+        let mut default = None;
         loop {
             let schemalet = self.get(id);
 
             let CanonicalSchemaletDetails::Reference(next_id) = &schemalet.details else {
+                // Unlike the title and description, a default on the
+                // referenced schema itself applies to the property.
+                if default.is_none() {
+                    default.clone_from(&schemalet.metadata.default);
+                }
                 return GottenStuff {
                     id,
                     schemalet,
                     description,
                     title,
+                    default,
                 };
             };
 
@@ -65,17 +167,15 @@ impl Converter {
             if let (None, Some(new_description)) = (&description, &schemalet.metadata.description) {
                 description = Some(new_description.clone());
             }
+            if let (None, Some(new_default)) = (&default, &schemalet.metadata.default) {
+                default = Some(new_default.clone());
+            }
 
             id = next_id;
         }
     }
 
     pub fn convert(&self, id: &SchemaRef) -> Type {
-        let name = match self.known_names.get(id) {
-            Some(s) => NameBuilder::Fixed(s.clone()),
-            None => NameBuilder::Unset,
-        };
-
         let schemalet = self.get(id);
         println!(
             "converting {}",
@@ -83,12 +183,71 @@ impl Converter {
         );
         let CanonicalSchemalet { metadata, details } = schemalet;
 
+        if let Some(user_type) = self.replacements.get(id, metadata) {
+            return Type::Native(user_type.to_native());
+        }
+
+        let hints = self.name_hints(id, metadata);
+        let name = if hints.is_empty() {
+            NameBuilder::Unset
+        } else {
+            NameBuilder::Hints(hints)
+        };
+
+        self.convert_details(name, metadata, details)
+    }
+
+    fn convert_details(
+        &self,
+        name: NameBuilder,
+        metadata: &SchemaletMetadata,
+        details: &CanonicalSchemaletDetails,
+    ) -> Type {
         let typ = match details {
             CanonicalSchemaletDetails::Anything => Type::JsonValue,
-            CanonicalSchemaletDetails::Nothing => todo!(),
-            CanonicalSchemaletDetails::Constant(_) => todo!(),
-            CanonicalSchemaletDetails::Reference(schema_ref) => todo!(),
-            CanonicalSchemaletDetails::Note(schema_ref) => todo!(),
+            // A type with no values.
+            CanonicalSchemaletDetails::Nothing => Type::Enum(TypeEnum::new(
+                name,
+                metadata.description.clone(),
+                None,
+                EnumTagType::External,
+                Vec::new(),
+                false,
+            )),
+            CanonicalSchemaletDetails::Constant(serde_json::Value::Null) => Type::Unit,
+            CanonicalSchemaletDetails::Constant(value) => Type::Constant(TypeConstant::new(
+                name,
+                metadata.description.clone(),
+                value.clone(),
+            )),
+            // We usually look through these when we get the ids of child
+            // types, but a schema we were asked for directly may be one.
+            // TODO 10/19/2026
+            // If the target is also converted in its own right we'll end up
+            // with two identical types.
+            CanonicalSchemaletDetails::Reference(schema_ref)
+            | CanonicalSchemaletDetails::Note(schema_ref) => {
+                let GottenStuff {
+                    id,
+                    schemalet,
+                    description,
+                    default,
+                    ..
+                } = self.resolve_and_get_stuff(schema_ref);
+                if let Some(user_type) = self.replacements.get(id, &schemalet.metadata) {
+                    return Type::Native(user_type.to_native());
+                }
+                let metadata = SchemaletMetadata {
+                    description: metadata
+                        .description
+                        .clone()
+                        .or(description)
+                        .or_else(|| schemalet.metadata.description.clone()),
+                    default: metadata.default.clone().or(default),
+                    ..schemalet.metadata.clone()
+                };
+                self.convert_details(name, &metadata, &schemalet.details)
+            }
             CanonicalSchemaletDetails::ExclusiveOneOf { subschemas, .. } => {
                 self.convert_one_of(name, metadata, subschemas)
             }
@@ -100,41 +259,45 @@ impl Converter {
             CanonicalSchemaletDetails::Value(SchemaletValue::Object(object)) => {
                 self.convert_object(name, metadata, object)
             }
-            CanonicalSchemaletDetails::Value(SchemaletValue::String { pattern, format }) => {
-                self.convert_string(name, metadata, pattern.as_ref(), format.as_ref())
-            }
+            CanonicalSchemaletDetails::Value(SchemaletValue::String {
+                pattern,
+                format,
+                min_length,
+                max_length,
+            }) => self.convert_string(
+                name,
+                metadata,
+                pattern.as_ref(),
+                format.as_ref(),
+                *min_length,
+                *max_length,
+            ),
             CanonicalSchemaletDetails::Value(SchemaletValue::Integer {
                 minimum,
                 exclusive_minimum,
-            }) => {
-                // TODO not handling this well ...
-                Type::Float("i64".to_string())
-            }
+                maximum,
+                exclusive_maximum,
+                format,
+            }) => self.convert_integer(
+                minimum.as_ref(),
+                exclusive_minimum.as_ref(),
+                maximum.as_ref(),
+                exclusive_maximum.as_ref(),
+                format.as_ref(),
+            ),
             CanonicalSchemaletDetails::Value(SchemaletValue::Number {
                 minimum,
                 exclusive_minimum,
+                ..
             }) => {
                 // TODO not handling this well ...
                 Type::Float("f64".to_string())
             }
-            CanonicalSchemaletDetails::Value(SchemaletValue::Null) => todo!(),
+            CanonicalSchemaletDetails::Value(SchemaletValue::Null) => Type::Unit,
         };
 
         typ
     }
-
-    fn convert_string(
-        &self,
-        name: NameBuilder,
-        metadata: &crate::schemalet::SchemaletMetadata,
-        pattern: Option<&String>,
-        format: Option<&String>,
-    ) -> Type {
-        match (pattern, format) {
-            (_, _) => Type::String,
-            // _ => panic!("{:?} {:?}", pattern, format),
-        }
-    }
 }
 
 pub struct GottenStuff<'a> {
@@ -142,4 +305,5 @@ pub struct GottenStuff<'a> {
     schemalet: &'a CanonicalSchemalet,
     description: Option<String>,
     title: Option<String>,
+    default: Option<serde_json::Value>,
 }
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use quote::format_ident;
 use unicode_ident::is_xid_continue;
 
@@ -5,11 +7,12 @@ use crate::{
     convert::{Converter, GottenStuff},
     schemalet::{SchemaRef, SchemaletMetadata, SchemaletValueObject},
     typespace::{
-        NameBuilder, StructProperty, StructPropertySerde, StructPropertyState, Type, TypeStruct,
+        JsonValue, NameBuilder, StructProperty, StructPropertySerde, StructPropertyState, Type,
+        TypeStruct,
     },
 };
 
-impl Converter {
+impl Converter<'_> {
     pub(crate) fn convert_object(
         &self,
         name: NameBuilder,
@@ -19,74 +22,121 @@ impl Converter {
         // TODO 6/30/2025
         // Increasingly I'm of the opinion I need to do the conversion from the
         // JSON Schema style object into my new, "structural" encoding.
+
+        // Once subschemas have been merged, `unevaluatedProperties` applies
+        // to the same properties as `additionalProperties`.
+        let object = &SchemaletValueObject {
+            additional_properties: object
+                .additional_properties
+                .clone()
+                .or_else(|| object.unevaluated_properties.clone()),
+            unevaluated_properties: None,
+            ..object.clone()
+        };
+
         match object {
             SchemaletValueObject {
                 properties,
                 required,
-                additional_properties: None,
+                additional_properties,
                 property_names: None,
                 pattern_properties: None,
-            } => {
-                let prop_names = properties
-                    .keys()
-                    .map(|prop_name| tmp_sanitize(prop_name))
-                    .collect::<Vec<_>>();
-
-                let properties = properties
-                    .iter()
-                    .zip(prop_names)
-                    .map(|((prop_name, prop_id), new_prop_name)| {
-                        let GottenStuff {
-                            id,
-                            schemalet: _,
-                            description,
-                            title: _,
-                        } = self.resolve_and_get_stuff(prop_id);
-
-                        let rust_name = format_ident!("{new_prop_name}");
-                        let json_name = if *prop_name == new_prop_name {
-                            StructPropertySerde::None
-                        } else {
-                            StructPropertySerde::Rename(prop_name.clone())
-                        };
-                        StructProperty::new(
-                            rust_name,
-                            json_name,
-                            // TODO need to figure this out
-                            StructPropertyState::Optional,
-                            // TODO maybe a helper to pull out descriptions for property meta?
-                            description,
-                            id.clone(),
-                        )
-                    })
-                    .collect();
+                unevaluated_properties: None,
+            } if additional_properties
+                .as_ref()
+                .is_none_or(|additional_properties| self.is_nothing(additional_properties)) =>
+            {
+                let properties = self.convert_properties(properties, required);
 
                 Type::Struct(TypeStruct::new(
                     name,
                     metadata.description.clone(),
-                    None,
+                    metadata.default.clone().map(JsonValue::new),
                     properties,
-                    false,
+                    // i.e. `additionalProperties: false`
+                    additional_properties.is_some(),
                 ))
             }
 
+            // Properties beyond the fixed ones are gathered into a map that
+            // serde flattens into the struct.
             SchemaletValueObject {
                 properties,
                 required,
                 additional_properties: Some(additional_properties),
                 property_names: None,
                 pattern_properties: None,
+                unevaluated_properties: None,
+            } if !properties.is_empty() && !self.is_nothing(additional_properties) => {
+                let mut properties = self.convert_properties(properties, required);
+
+                let mut extra_name = "extra".to_string();
+                while properties.iter().any(|prop| prop.rust_name == extra_name) {
+                    extra_name.push('_');
+                }
+                properties.push(StructProperty::new(
+                    format_ident!("{extra_name}"),
+                    StructPropertySerde::Flatten,
+                    StructPropertyState::Required,
+                    None,
+                    additional_properties_map_id(additional_properties),
+                ));
+
+                Type::Struct(TypeStruct::new(
+                    name,
+                    metadata.description.clone(),
+                    metadata.default.clone().map(JsonValue::new),
+                    properties,
+                    false,
+                ))
+            }
+
+            SchemaletValueObject {
+                properties,
+                required,
+                additional_properties,
+                property_names,
+                pattern_properties: None,
+                unevaluated_properties: None,
             } if properties.is_empty() && required.is_empty() => {
-                // TODO not sure what to do here...
-                let key_id = SchemaRef::Internal("string".to_string());
-                let GottenStuff {
-                    id,
-                    schemalet: _,
-                    description: _,
-                    title: _,
-                } = self.resolve_and_get_stuff(additional_properties);
+                // Any `propertyNames` has already been merged with a string
+                // schema; absent constraints the key is a String.
+                let key_id = property_names.as_ref().map_or_else(
+                    || SchemaRef::Internal("string".to_string()),
+                    |property_names| self.resolve_and_get_stuff(property_names).id.clone(),
+                );
+                let value_id = additional_properties.as_ref().map_or_else(
+                    || SchemaRef::Internal("anything".to_string()),
+                    |additional_properties| {
+                        self.resolve_and_get_stuff(additional_properties).id.clone()
+                    },
+                );
 
-                Type::Map(key_id.clone(), id.clone())
+                Type::Map(key_id, value_id)
+            }
+
+            // A single pattern becomes a map whose keys are checked against
+            // that pattern. Strictly, absent `additionalProperties: false`,
+            // other properties would be permitted too but that's rarely what
+            // the author intends.
+            SchemaletValueObject {
+                properties,
+                required,
+                additional_properties,
+                property_names: None,
+                pattern_properties: Some(pattern_properties),
+                unevaluated_properties: None,
+            } if properties.is_empty()
+                && required.is_empty()
+                && pattern_properties.len() == 1
+                && additional_properties
+                    .as_ref()
+                    .is_none_or(|additional_properties| self.is_nothing(additional_properties)) =>
+            {
+                let (pattern, value_id) = pattern_properties.iter().next().unwrap();
+                let value_id = self.resolve_and_get_stuff(value_id).id.clone();
+
+                Type::Map(pattern_key_id(pattern), value_id)
             }
 
             _ => todo!(
@@ -95,6 +145,85 @@ impl Converter {
             ),
         }
     }
+
+    /// Whether the object permits no properties beyond those it names i.e.
+    /// `additionalProperties: false` or `unevaluatedProperties: false`.
+    pub(crate) fn denies_unknown_fields(&self, object: &SchemaletValueObject) -> bool {
+        object
+            .additional_properties
+            .as_ref()
+            .or(object.unevaluated_properties.as_ref())
+            .is_some_and(|additional_properties| self.is_nothing(additional_properties))
+            && object.pattern_properties.is_none()
+    }
+
+    fn convert_properties(
+        &self,
+        properties: &BTreeMap<String, SchemaRef>,
+        required: &[String],
+    ) -> Vec<StructProperty> {
+        let prop_names = properties
+            .keys()
+            .map(|prop_name| tmp_sanitize(prop_name))
+            .collect::<Vec<_>>();
+
+        properties
+            .iter()
+            .zip(prop_names)
+            .map(|((prop_name, prop_id), new_prop_name)| {
+                let GottenStuff {
+                    id,
+                    schemalet,
+                    description,
+                    title: _,
+                    default,
+                } = self.resolve_and_get_stuff(prop_id);
+                // A property's own schema describes the property; only a
+                // referenced schema describes its type instead.
+                let description = description.or_else(|| {
+                    (id == prop_id)
+                        .then(|| schemalet.metadata.description.clone())
+                        .flatten()
+                });
+
+                let rust_name = format_ident!("{new_prop_name}");
+                let json_name = if *prop_name == new_prop_name {
+                    StructPropertySerde::None
+                } else {
+                    StructPropertySerde::Rename(prop_name.clone())
+                };
+                let state = if required.contains(prop_name) {
+                    StructPropertyState::Required
+                } else if let Some(default) = default {
+                    StructPropertyState::Default(JsonValue::new(default))
+                } else if self.distinguish_null && self.admits_null(id) {
+                    StructPropertyState::OptionalNullable
+                } else {
+                    StructPropertyState::Optional
+                };
+                StructProperty::new(
+                    rust_name,
+                    json_name,
+                    state,
+                    // TODO maybe a helper to pull out descriptions for property meta?
+                    description,
+                    id.clone(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// The string type for the keys of a map from `patternProperties`; the
+/// normalizer adds these to the graph.
+pub(crate) fn pattern_key_id(pattern: &str) -> SchemaRef {
+    SchemaRef::Internal(format!("patternProperties/{pattern}"))
+}
+
+/// The map type for the `additionalProperties` of a struct that also has
+/// fixed properties; the normalizer adds these to the graph.
+pub(crate) fn additional_properties_map_id(additional_properties: &SchemaRef) -> SchemaRef {
+    SchemaRef::Internal(format!("additionalProperties/{additional_properties}"))
 }
 
 fn tmp_sanitize(prop_name: &str) -> String {
@@ -1,25 +1,39 @@
-use std::{borrow::Cow, collections::BTreeSet};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+};
 
 use heck::ToPascalCase;
 
 use crate::{
-    convert::Converter,
+    convert::{Converter, GottenStuff},
     schemalet::{
-        CanonicalSchemalet, CanonicalSchemaletDetails, SchemaRef, SchemaletMetadata,
-        SchemaletValue, SchemaletValueObject,
+        one_of_disjoint, resolve_note, CanonicalSchemalet, CanonicalSchemaletDetails, SchemaRef,
+        SchemaletMetadata, SchemaletValue, SchemaletValueObject,
     },
     typespace::{
-        EnumTagType, EnumVariant, NameBuilder, StructProperty, Type, TypeEnum, VariantDetails,
+        EnumTagType, EnumVariant, JsonValue, NameBuilder, StructProperty, Type, TypeEnum,
+        VariantDetails,
     },
 };
 
-impl Converter {
+impl Converter<'_> {
     pub(crate) fn convert_one_of(
         &self,
         name: NameBuilder,
         metadata: &SchemaletMetadata,
         subschemas: &[SchemaRef],
     ) -> Type {
+        // "T or null" is an Option<T>. If there are several non-null
+        // subschemas, the normalizer has made a oneOf of just those.
+        if let Some(non_null) = non_null_subschemas(self.graph, subschemas) {
+            let inner = match non_null.as_slice() {
+                [only] => self.resolve_and_get_stuff(only).id.clone(),
+                _ => non_null_id(&non_null),
+            };
+            return Type::Option(inner);
+        }
+
         let resolved_subschemas = subschemas
             .into_iter()
             .map(|schema_ref| self.get(schema_ref))
@@ -48,12 +62,22 @@ impl Converter {
 
         println!("{}", serde_json::to_string_pretty(&proto_variants).unwrap());
 
+        // Adjacent tagging must be considered before internal tagging: every
+        // adjacently tagged enum could also be read as internally tagged, but
+        // the adjacently tagged form is less repetitive.
         let ty = if let Some(ty) =
+            self.maybe_adjacently_tagged_enum(name.clone(), metadata, &proto_variants)
+        {
+            ty
+        } else if let Some(ty) =
+            self.maybe_internally_tagged_enum(name.clone(), metadata, &proto_variants)
+        {
+            ty
+        } else if let Some(ty) =
             self.maybe_externally_tagged_enum(name.clone(), metadata, &proto_variants)
         {
             ty
         } else {
-            // TODO ... adjacent and internal
             self.untagged_enum(name, metadata, &proto_variants)
         };
 
@@ -98,11 +122,17 @@ impl Converter {
                         }
                     })
                     .collect::<Option<Vec<_>>>(),
+                // The property is the tag so it must be present.
                 CanonicalSchemaletDetails::Value(SchemaletValue::Object(
                     // TODO more checks?
-                    // TODO required!
-                    SchemaletValueObject { properties, .. },
-                )) if properties.len() == 1 => {
+                    SchemaletValueObject {
+                        properties,
+                        required,
+                        ..
+                    },
+                )) if properties.len() == 1
+                    && properties.keys().all(|name| required.contains(name)) =>
+                {
                     let (name, schema_ref) = properties.iter().next().unwrap().clone();
                     Some(vec![ProtoVariantExternal {
                         proto: Cow::Borrowed(proto),
@@ -140,13 +170,167 @@ impl Converter {
         Some(Type::Enum(TypeEnum::new(
             name,
             metadata.description.clone(),
-            None,
+            metadata.default.clone().map(JsonValue::new),
             EnumTagType::External,
             variants,
             false,
         )))
     }
 
+    /// Properties that could serve as the tag of an adjacently or internally
+    /// tagged enum: required by every variant and with a distinct, constant
+    /// string value in each.
+    fn tag_candidates<'a>(
+        &self,
+        objects: &[&'a SchemaletValueObject],
+    ) -> Vec<(&'a String, Vec<String>)> {
+        let Some((first, rest)) = objects.split_first() else {
+            return Vec::new();
+        };
+
+        first
+            .required
+            .iter()
+            .filter(|prop_name| {
+                rest.iter()
+                    .all(|object| object.required.contains(prop_name))
+            })
+            .filter_map(|prop_name| {
+                let values = objects
+                    .iter()
+                    .map(|object| {
+                        let prop_id = object.properties.get(prop_name)?;
+                        self.constant_value(prop_id)?.as_str().map(str::to_string)
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                let unique = values.iter().collect::<BTreeSet<_>>();
+                (unique.len() == values.len()).then_some((prop_name, values))
+            })
+            .collect()
+    }
+
+    /// The value of a constant schema, looking through references.
+    fn constant_value<'a>(&'a self, mut id: &'a SchemaRef) -> Option<&'a serde_json::Value> {
+        loop {
+            match &self.get(id).details {
+                CanonicalSchemaletDetails::Constant(value) => break Some(value),
+                CanonicalSchemaletDetails::Reference(next_id)
+                | CanonicalSchemaletDetails::Note(next_id) => id = next_id,
+                _ => break None,
+            }
+        }
+    }
+
+    /// Every variant must be an object for the enum to be tagged.
+    fn variant_objects<'a>(
+        &'a self,
+        proto_variants: &[ProtoVariant<'a>],
+    ) -> Option<Vec<&'a SchemaletValueObject>> {
+        proto_variants
+            .iter()
+            .map(|proto| self.resolve(proto.id).as_object())
+            .collect()
+    }
+
+    /// Adjacently tagged variants are objects with the tag and, optionally, a
+    /// single content property that has the same name in every variant.
+    fn maybe_adjacently_tagged_enum(
+        &self,
+        name: NameBuilder,
+        metadata: &SchemaletMetadata,
+        proto_variants: &[ProtoVariant],
+    ) -> Option<Type> {
+        let objects = self.variant_objects(proto_variants)?;
+
+        self.tag_candidates(&objects)
+            .into_iter()
+            .find_map(|(tag, tag_values)| {
+                let content_names = objects
+                    .iter()
+                    .flat_map(|object| object.properties.keys())
+                    .filter(|prop_name| *prop_name != tag)
+                    .collect::<BTreeSet<_>>();
+                let [content] = content_names.into_iter().collect::<Vec<_>>()[..] else {
+                    return None;
+                };
+
+                let variants = proto_variants
+                    .iter()
+                    .zip(&objects)
+                    .zip(tag_values)
+                    .map(|((proto, object), tag_value)| {
+                        // The content must be present if the variant has any
+                        // content at all.
+                        let details = match object.properties.get(content) {
+                            None => VariantDetails::Simple,
+                            Some(_) if !object.required.contains(content) => return None,
+                            Some(content_id) => {
+                                let GottenStuff { id, .. } = self.resolve_and_get_stuff(content_id);
+                                VariantDetails::Item(id.clone())
+                            }
+                        };
+                        Some(tagged_variant(proto, tag_value, details))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+
+                Some(Type::Enum(TypeEnum::new(
+                    name.clone(),
+                    metadata.description.clone(),
+                    metadata.default.clone().map(JsonValue::new),
+                    EnumTagType::Adjacent {
+                        tag: tag.clone(),
+                        content: content.clone(),
+                    },
+                    variants,
+                    objects
+                        .iter()
+                        .all(|object| self.denies_unknown_fields(object)),
+                )))
+            })
+    }
+
+    /// Internally tagged variants are objects with the tag alongside the
+    /// variant's other properties.
+    fn maybe_internally_tagged_enum(
+        &self,
+        name: NameBuilder,
+        metadata: &SchemaletMetadata,
+        proto_variants: &[ProtoVariant],
+    ) -> Option<Type> {
+        let objects = self.variant_objects(proto_variants)?;
+        let (tag, tag_values) = self.tag_candidates(&objects).into_iter().next()?;
+
+        let variants = proto_variants
+            .iter()
+            .zip(tag_values)
+            .map(|(proto, tag_value)| {
+                let schemalet = self.resolve(proto.id);
+                let properties = self
+                    .xxx_maybe_struct_props(schemalet)?
+                    .into_iter()
+                    .filter(|prop| prop.serialized_name() != *tag)
+                    .collect::<Vec<_>>();
+                let details = if properties.is_empty() {
+                    VariantDetails::Simple
+                } else {
+                    VariantDetails::Struct(properties)
+                };
+                Some(tagged_variant(proto, tag_value, details))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Type::Enum(TypeEnum::new(
+            name,
+            metadata.description.clone(),
+            metadata.default.clone().map(JsonValue::new),
+            EnumTagType::Internal { tag: tag.clone() },
+            variants,
+            objects
+                .iter()
+                .all(|object| self.denies_unknown_fields(object)),
+        )))
+    }
+
     fn untagged_enum(
         &self,
         name: NameBuilder,
@@ -165,17 +349,21 @@ impl Converter {
         // the tiles and paths); then we'll use type classification (integer,
         // boolean, object), and finally we'll fall back on Variant{n}.
 
-        let variant_names = if let Some(title_names) = proto_variants
+        let title_names = proto_variants
             .iter()
             .map(|proto| {
                 proto
                     .name
                     .clone()
                     .or_else(|| proto.schemalet.metadata.title.clone())
+                    .map(|title| title.to_pascal_case())
+                    .filter(|name| name.starts_with(|c: char| c.is_alphabetic()))
             })
             .collect::<Option<Vec<_>>>()
-        {
-            todo!()
+            .filter(|names| names.iter().collect::<BTreeSet<_>>().len() == names.len());
+
+        let variant_names = if let Some(title_names) = title_names {
+            title_names
         } else if let Some(kind_names) = maybe_kind_names(proto_variants) {
             kind_names
         } else {
@@ -205,10 +393,29 @@ impl Converter {
             })
             .collect::<Vec<_>>();
 
+        // The schema rejects values that are valid for more than one
+        // variant, but serde takes the first variant that matches; unless we
+        // can show that never happens, we say so.
+        let ids = proto_variants
+            .iter()
+            .map(|proto| proto.id.clone())
+            .collect::<Vec<_>>();
+        let description = match one_of_disjoint(self.graph, &ids) {
+            Some(true) => metadata.description.clone(),
+            _ => {
+                let note = "A value that's valid for more than one variant is taken as the \
+                            first of them, though the schema rejects it.";
+                Some(match &metadata.description {
+                    Some(description) => format!("{description}\n\n{note}"),
+                    None => note.to_string(),
+                })
+            }
+        };
+
         Type::Enum(TypeEnum::new(
             name,
-            metadata.description.clone(),
-            None,
+            description,
+            metadata.default.clone().map(JsonValue::new),
             EnumTagType::Untagged,
             variants,
             false,
@@ -235,6 +442,53 @@ impl Converter {
     }
 }
 
+/// If exactly one of the subschemas is `null`, the others.
+pub(crate) fn non_null_subschemas(
+    graph: &BTreeMap<SchemaRef, CanonicalSchemalet>,
+    subschemas: &[SchemaRef],
+) -> Option<Vec<SchemaRef>> {
+    let is_null = |id: &SchemaRef| {
+        resolve_note(graph, id).map(|schemalet| {
+            matches!(
+                schemalet.details,
+                CanonicalSchemaletDetails::Value(SchemaletValue::Null)
+                    | CanonicalSchemaletDetails::Constant(serde_json::Value::Null)
+            )
+        })
+    };
+
+    let mut non_null = Vec::new();
+    for id in subschemas {
+        if !is_null(id)? {
+            non_null.push(id.clone());
+        }
+    }
+
+    (non_null.len() + 1 == subschemas.len() && !non_null.is_empty()).then_some(non_null)
+}
+
+/// The oneOf of the non-null subschemas of a oneOf that includes `null`; the
+/// normalizer adds these to the graph.
+pub(crate) fn non_null_id(non_null: &[SchemaRef]) -> SchemaRef {
+    let ids = non_null
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    SchemaRef::Internal(format!("nonNull/{ids}"))
+}
+
+fn tagged_variant(proto: &ProtoVariant, tag_value: String, details: VariantDetails) -> EnumVariant {
+    let rust_name = tag_value.to_pascal_case();
+    let rename = (tag_value != rust_name).then_some(tag_value);
+    EnumVariant {
+        rust_name,
+        rename,
+        description: proto.description.clone(),
+        details,
+    }
+}
+
 fn maybe_kind_names(proto_variants: &[ProtoVariant]) -> Option<Vec<String>> {
     let xxx = proto_variants
         .iter()
@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    schemalet::{SchemaRef, SchemaletMetadata},
+    typespace::{TypeNative, TypeTrait},
+};
+
+/// An existing Rust type to use in place of generating one.
+#[derive(Debug, Clone)]
+pub struct UserType {
+    path: String,
+    traits: Vec<TypeTrait>,
+}
+
+impl UserType {
+    /// The type at `path` (e.g. `::my_crate::Money`) which implements exactly
+    /// `traits`; generated types that contain it only implement those traits
+    /// that it does.
+    pub fn new(path: impl ToString, traits: impl IntoIterator<Item = TypeTrait>) -> Self {
+        Self {
+            path: path.to_string(),
+            traits: traits.into_iter().collect(),
+        }
+    }
+
+    pub(crate) fn to_native(&self) -> TypeNative {
+        TypeNative::new(&self.path, self.traits.iter().copied())
+    }
+}
+
+/// Schemas for which we use existing types rather than generating them. A
+/// schema may be identified by its id, by its title, or by an `x-rust-type`
+/// extension that names the type's path; in that order of precedence.
+#[derive(Debug, Clone, Default)]
+pub struct ReplacementTable {
+    ids: BTreeMap<String, UserType>,
+    titles: BTreeMap<String, UserType>,
+    rust_types: BTreeMap<String, UserType>,
+}
+
+impl ReplacementTable {
+    /// Use `typ` for the schema with the given id e.g.
+    /// `https://example.com/types.json#/$defs/money`. An id without a
+    /// fragment refers to the root of its document.
+    pub fn insert_id(&mut self, id: impl ToString, typ: UserType) -> &mut Self {
+        let mut id = id.to_string();
+        if !id.contains('#') {
+            id.push('#');
+        }
+        self.ids.insert(id, typ);
+        self
+    }
+
+    /// Use `typ` for schemas with the given title.
+    pub fn insert_title(&mut self, title: impl ToString, typ: UserType) -> &mut Self {
+        self.titles.insert(title.to_string(), typ);
+        self
+    }
+
+    /// Use `typ` for schemas whose `x-rust-type` is its path. Schemas with
+    /// an `x-rust-type` that isn't in the table are generated as usual since
+    /// we know neither that the type is available nor what it implements.
+    pub fn insert_rust_type(&mut self, typ: UserType) -> &mut Self {
+        self.rust_types.insert(typ.path.clone(), typ);
+        self
+    }
+
+    pub(crate) fn get(&self, id: &SchemaRef, metadata: &SchemaletMetadata) -> Option<&UserType> {
+        let by_id = match id {
+            SchemaRef::Id(id) => self.ids.get(id),
+            _ => None,
+        };
+        by_id
+            .or_else(|| {
+                metadata
+                    .title
+                    .as_ref()
+                    .and_then(|title| self.titles.get(title))
+            })
+            .or_else(|| {
+                metadata
+                    .rust_type
+                    .as_ref()
+                    .and_then(|path| self.rust_types.get(path))
+            })
+    }
+}
@@ -0,0 +1,310 @@
+//! Structural deduplication of canonical schemalets.
+//!
+//! The same anonymous schema (say, `{"type": "string", "format": "uuid"}`)
+//! may appear many times within a document--or across documents. Each
+//! occurrence has its own `SchemaRef` and, left alone, would become its own
+//! generated type. Here we figure out which schemalets are structurally
+//! identical, including all the schemalets they reference, and point every
+//! reference at a single representative.
+//!
+//! Because the graph may contain cycles we can't simply hash from the leaves
+//! up. Instead we do partition refinement: start by grouping schemalets by
+//! their local shape (i.e. ignoring the identity of their children) and then
+//! repeatedly split groups whose members have children in different groups.
+//! When no group splits, every group is a set of equivalent schemalets.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::schemalet::{CanonicalSchemalet, SchemaRef};
+
+/// The equivalence classes of a growing graph. Each class has a single
+/// representative that, once chosen, never changes: types generated for a
+/// representative remain valid as we add more schemalets.
+#[derive(Debug, Default)]
+pub(crate) struct Dedup {
+    /// Structurally duplicate schemalets mapped to their representatives.
+    representatives: BTreeMap<SchemaRef, SchemaRef>,
+    /// Representatives by local shape (see [`local_shape`]).
+    shapes: BTreeMap<String, BTreeSet<SchemaRef>>,
+}
+
+impl Dedup {
+    /// The representative of the class of `id`.
+    pub fn representative<'a>(&'a self, id: &'a SchemaRef) -> &'a SchemaRef {
+        self.representatives.get(id).unwrap_or(id)
+    }
+
+    /// Collapse the schemalets `added` to `graph` into the existing classes
+    /// or into new classes of their own. Every child reference of an added
+    /// schemalet is rewritten to point to the representative of its class;
+    /// the schemalets themselves remain in the graph. Returns the mapping
+    /// from each added duplicate to its representative.
+    ///
+    /// The representatives are pairwise inequivalent, so we need only
+    /// consider those with the same local shape as something we've added:
+    /// anything else can be compared by identity alone.
+    pub fn add(
+        &mut self,
+        graph: &mut BTreeMap<SchemaRef, CanonicalSchemalet>,
+        added: &BTreeSet<SchemaRef>,
+    ) -> BTreeMap<SchemaRef, SchemaRef> {
+        // Added schemalets may refer to existing duplicates.
+        for id in added {
+            let schemalet = graph.get_mut(id).unwrap();
+            for child in schemalet.details.children_mut() {
+                if let Some(representative) = self.representatives.get(child) {
+                    *child = representative.clone();
+                }
+            }
+        }
+
+        let added_shapes = added
+            .iter()
+            .map(|id| (id, local_shape(graph.get(id).unwrap())))
+            .collect::<BTreeMap<_, _>>();
+        let existing = added_shapes
+            .values()
+            .filter_map(|shape| self.shapes.get(shape))
+            .flatten()
+            .collect::<BTreeSet<_>>();
+
+        let classes = equivalence(graph, existing.iter().copied().chain(added));
+
+        // Existing representatives keep their roles; otherwise the least
+        // SchemaRef in each class is its representative.
+        let mut class_representative = BTreeMap::new();
+        for id in &existing {
+            class_representative.insert(classes[*id], *id);
+        }
+        for id in added {
+            class_representative.entry(classes[id]).or_insert(id);
+        }
+
+        let new_representatives = added
+            .iter()
+            .filter_map(|id| {
+                let representative = class_representative[&classes[id]];
+                (representative != id).then(|| (id.clone(), representative.clone()))
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        for id in added {
+            let schemalet = graph.get_mut(id).unwrap();
+            for child in schemalet.details.children_mut() {
+                if let Some(representative) = new_representatives.get(child) {
+                    *child = representative.clone();
+                }
+            }
+        }
+
+        for (id, shape) in added_shapes {
+            if !new_representatives.contains_key(id) {
+                self.shapes.entry(shape).or_default().insert(id.clone());
+            }
+        }
+        self.representatives.extend(new_representatives.clone());
+
+        new_representatives
+    }
+}
+
+/// The metadata and details of a schemalet with all child references blotted
+/// out.
+fn local_shape(schemalet: &CanonicalSchemalet) -> String {
+    let mut schemalet = schemalet.clone();
+    for child in schemalet.details.children_mut() {
+        *child = SchemaRef::Internal(String::new());
+    }
+    serde_json::to_string(&schemalet).unwrap()
+}
+
+/// Partition `ids` into equivalence classes, returning the class of each.
+/// A reference to a schemalet outside of `ids` can only be equivalent to
+/// another reference to the very same thing.
+fn equivalence<'a>(
+    graph: &BTreeMap<SchemaRef, CanonicalSchemalet>,
+    ids: impl IntoIterator<Item = &'a SchemaRef>,
+) -> BTreeMap<&'a SchemaRef, usize> {
+    let ids = ids.into_iter().collect::<BTreeSet<_>>();
+
+    // Initial partition: the local shape of each schemalet.
+    let mut shapes = BTreeMap::new();
+    let mut classes = ids
+        .iter()
+        .map(|id| {
+            let shape = local_shape(graph.get(*id).unwrap());
+            let next = shapes.len();
+            (*id, *shapes.entry(shape).or_insert(next))
+        })
+        .collect::<BTreeMap<_, _>>();
+    let mut count = shapes.len();
+
+    // Refine until the number of classes stabilizes. Each signature includes
+    // the previous class so classes only ever split; if the count hasn't
+    // changed then neither has the partition.
+    loop {
+        let mut signatures = BTreeMap::new();
+        let next_classes = classes
+            .iter()
+            .map(|(id, class)| {
+                let children = graph
+                    .get(*id)
+                    .unwrap()
+                    .children()
+                    .into_iter()
+                    .map(|child| classes.get(child).copied().ok_or(child))
+                    .collect::<Vec<_>>();
+                let next = signatures.len();
+                (*id, *signatures.entry((*class, children)).or_insert(next))
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let next_count = signatures.len();
+        classes = next_classes;
+        if next_count == count {
+            break;
+        }
+        count = next_count;
+    }
+
+    classes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::schemalet::{
+        CanonicalSchemalet, CanonicalSchemaletDetails, SchemaRef, SchemaletValue,
+        SchemaletValueArray, SchemaletValueObject,
+    };
+
+    use super::Dedup;
+
+    fn dedup(
+        graph: &mut BTreeMap<SchemaRef, CanonicalSchemalet>,
+    ) -> BTreeMap<SchemaRef, SchemaRef> {
+        let all = graph.keys().cloned().collect();
+        Dedup::default().add(graph, &all)
+    }
+
+    fn value(value: SchemaletValue) -> CanonicalSchemalet {
+        CanonicalSchemalet {
+            metadata: Default::default(),
+            details: CanonicalSchemaletDetails::Value(value),
+        }
+    }
+
+    fn uuid() -> CanonicalSchemalet {
+        value(SchemaletValue::String {
+            pattern: None,
+            format: Some("uuid".to_string()),
+            min_length: None,
+            max_length: None,
+        })
+    }
+
+    fn id(s: &str) -> SchemaRef {
+        SchemaRef::Id(s.to_string())
+    }
+
+    #[test]
+    fn test_dedup_leaves_and_cycles() {
+        // Two identical self-referential list types, each with a uuid.
+        let list = |name: &str| {
+            let properties = [
+                ("id".to_string(), id(&format!("{name}/id"))),
+                ("next".to_string(), id(&format!("{name}/next"))),
+            ]
+            .into();
+            value(SchemaletValue::Object(SchemaletValueObject {
+                properties,
+                ..Default::default()
+            }))
+        };
+        let next = |name: &str| {
+            value(SchemaletValue::Array(SchemaletValueArray {
+                items: Some(id(name)),
+                ..Default::default()
+            }))
+        };
+
+        let mut graph = BTreeMap::from([
+            (id("a"), list("a")),
+            (id("a/id"), uuid()),
+            (id("a/next"), next("a")),
+            (id("b"), list("b")),
+            (id("b/id"), uuid()),
+            (id("b/next"), next("b")),
+            (
+                id("c"),
+                value(SchemaletValue::String {
+                    pattern: None,
+                    format: None,
+                    min_length: None,
+                    max_length: None,
+                }),
+            ),
+        ]);
+
+        let representatives = dedup(&mut graph);
+
+        assert_eq!(
+            representatives,
+            BTreeMap::from([
+                (id("b"), id("a")),
+                (id("b/id"), id("a/id")),
+                (id("b/next"), id("a/next")),
+            ])
+        );
+
+        let b = graph.get(&id("b")).unwrap();
+        assert_eq!(b.children(), vec![&id("a/id"), &id("a/next")]);
+        let b_next = graph.get(&id("b/next")).unwrap();
+        assert_eq!(b_next.children(), vec![&id("a")]);
+    }
+
+    #[test]
+    fn test_dedup_incremental() {
+        let list = |name: &str| {
+            value(SchemaletValue::Object(SchemaletValueObject {
+                properties: [("id".to_string(), id(&format!("{name}/id")))].into(),
+                ..Default::default()
+            }))
+        };
+
+        let mut dedup = Dedup::default();
+        let mut graph = BTreeMap::from([(id("z"), list("z")), (id("z/id"), uuid())]);
+        let added = graph.keys().cloned().collect();
+        assert!(dedup.add(&mut graph, &added).is_empty());
+
+        // The new schemalets sort before the existing ones but the existing
+        // representatives remain. The new list refers to an existing
+        // duplicate by way of its representative.
+        graph.insert(id("a"), list("a"));
+        graph.insert(id("a/id"), uuid());
+        graph.insert(id("b"), list("z"));
+        let added = [id("a"), id("a/id"), id("b")].into();
+        assert_eq!(
+            dedup.add(&mut graph, &added),
+            BTreeMap::from([
+                (id("a"), id("z")),
+                (id("a/id"), id("z/id")),
+                (id("b"), id("z")),
+            ])
+        );
+        assert_eq!(dedup.representative(&id("a/id")), &id("z/id"));
+        assert_eq!(graph.get(&id("a")).unwrap().children(), vec![&id("z/id")]);
+
+        // Something different is its own representative.
+        graph.insert(
+            id("c"),
+            value(SchemaletValue::Object(SchemaletValueObject {
+                properties: [("id".to_string(), id("a"))].into(),
+                ..Default::default()
+            })),
+        );
+        assert!(dedup.add(&mut graph, &[id("c")].into()).is_empty());
+        assert_eq!(graph.get(&id("c")).unwrap().children(), vec![&id("z")]);
+    }
+}
@@ -0,0 +1,700 @@
+//! Export schemalet graphs for inspection.
+//!
+//! Both the raw graph (i.e. schemalets as extracted from the source
+//! documents) and the canonical graph (i.e. after normalization) can be
+//! exported as JSON or as Graphviz DOT. Edges are labeled with the relation
+//! between the schemalets, which is often enough to see why a schema turned
+//! into the type it did.
+//!
+//! The canonical graph can also be emitted as a simplified JSON Schema; see
+//! [`to_json_schema`].
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+use serde::Serialize;
+
+use crate::schemalet::{
+    CanonicalSchemalet, CanonicalSchemaletDetails, Relation, SchemaRef, Schemalet,
+    SchemaletDetails, SchemaletValue,
+};
+
+/// A node in a schemalet graph.
+pub trait GraphNode: Serialize {
+    /// Subordinate schemalets and their relation to this one.
+    fn edges(&self) -> Vec<(Relation, &SchemaRef)>;
+
+    /// A brief, human-readable summary of the node.
+    fn summary(&self) -> String;
+}
+
+impl GraphNode for Schemalet {
+    fn edges(&self) -> Vec<(Relation, &SchemaRef)> {
+        self.details.edges()
+    }
+
+    fn summary(&self) -> String {
+        let kind = match &self.details {
+            SchemaletDetails::Anything => "anything".to_string(),
+            SchemaletDetails::Nothing => "nothing".to_string(),
+            SchemaletDetails::OneOf(_) => "oneOf".to_string(),
+            SchemaletDetails::AnyOf(_) => "anyOf".to_string(),
+            SchemaletDetails::AllOf(_) => "allOf".to_string(),
+            SchemaletDetails::Not(_) => "not".to_string(),
+            SchemaletDetails::IfThen(..) => "if/then".to_string(),
+            SchemaletDetails::IfThenElse(..) => "if/then/else".to_string(),
+            SchemaletDetails::RawRef(target) => format!("$ref {target}"),
+            SchemaletDetails::RawDynamicRef(target) => format!("$dynamicRef {target}"),
+            SchemaletDetails::Constant(value) => format!("const {value}"),
+            SchemaletDetails::Value(value) => value_summary(value),
+            SchemaletDetails::ExclusiveOneOf(_) => "xor".to_string(),
+            SchemaletDetails::Merge(_) => "merge".to_string(),
+            SchemaletDetails::ResolvedRef(_) => "reference".to_string(),
+            SchemaletDetails::ResolvedDynamicRef(_) => "dynamic reference".to_string(),
+            SchemaletDetails::YesNo { .. } => "yes/no".to_string(),
+        };
+        with_title(kind, self.metadata.title.as_deref())
+    }
+}
+
+impl GraphNode for CanonicalSchemalet {
+    fn edges(&self) -> Vec<(Relation, &SchemaRef)> {
+        self.details.edges()
+    }
+
+    fn summary(&self) -> String {
+        let kind = match &self.details {
+            CanonicalSchemaletDetails::Anything => "anything".to_string(),
+            CanonicalSchemaletDetails::Nothing => "nothing".to_string(),
+            CanonicalSchemaletDetails::Constant(value) => format!("const {value}"),
+            CanonicalSchemaletDetails::Reference(_) => "reference".to_string(),
+            CanonicalSchemaletDetails::Note(_) => "note".to_string(),
+            CanonicalSchemaletDetails::ExclusiveOneOf { .. } => "xor".to_string(),
+            CanonicalSchemaletDetails::Value(value) => value_summary(value),
+        };
+        with_title(kind, self.metadata.title.as_deref())
+    }
+}
+
+fn value_summary(value: &SchemaletValue) -> String {
+    match value {
+        SchemaletValue::Boolean => "boolean".to_string(),
+        SchemaletValue::Array(_) => "array".to_string(),
+        SchemaletValue::Object(_) => "object".to_string(),
+        SchemaletValue::String {
+            format: Some(format),
+            ..
+        } => format!("string ({format})"),
+        SchemaletValue::String { .. } => "string".to_string(),
+        SchemaletValue::Integer {
+            format: Some(format),
+            ..
+        } => format!("integer ({format})"),
+        SchemaletValue::Integer { .. } => "integer".to_string(),
+        SchemaletValue::Number { .. } => "number".to_string(),
+        SchemaletValue::Null => "null".to_string(),
+    }
+}
+
+fn with_title(kind: String, title: Option<&str>) -> String {
+    match title {
+        Some(title) => format!("{kind} \"{title}\""),
+        None => kind,
+    }
+}
+
+#[derive(Serialize)]
+struct JsonGraph<'a, T> {
+    nodes: &'a BTreeMap<SchemaRef, T>,
+    edges: Vec<JsonEdge<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonEdge<'a> {
+    from: &'a SchemaRef,
+    to: &'a SchemaRef,
+    relation: Relation,
+}
+
+/// Export a schemalet graph as JSON. The result is an object with a `nodes`
+/// property that maps each schemalet's id to the schemalet, and an `edges`
+/// property with an array of `{ from, to, relation }` objects.
+pub fn to_json<T: GraphNode>(graph: &BTreeMap<SchemaRef, T>) -> serde_json::Value {
+    let edges = graph
+        .iter()
+        .flat_map(|(from, node)| {
+            node.edges()
+                .into_iter()
+                .map(move |(relation, to)| JsonEdge { from, to, relation })
+        })
+        .collect();
+
+    serde_json::to_value(JsonGraph {
+        nodes: graph,
+        edges,
+    })
+    .unwrap()
+}
+
+/// Export a schemalet graph as a Graphviz DOT digraph. Each node is labeled
+/// with its id and a summary; each edge is labeled with its relation.
+/// Referenced schemalets that are absent from the graph are drawn dashed.
+pub fn to_dot<T: GraphNode>(graph: &BTreeMap<SchemaRef, T>) -> String {
+    let mut out = String::new();
+    let mut missing = BTreeSet::new();
+
+    writeln!(out, "digraph schemalets {{").unwrap();
+    writeln!(out, "    node [shape=box];").unwrap();
+
+    for (id, node) in graph {
+        let label = format!("{id}\n{}", node.summary());
+        writeln!(
+            out,
+            "    {} [label={}];",
+            dot_quote(&id.to_string()),
+            dot_quote(&label)
+        )
+        .unwrap();
+    }
+
+    for (from, node) in graph {
+        for (relation, to) in node.edges() {
+            if !graph.contains_key(to) {
+                missing.insert(to);
+            }
+            writeln!(
+                out,
+                "    {} -> {} [label={}];",
+                dot_quote(&from.to_string()),
+                dot_quote(&to.to_string()),
+                dot_quote(&relation.to_string()),
+            )
+            .unwrap();
+        }
+    }
+
+    for id in missing {
+        writeln!(out, "    {} [style=dashed];", dot_quote(&id.to_string())).unwrap();
+    }
+
+    writeln!(out, "}}").unwrap();
+
+    out
+}
+
+fn dot_quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Emit the canonical graph rooted at `root` as a (simplified) 2020-12 JSON
+/// Schema. Normalization has already merged `allOf`, resolved references, and
+/// expanded `anyOf`, so the result is typically much simpler than the
+/// original schema while accepting the same instances.
+///
+/// Schemalets that are referenced from several places or that participate in
+/// cycles are emitted as `$defs` entries; all others are inlined.
+pub fn to_json_schema(
+    graph: &BTreeMap<SchemaRef, CanonicalSchemalet>,
+    root: &SchemaRef,
+) -> serde_json::Value {
+    let hoisted = find_hoisted(graph, root);
+
+    // Choose a unique, pointer-safe name for each definition.
+    let mut used = BTreeSet::new();
+    let names = hoisted
+        .iter()
+        .map(|id| {
+            let base = def_name(id);
+            let mut name = base.clone();
+            let mut ii = 1;
+            while !used.insert(name.clone()) {
+                name = format!("{base}{ii}");
+                ii += 1;
+            }
+            (*id, name)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let emitter = SchemaEmitter { graph, names };
+
+    let mut schema = match emitter.names.get(root) {
+        Some(name) => serde_json::json!({ "$ref": format!("#/$defs/{name}") }),
+        None => emitter.emit(root),
+    };
+
+    let defs = emitter
+        .names
+        .iter()
+        .map(|(id, name)| (name.clone(), emitter.emit(id)))
+        .collect::<serde_json::Map<_, _>>();
+
+    let object = as_object(&mut schema);
+    object.insert(
+        "$schema".to_string(),
+        "https://json-schema.org/draft/2020-12/schema".into(),
+    );
+    if !defs.is_empty() {
+        object.insert("$defs".to_string(), defs.into());
+    }
+
+    schema
+}
+
+/// Find the schemalets that must become definitions: those with more than one
+/// referrer and those that are the target of a back edge (i.e. that close a
+/// cycle).
+fn find_hoisted<'a>(
+    graph: &'a BTreeMap<SchemaRef, CanonicalSchemalet>,
+    root: &'a SchemaRef,
+) -> BTreeSet<&'a SchemaRef> {
+    let mut referrers = BTreeMap::<&SchemaRef, usize>::new();
+    let mut hoisted = BTreeSet::new();
+    let mut visited = BTreeSet::new();
+    let mut active = BTreeSet::new();
+
+    // Iterative depth-first traversal; the boolean indicates if we're
+    // entering or leaving the node.
+    let mut stack = vec![(root, true)];
+    visited.insert(root);
+
+    while let Some((id, entering)) = stack.pop() {
+        if !entering {
+            active.remove(id);
+            continue;
+        }
+
+        active.insert(id);
+        stack.push((id, false));
+
+        let Some(schemalet) = graph.get(id) else {
+            continue;
+        };
+
+        for child in schemalet.children() {
+            *referrers.entry(child).or_default() += 1;
+            if active.contains(child) {
+                hoisted.insert(child);
+            } else if visited.insert(child) {
+                stack.push((child, true));
+            }
+        }
+    }
+
+    hoisted.extend(
+        referrers
+            .into_iter()
+            .filter_map(|(id, count)| (count > 1).then_some(id)),
+    );
+
+    hoisted
+}
+
+fn def_name(id: &SchemaRef) -> String {
+    let raw = match id {
+        SchemaRef::Id(id) | SchemaRef::Partial(id, _) => id
+            .rsplit(['/', '#'])
+            .find(|segment| !segment.is_empty())
+            .unwrap_or_default(),
+        SchemaRef::Internal(name) => name,
+        SchemaRef::Merge(_) => "merge",
+        SchemaRef::YesNo { .. } => "variant",
+        SchemaRef::Box(inner) => return def_name(inner),
+    };
+
+    let name = raw
+        .chars()
+        .filter(|ch| ch.is_ascii_alphanumeric() || *ch == '_' || *ch == '-')
+        .collect::<String>();
+
+    if name.is_empty() {
+        "schema".to_string()
+    } else {
+        name
+    }
+}
+
+/// Make sure that the schema is in object form so that we can add
+/// properties to it.
+fn as_object(schema: &mut serde_json::Value) -> &mut serde_json::Map<String, serde_json::Value> {
+    if let serde_json::Value::Bool(value) = schema {
+        *schema = if *value {
+            serde_json::json!({})
+        } else {
+            serde_json::json!({ "not": {} })
+        };
+    }
+    schema.as_object_mut().unwrap()
+}
+
+struct SchemaEmitter<'a> {
+    graph: &'a BTreeMap<SchemaRef, CanonicalSchemalet>,
+    names: BTreeMap<&'a SchemaRef, String>,
+}
+
+impl SchemaEmitter<'_> {
+    /// Emit a reference to the given schemalet: either a `$ref` to its
+    /// definition or the schemalet inline.
+    fn subschema(&self, id: &SchemaRef) -> serde_json::Value {
+        match self.names.get(id) {
+            Some(name) => serde_json::json!({ "$ref": format!("#/$defs/{name}") }),
+            None => self.emit(id),
+        }
+    }
+
+    fn emit(&self, id: &SchemaRef) -> serde_json::Value {
+        let CanonicalSchemalet { metadata, details } = self
+            .graph
+            .get(id)
+            .unwrap_or_else(|| panic!("failed to lookup {id}"));
+
+        let mut schema = match details {
+            CanonicalSchemaletDetails::Anything => serde_json::Value::Bool(true),
+            CanonicalSchemaletDetails::Nothing => serde_json::Value::Bool(false),
+            CanonicalSchemaletDetails::Constant(value) => serde_json::json!({ "const": value }),
+            CanonicalSchemaletDetails::Reference(target)
+            | CanonicalSchemaletDetails::Note(target) => self.subschema(target),
+            CanonicalSchemaletDetails::ExclusiveOneOf { subschemas, .. } => {
+                let subschemas = subschemas
+                    .iter()
+                    .map(|id| self.subschema(id))
+                    .collect::<Vec<_>>();
+                serde_json::json!({ "oneOf": subschemas })
+            }
+            CanonicalSchemaletDetails::Value(value) => self.emit_value(value),
+        };
+
+        // Metadata from this schemalet takes precedence over that of any
+        // inlined target.
+        let metadata = serde_json::to_value(metadata).unwrap();
+        let serde_json::Value::Object(metadata) = metadata else {
+            unreachable!()
+        };
+        if !metadata.is_empty() {
+            as_object(&mut schema).extend(metadata);
+        }
+
+        schema
+    }
+
+    fn emit_value(&self, value: &SchemaletValue) -> serde_json::Value {
+        let mut out = serde_json::Map::new();
+        match value {
+            SchemaletValue::Boolean => {
+                out.insert("type".to_string(), "boolean".into());
+            }
+            SchemaletValue::Array(array) => {
+                let crate::schemalet::SchemaletValueArray {
+                    items,
+                    prefix_items,
+                    max_items,
+                    min_items,
+                    unique_items,
+                } = array;
+                out.insert("type".to_string(), "array".into());
+                if let Some(prefix_items) = prefix_items {
+                    let prefix_items = prefix_items
+                        .iter()
+                        .map(|id| self.subschema(id))
+                        .collect::<Vec<_>>();
+                    out.insert("prefixItems".to_string(), prefix_items.into());
+                }
+                if let Some(items) = items {
+                    out.insert("items".to_string(), self.subschema(items));
+                }
+                if let Some(min_items) = min_items {
+                    out.insert("minItems".to_string(), (*min_items).into());
+                }
+                if let Some(max_items) = max_items {
+                    out.insert("maxItems".to_string(), (*max_items).into());
+                }
+                if let Some(unique_items) = unique_items {
+                    out.insert("uniqueItems".to_string(), (*unique_items).into());
+                }
+            }
+            SchemaletValue::Object(object) => {
+                let crate::schemalet::SchemaletValueObject {
+                    properties,
+                    required,
+                    additional_properties,
+                    property_names,
+                    pattern_properties,
+                    unevaluated_properties,
+                } = object;
+                out.insert("type".to_string(), "object".into());
+                if !properties.is_empty() {
+                    let properties = properties
+                        .iter()
+                        .map(|(name, id)| (name.clone(), self.subschema(id)))
+                        .collect::<serde_json::Map<_, _>>();
+                    out.insert("properties".to_string(), properties.into());
+                }
+                if !required.is_empty() {
+                    out.insert("required".to_string(), required.clone().into());
+                }
+                if let Some(additional_properties) = additional_properties {
+                    out.insert(
+                        "additionalProperties".to_string(),
+                        self.subschema(additional_properties),
+                    );
+                }
+                if let Some(property_names) = property_names {
+                    out.insert("propertyNames".to_string(), self.subschema(property_names));
+                }
+                if let Some(pattern_properties) = pattern_properties {
+                    let pattern_properties = pattern_properties
+                        .iter()
+                        .map(|(pattern, id)| (pattern.clone(), self.subschema(id)))
+                        .collect::<serde_json::Map<_, _>>();
+                    out.insert("patternProperties".to_string(), pattern_properties.into());
+                }
+                if let Some(unevaluated_properties) = unevaluated_properties {
+                    out.insert(
+                        "unevaluatedProperties".to_string(),
+                        self.subschema(unevaluated_properties),
+                    );
+                }
+            }
+            SchemaletValue::String {
+                pattern,
+                format,
+                min_length,
+                max_length,
+            } => {
+                out.insert("type".to_string(), "string".into());
+                if let Some(pattern) = pattern {
+                    out.insert("pattern".to_string(), pattern.clone().into());
+                }
+                if let Some(format) = format {
+                    out.insert("format".to_string(), format.clone().into());
+                }
+                if let Some(min_length) = min_length {
+                    out.insert("minLength".to_string(), (*min_length).into());
+                }
+                if let Some(max_length) = max_length {
+                    out.insert("maxLength".to_string(), (*max_length).into());
+                }
+            }
+            SchemaletValue::Integer {
+                minimum,
+                exclusive_minimum,
+                maximum,
+                exclusive_maximum,
+                ..
+            }
+            | SchemaletValue::Number {
+                minimum,
+                exclusive_minimum,
+                maximum,
+                exclusive_maximum,
+            } => {
+                let typ = if matches!(value, SchemaletValue::Integer { .. }) {
+                    "integer"
+                } else {
+                    "number"
+                };
+                out.insert("type".to_string(), typ.into());
+                if let SchemaletValue::Integer {
+                    format: Some(format),
+                    ..
+                } = value
+                {
+                    out.insert("format".to_string(), format.clone().into());
+                }
+                if let Some(minimum) = minimum {
+                    out.insert("minimum".to_string(), minimum.clone().into());
+                }
+                if let Some(exclusive_minimum) = exclusive_minimum {
+                    out.insert(
+                        "exclusiveMinimum".to_string(),
+                        exclusive_minimum.clone().into(),
+                    );
+                }
+                if let Some(maximum) = maximum {
+                    out.insert("maximum".to_string(), maximum.clone().into());
+                }
+                if let Some(exclusive_maximum) = exclusive_maximum {
+                    out.insert(
+                        "exclusiveMaximum".to_string(),
+                        exclusive_maximum.clone().into(),
+                    );
+                }
+            }
+            SchemaletValue::Null => {
+                out.insert("type".to_string(), "null".into());
+            }
+        }
+        out.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+
+    use crate::schemalet::{
+        CanonicalSchemalet, CanonicalSchemaletDetails, SchemaRef, SchemaletMetadata,
+        SchemaletValue, SchemaletValueArray, SchemaletValueObject,
+    };
+
+    use super::{to_dot, to_json, to_json_schema};
+
+    fn id(s: &str) -> SchemaRef {
+        SchemaRef::Id(s.to_string())
+    }
+
+    fn value(value: SchemaletValue) -> CanonicalSchemalet {
+        CanonicalSchemalet {
+            metadata: Default::default(),
+            details: CanonicalSchemaletDetails::Value(value),
+        }
+    }
+
+    #[test]
+    fn test_json_schema_defs_and_inlining() {
+        // A tree node with a (shared) name property and a list of children.
+        let graph = BTreeMap::from([
+            (
+                id("x#"),
+                CanonicalSchemalet {
+                    metadata: SchemaletMetadata {
+                        title: Some("Node".to_string()),
+                        ..Default::default()
+                    },
+                    details: CanonicalSchemaletDetails::Value(SchemaletValue::Object(
+                        SchemaletValueObject {
+                            properties: [
+                                ("name".to_string(), id("x#/$defs/name")),
+                                ("alias".to_string(), id("x#/$defs/name")),
+                                ("children".to_string(), id("x#/properties/children")),
+                            ]
+                            .into(),
+                            required: vec!["name".to_string()],
+                            ..Default::default()
+                        },
+                    )),
+                },
+            ),
+            (
+                id("x#/$defs/name"),
+                value(SchemaletValue::String {
+                    pattern: None,
+                    format: None,
+                    min_length: None,
+                    max_length: None,
+                }),
+            ),
+            (
+                id("x#/properties/children"),
+                value(SchemaletValue::Array(SchemaletValueArray {
+                    items: Some(id("x#")),
+                    ..Default::default()
+                })),
+            ),
+        ]);
+
+        let schema = to_json_schema(&graph, &id("x#"));
+
+        assert_eq!(
+            schema,
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$ref": "#/$defs/x",
+                "$defs": {
+                    "x": {
+                        "title": "Node",
+                        "type": "object",
+                        "properties": {
+                            "alias": { "$ref": "#/$defs/name" },
+                            "children": {
+                                "type": "array",
+                                "items": { "$ref": "#/$defs/x" }
+                            },
+                            "name": { "$ref": "#/$defs/name" }
+                        },
+                        "required": ["name"]
+                    },
+                    "name": { "type": "string" }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_json_and_dot() {
+        // An object whose property refers to a named string and to a
+        // schemalet that's missing from the graph.
+        let graph = BTreeMap::from([
+            (
+                id("x#"),
+                value(SchemaletValue::Object(SchemaletValueObject {
+                    properties: [
+                        ("name".to_string(), id("x#/$defs/name")),
+                        ("other".to_string(), id("y#")),
+                    ]
+                    .into(),
+                    ..Default::default()
+                })),
+            ),
+            (
+                id("x#/$defs/name"),
+                CanonicalSchemalet {
+                    metadata: SchemaletMetadata {
+                        title: Some("Name".to_string()),
+                        ..Default::default()
+                    },
+                    details: CanonicalSchemaletDetails::Value(SchemaletValue::String {
+                        pattern: None,
+                        format: Some("email".to_string()),
+                        min_length: None,
+                        max_length: None,
+                    }),
+                },
+            ),
+        ]);
+
+        let json = to_json(&graph);
+        assert_eq!(
+            json["nodes"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>(),
+            ["x#", "x#/$defs/name"]
+        );
+        assert_eq!(
+            json["edges"],
+            json!([
+                { "from": "x#", "to": "x#/$defs/name", "relation": "property name" },
+                { "from": "x#", "to": "y#", "relation": "property other" }
+            ])
+        );
+
+        assert_eq!(
+            to_dot(&graph),
+            r#"digraph schemalets {
+    node [shape=box];
+    "x#" [label="x#\nobject"];
+    "x#/$defs/name" [label="x#/$defs/name\nstring (email) \"Name\""];
+    "x#" -> "x#/$defs/name" [label="property name"];
+    "x#" -> "y#" [label="property other"];
+    "y#" [style=dashed];
+}
+"#
+        );
+    }
+}
@@ -21,7 +21,10 @@ use url::Url;
 mod append_map;
 mod bool_or;
 mod bootstrap;
+pub mod compat;
 pub mod convert;
+mod dedup;
+pub mod export;
 pub mod ir;
 pub mod ir2;
 mod loader;
@@ -1,6 +1,6 @@
 use std::{
     cell::RefCell,
-    collections::{BTreeMap, BTreeSet, VecDeque},
+    collections::{BTreeMap, BTreeSet},
     marker::PhantomData,
     rc::Rc,
 };
@@ -42,114 +42,172 @@ impl<Id> Namespace<Id, NamespaceOpen>
 where
     Id: Ord + Clone + std::fmt::Display + std::fmt::Debug,
 {
+    /// Resolve every name to a unique string. Fixed names come first; if
+    /// several types want the same fixed name, each takes one of its
+    /// suggested names or is qualified with its qualifier (e.g. the name of
+    /// its document). Other names are resolved in waves outward from those
+    /// already resolved, preferring suggested names in order, then names
+    /// derived from a parent, then a qualified name. Names with only a
+    /// default wait until no other names are ready so that they don't take
+    /// names others want. When all candidates are taken we append a number to
+    /// the first of them.
     pub fn finalize(self) -> Result<Namespace<Id, NamespaceFinalized>, Error<Id>> {
         let Self { names, .. } = self;
 
-        println!("finalizing {:#?}", names);
+        let pending = names
+            .iter()
+            .map(|(id, name)| {
+                let NameInner::Pending { hints, .. } = &*name.borrow() else {
+                    unreachable!()
+                };
+                (id.clone(), PendingName::new(hints))
+            })
+            .collect::<BTreeMap<_, _>>();
 
-        // let mut resolved = BTreeMap::new();
-        let mut resolved_names = BTreeSet::<String>::new();
-        let mut resolved_ids = BTreeSet::<Id>::new();
-        let mut id_to_children = BTreeMap::<Id, Vec<Id>>::new();
+        // Every name needs something from which to make a name; a derived
+        // name also needs its parent to be in the namespace.
+        let missing = pending
+            .iter()
+            .filter(|(_, pending)| {
+                pending.fixed.is_none()
+                    && pending.suggested.is_empty()
+                    && pending.default.is_none()
+                    && !pending
+                        .derived
+                        .iter()
+                        .any(|(parent, _)| names.contains_key(parent))
+            })
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(Error::MissingHints(missing));
+        }
 
-        // Make sure that all names have some sort of hint
-        for (id, name) in &names {
-            let xxx = name.borrow();
-            let NameInner::Pending { id: _, hints } = &*xxx else {
-                panic!()
-            };
+        let mut resolved = BTreeMap::<Id, String>::new();
+        let mut taken = BTreeSet::<String>::new();
 
-            let hints = hints.iter().cloned().collect::<Vec<_>>();
-            drop(xxx);
-
-            for hint in &hints {
-                match hint {
-                    NameInnerHint::Fixed(s) => {
-                        assert!(!resolved_names.contains(s));
-                        name.replace(NameInner::Resolved(s.to_pascal_case()));
-                        resolved_names.insert(s.clone());
-                        resolved_ids.insert(id.clone());
-                        break;
-                    }
-                    NameInnerHint::Derive {
-                        parent,
-                        addition: _,
-                    } => {
-                        assert!(names.contains_key(parent));
-                        id_to_children
-                            .entry(parent.clone())
-                            .or_default()
-                            .push(id.clone());
-                    }
-                }
+        let mut by_fixed = BTreeMap::<String, Vec<&Id>>::new();
+        for (id, pending) in &pending {
+            if let Some(fixed) = &pending.fixed {
+                by_fixed.entry(fixed.to_pascal_case()).or_default().push(id);
             }
-
-            assert!(!hints.is_empty());
         }
 
-        // Start with the direct dependants of the names that we've initially
-        // resolved.
-        let mut work = resolved_ids
-            .iter()
-            .inspect(|parent_id| println!("{parent_id}"))
-            .filter_map(|parent_id| id_to_children.get(parent_id))
-            .flatten()
-            .collect::<VecDeque<_>>();
-
-        while let Some(id) = work.pop_front() {
-            if resolved_ids.contains(id) {
+        // Uncontested fixed names first so that qualified names can't take
+        // them.
+        for (fixed, ids) in &by_fixed {
+            if let [id] = ids.as_slice() {
+                taken.insert(fixed.clone());
+                resolved.insert((*id).clone(), fixed.clone());
+            }
+        }
+        for (fixed, ids) in &by_fixed {
+            if ids.len() < 2 {
                 continue;
             }
+            for id in ids {
+                let pending = &pending[*id];
+                let qualified = pending
+                    .qualifier
+                    .iter()
+                    .map(|qualifier| format!("{qualifier}{fixed}").to_pascal_case())
+                    .collect::<Vec<_>>();
+                let base = qualified.last().unwrap_or(fixed);
+                let candidates = pending
+                    .suggested
+                    .iter()
+                    .map(|suggested| suggested.to_pascal_case())
+                    .filter(|suggested| suggested != fixed)
+                    .chain(qualified.iter().cloned())
+                    .collect::<Vec<_>>();
+                let name = choose(&mut taken, &candidates, base);
+                resolved.insert((*id).clone(), name);
+            }
+        }
 
-            println!("id {id}");
-
-            let name = names.get(id).unwrap();
-            let xxx = name.borrow();
-            let NameInner::Pending { id: _, hints } = &*xxx else {
-                // Cycle?
-                panic!()
+        loop {
+            let ready = pending
+                .iter()
+                .filter(|(id, pending)| {
+                    !resolved.contains_key(*id)
+                        && (!pending.suggested.is_empty()
+                            || pending
+                                .derived
+                                .iter()
+                                .any(|(parent, _)| resolved.contains_key(parent)))
+                })
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>();
+
+            // Then names with only a default.
+            let ready = match ready.is_empty() {
+                false => ready,
+                true => pending
+                    .iter()
+                    .filter(|(id, pending)| {
+                        !resolved.contains_key(*id) && pending.default.is_some()
+                    })
+                    .map(|(id, _)| id)
+                    .collect(),
             };
 
-            println!("{:#?}", xxx);
-
-            let hints = hints.iter().cloned().collect::<Vec<_>>();
-            drop(xxx);
-            let mut any = false;
-
-            for hint in hints {
-                println!("{:#?}", resolved_names);
-                let NameInnerHint::Derive { parent, addition } = &hint else {
-                    continue;
-                };
-
-                let parent_name = names.get(parent).unwrap().borrow().as_resolved();
-
-                let new_name = format!("{}{}", parent_name, addition);
-                let new_name = new_name.to_pascal_case();
-
-                if !resolved_names.contains(&new_name) {
-                    resolved_names.insert(new_name.clone());
-                    name.replace(NameInner::Resolved(new_name));
+            // Any remaining names derive only from each other; start with
+            // the first of them, using its additions alone.
+            let (ready, orphaned) = match ready.is_empty() {
+                false => (ready, false),
+                true => match pending.keys().find(|id| !resolved.contains_key(*id)) {
+                    Some(id) => (vec![id], true),
+                    None => break,
+                },
+            };
 
-                    if let Some(children) = id_to_children.get(id) {
-                        work.extend(children);
-                    }
-                    any = true;
-                    break;
+            for id in ready {
+                let pending = &pending[id];
+                let parents = pending
+                    .derived
+                    .iter()
+                    .filter_map(|(parent, addition)| {
+                        let parent_name = match orphaned {
+                            true => "",
+                            false => resolved.get(parent)?,
+                        };
+                        Some((parent_name, addition))
+                    })
+                    .collect::<Vec<_>>();
+
+                // Case the addition on its own so that its first letter is
+                // capitalized even when it's appended to a parent's name.
+                let derived = parents
+                    .iter()
+                    .map(|(parent_name, addition)| {
+                        format!("{parent_name}{}", addition.to_pascal_case()).to_pascal_case()
+                    })
+                    .collect::<Vec<_>>();
+
+                let mut candidates = pending
+                    .suggested
+                    .iter()
+                    .map(|suggested| suggested.to_pascal_case())
+                    .collect::<Vec<_>>();
+                if let Some(suggested) = pending.suggested.first() {
+                    candidates.extend(parents.iter().map(|(parent_name, _)| {
+                        format!("{parent_name}{}", suggested.to_pascal_case()).to_pascal_case()
+                    }));
                 }
-            }
-
-            assert!(any);
+                candidates.extend(derived);
+                candidates.extend(pending.default.iter().map(|s| s.to_pascal_case()));
+                if let (Some(qualifier), Some(first)) = (&pending.qualifier, candidates.first()) {
+                    candidates.push(format!("{qualifier}{first}").to_pascal_case());
+                }
+                candidates.dedup();
 
-            resolved_ids.insert(id.clone());
+                let name = choose(&mut taken, &candidates, &candidates[0]);
+                resolved.insert(id.clone(), name);
+            }
         }
 
         for (id, name) in &names {
-            let NameInner::Resolved(s) = &*name.borrow() else {
-                panic!("not all names were resolved {}", id);
-            };
-
-            println!("{id} => {s}");
+            name.replace(NameInner::Resolved(resolved.remove(id).unwrap()));
         }
 
         Ok(Namespace {
@@ -179,7 +237,67 @@ enum NameInner<Id> {
 #[derive(Debug, Clone)]
 enum NameInnerHint<Id> {
     Fixed(String),
+    Suggest(String),
+    Default(String),
     Derive { parent: Id, addition: String },
+    Qualify(String),
+}
+
+/// The hints for a name gathered by kind.
+struct PendingName<Id> {
+    fixed: Option<String>,
+    /// In order of preference.
+    suggested: Vec<String>,
+    default: Option<String>,
+    derived: Vec<(Id, String)>,
+    qualifier: Option<String>,
+}
+
+impl<Id: Clone> PendingName<Id> {
+    fn new(hints: &[NameInnerHint<Id>]) -> Self {
+        let mut pending = Self {
+            fixed: None,
+            suggested: Vec::new(),
+            default: None,
+            derived: Vec::new(),
+            qualifier: None,
+        };
+        for hint in hints {
+            match hint {
+                NameInnerHint::Fixed(s) => {
+                    pending.fixed.get_or_insert_with(|| s.clone());
+                }
+                NameInnerHint::Suggest(s) => pending.suggested.push(s.clone()),
+                NameInnerHint::Default(s) => {
+                    pending.default.get_or_insert_with(|| s.clone());
+                }
+                NameInnerHint::Derive { parent, addition } => {
+                    pending.derived.push((parent.clone(), addition.clone()))
+                }
+                NameInnerHint::Qualify(s) => {
+                    pending.qualifier.get_or_insert_with(|| s.to_pascal_case());
+                }
+            }
+        }
+        pending
+    }
+}
+
+/// The first candidate that isn't taken or, failing that, `base` with the
+/// lowest numeric suffix that isn't taken.
+fn choose(taken: &mut BTreeSet<String>, candidates: &[String], base: &str) -> String {
+    let name = candidates
+        .iter()
+        .find(|candidate| !taken.contains(*candidate))
+        .cloned()
+        .unwrap_or_else(|| {
+            (2..)
+                .map(|n| format!("{base}{n}"))
+                .find(|candidate| !taken.contains(candidate))
+                .unwrap()
+        });
+    taken.insert(name.clone());
+    name
 }
 
 impl<Id> NameInner<Id> {
@@ -219,8 +337,24 @@ where
         hints.push(NameInnerHint::Fixed(s.as_ref().to_string()))
     }
 
+    /// A preferred name, such as a title, that is used if it's available. A
+    /// name may have several suggestions, tried in the order given.
     pub fn suggest_name(&self, s: impl AsRef<str>) {
-        todo!();
+        let NameInner::Pending { hints, .. } = &mut *self.inner.borrow_mut() else {
+            panic!()
+        };
+
+        hints.push(NameInnerHint::Suggest(s.as_ref().to_string()))
+    }
+
+    /// A name that gives way to all others, such as that of a type for a
+    /// string format; it's used only if no other name wants it.
+    pub fn default_name(&self, s: impl AsRef<str>) {
+        let NameInner::Pending { hints, .. } = &mut *self.inner.borrow_mut() else {
+            panic!()
+        };
+
+        hints.push(NameInnerHint::Default(s.as_ref().to_string()))
     }
 
     pub fn derive_name(&self, parent_id: &Id, addition: impl AsRef<str>) {
@@ -233,11 +367,21 @@ where
             addition: addition.as_ref().to_string(),
         })
     }
+
+    /// A prefix, such as the name of a document, used to distinguish this
+    /// name from others that would otherwise be the same.
+    pub fn qualify(&self, s: impl AsRef<str>) {
+        let NameInner::Pending { hints, .. } = &mut *self.inner.borrow_mut() else {
+            panic!()
+        };
+
+        hints.push(NameInnerHint::Qualify(s.as_ref().to_string()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::namespace::Namespace;
+    use crate::namespace::{Error, Namespace};
 
     #[derive(Default)]
     struct IdMaker(usize);
@@ -267,12 +411,148 @@ mod tests {
 
         grandkid.derive_name(kid2.id(), "child");
 
-        let namespace = namespace.finalize();
+        namespace.finalize().unwrap();
 
-        for name in name_holder {
-            println!("{}", name.to_string())
-        }
+        let names = name_holder
+            .into_iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "RootName",
+                "RootNameKid1",
+                "RootNameKid2",
+                "RootNameKid2Child"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_naming_collisions() {
+        let mut id_maker = IdMaker::default();
+        let mut namespace = Namespace::default();
+
+        // The same $defs name in two documents.
+        let orders_item = namespace.make_name(id_maker.next());
+        orders_item.set_name("Item");
+        orders_item.qualify("orders");
+        let inventory_item = namespace.make_name(id_maker.next());
+        inventory_item.set_name("Item");
+        inventory_item.qualify("inventory");
+
+        // Titles are preferred and qualified by a parent when they collide.
+        let titled = namespace.make_name(id_maker.next());
+        titled.suggest_name("line");
+        titled.derive_name(orders_item.id(), "lines");
+        let titled_again = namespace.make_name(id_maker.next());
+        titled_again.suggest_name("line");
+        titled_again.derive_name(inventory_item.id(), "lines");
+
+        // With nothing else available, a number.
+        let same = namespace.make_name(id_maker.next());
+        same.derive_name(orders_item.id(), "lines");
+        let same_again = namespace.make_name(id_maker.next());
+        same_again.derive_name(orders_item.id(), "lines");
+        let same_yet_again = namespace.make_name(id_maker.next());
+        same_yet_again.derive_name(orders_item.id(), "lines");
+
+        let name_holder = vec![
+            &orders_item,
+            &inventory_item,
+            &titled,
+            &titled_again,
+            &same,
+            &same_again,
+            &same_yet_again,
+        ];
+
+        namespace.finalize().unwrap();
+
+        let names = name_holder
+            .into_iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "OrdersItem",
+                "InventoryItem",
+                "Line",
+                "InventoryItemLine",
+                "OrdersItemLines",
+                "OrdersItemLines2",
+                "OrdersItemLines3",
+            ]
+        );
+    }
 
-        panic!()
+    #[test]
+    fn test_naming_suggestions() {
+        let mut id_maker = IdMaker::default();
+        let mut namespace = Namespace::default();
+
+        // Contested fixed names give way to a suggestion before qualifying.
+        let orders_item = namespace.make_name(id_maker.next());
+        orders_item.set_name("Item");
+        orders_item.suggest_name("order line");
+        orders_item.qualify("orders");
+        let inventory_item = namespace.make_name(id_maker.next());
+        inventory_item.set_name("Item");
+        inventory_item.suggest_name("item");
+        inventory_item.qualify("inventory");
+
+        // Suggestions are tried in order.
+        let entry = namespace.make_name(id_maker.next());
+        entry.suggest_name("entry");
+        let record = namespace.make_name(id_maker.next());
+        record.suggest_name("entry");
+        record.suggest_name("record");
+
+        // A default gives way to a suggestion even from a later name.
+        let defaulted = namespace.make_name(id_maker.next());
+        defaulted.default_name("email");
+        let titled = namespace.make_name(id_maker.next());
+        titled.suggest_name("email");
+
+        let name_holder = vec![
+            &orders_item,
+            &inventory_item,
+            &entry,
+            &record,
+            &defaulted,
+            &titled,
+        ];
+
+        namespace.finalize().unwrap();
+
+        let names = name_holder
+            .into_iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "OrderLine",
+                "InventoryItem",
+                "Entry",
+                "Record",
+                "Email2",
+                "Email"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_naming_missing_hints() {
+        let mut namespace = Namespace::<usize>::default();
+        let named = namespace.make_name(1);
+        named.set_name("named");
+        let _unnamed = namespace.make_name(2);
+
+        let Err(Error::MissingHints(missing)) = namespace.finalize() else {
+            panic!("expected missing hints")
+        };
+        assert_eq!(missing, [2]);
     }
 }
@@ -106,8 +106,90 @@ pub struct SchemaletMetadata {
     pub title: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub examples: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "is_false")]
+    pub deprecated: bool,
+    #[serde(rename = "readOnly", skip_serializing_if = "is_false")]
+    pub read_only: bool,
+    #[serde(rename = "writeOnly", skip_serializing_if = "is_false")]
+    pub write_only: bool,
+    #[serde(rename = "$comment", skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// The path of an existing Rust type for the schema (`x-rust-type`).
+    #[serde(rename = "x-rust-type", skip_serializing_if = "Option::is_none")]
+    pub rust_type: Option<String>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl SchemaletMetadata {
+    /// Fold in metadata from the conjuncts of an `allOf`; only conjuncts
+    /// contribute because the annotations of one branch of a disjunction
+    /// needn't describe the merged schema, though those of a conjunct that is
+    /// itself a disjunction do. `self` is the metadata
+    /// of the schema with the `allOf` and takes precedence; the conjuncts
+    /// follow in the order in which they appear in the schema. The rules are:
+    /// - `title`, `description`, and `$comment`: the first value wins.
+    /// - `default`: the outermost value wins; otherwise a value on which all
+    ///   conjuncts with a default agree. Conflicting defaults are dropped
+    ///   rather than picking one that may not be valid for the others.
+    /// - `examples`: all examples are retained (without duplicates).
+    /// - `deprecated`, `readOnly`, and `writeOnly`: if any schema asserts the
+    ///   annotation, the merged schema does as well.
+    /// - `x-rust-type`: only the outermost value is kept; a type for one
+    ///   conjunct says nothing about the merged schema.
+    pub fn merge_conjuncts<'a>(
+        &mut self,
+        conjuncts: impl IntoIterator<Item = &'a SchemaletMetadata>,
+    ) {
+        let mut defaults = Vec::new();
+
+        for conjunct in conjuncts {
+            let SchemaletMetadata {
+                title,
+                description,
+                default,
+                examples,
+                deprecated,
+                read_only,
+                write_only,
+                comment,
+                rust_type: _,
+            } = conjunct;
+
+            if self.title.is_none() {
+                self.title.clone_from(title);
+            }
+            if self.description.is_none() {
+                self.description.clone_from(description);
+            }
+            if self.comment.is_none() {
+                self.comment.clone_from(comment);
+            }
+            defaults.extend(default);
+            for example in examples {
+                if !self.examples.contains(example) {
+                    self.examples.push(example.clone());
+                }
+            }
+            self.deprecated |= deprecated;
+            self.read_only |= read_only;
+            self.write_only |= write_only;
+        }
+
+        if self.default.is_none() {
+            if let Some((first, rest)) = defaults.split_first() {
+                if rest.iter().all(|default| default == first) {
+                    self.default = Some((*first).clone());
+                }
+            }
+        }
+    }
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -130,6 +212,10 @@ pub enum SchemaletDetails {
 
     // Synthetic
     ExclusiveOneOf(Vec<SchemaRef>),
+    // A conjunction that we construct (e.g. for each variant of a oneOf
+    // within an allOf); unlike an `allOf`, it takes no metadata from its
+    // subschemas.
+    Merge(Vec<SchemaRef>),
     ResolvedRef(SchemaRef),
     ResolvedDynamicRef(SchemaRef),
     YesNo { yes: SchemaRef, no: Vec<SchemaRef> },
@@ -145,18 +231,33 @@ pub enum SchemaletValue {
         pattern: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         format: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        min_length: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_length: Option<u64>,
     },
     Integer {
         #[serde(skip_serializing_if = "Option::is_none")]
-        minimum: Option<i64>,
+        minimum: Option<serde_json::Number>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        exclusive_minimum: Option<serde_json::Number>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        maximum: Option<serde_json::Number>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        exclusive_minimum: Option<i64>,
+        exclusive_maximum: Option<serde_json::Number>,
+        /// e.g. the OpenAPI formats "int32" or "uint8"
+        #[serde(skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
     },
     Number {
         #[serde(skip_serializing_if = "Option::is_none")]
-        minimum: Option<i64>,
+        minimum: Option<serde_json::Number>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        exclusive_minimum: Option<serde_json::Number>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        exclusive_minimum: Option<i64>,
+        maximum: Option<serde_json::Number>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        exclusive_maximum: Option<serde_json::Number>,
     },
     Null,
 }
@@ -209,9 +310,8 @@ impl CanonicalSchemaletDetails {
             CanonicalSchemaletDetails::Constant(value) => match value {
                 serde_json::Value::Null => Some(SchemaletType::Null),
                 serde_json::Value::Bool(_) => Some(SchemaletType::Boolean),
-                serde_json::Value::Number(_) => {
-                    todo!()
-                }
+                serde_json::Value::Number(number) if number.is_f64() => Some(SchemaletType::Number),
+                serde_json::Value::Number(_) => Some(SchemaletType::Integer),
                 serde_json::Value::String(_) => Some(SchemaletType::String),
                 serde_json::Value::Array(_) => Some(SchemaletType::Array),
                 serde_json::Value::Object(_) => Some(SchemaletType::Object),
@@ -245,6 +345,129 @@ impl CanonicalSchemaletDetails {
         };
         Some(obj)
     }
+
+    /// References to all subordinate schemalets in a stable order.
+    pub fn children(&self) -> Vec<&SchemaRef> {
+        self.edges().into_iter().map(|(_, child)| child).collect()
+    }
+
+    /// References to all subordinate schemalets along with their relation to
+    /// this schemalet.
+    pub fn edges(&self) -> Vec<(Relation, &SchemaRef)> {
+        match self {
+            CanonicalSchemaletDetails::Anything
+            | CanonicalSchemaletDetails::Nothing
+            | CanonicalSchemaletDetails::Constant(_) => Vec::new(),
+            CanonicalSchemaletDetails::Reference(schema_ref) => {
+                vec![(Relation::Reference, schema_ref)]
+            }
+            CanonicalSchemaletDetails::Note(schema_ref) => vec![(Relation::Note, schema_ref)],
+            CanonicalSchemaletDetails::ExclusiveOneOf { subschemas, .. } => {
+                indexed(Relation::Variant, subschemas)
+            }
+            CanonicalSchemaletDetails::Value(value) => value.edges(),
+        }
+    }
+
+    /// Mutable references to all subordinate schemalets in the same order as
+    /// [`Self::children`].
+    pub fn children_mut(&mut self) -> Vec<&mut SchemaRef> {
+        match self {
+            CanonicalSchemaletDetails::Anything
+            | CanonicalSchemaletDetails::Nothing
+            | CanonicalSchemaletDetails::Constant(_) => Vec::new(),
+            CanonicalSchemaletDetails::Reference(schema_ref)
+            | CanonicalSchemaletDetails::Note(schema_ref) => vec![schema_ref],
+            CanonicalSchemaletDetails::ExclusiveOneOf { subschemas, .. } => {
+                subschemas.iter_mut().collect()
+            }
+            CanonicalSchemaletDetails::Value(value) => value.children_mut(),
+        }
+    }
+}
+
+impl SchemaletValue {
+    fn edges(&self) -> Vec<(Relation, &SchemaRef)> {
+        match self {
+            SchemaletValue::Array(SchemaletValueArray {
+                items,
+                prefix_items,
+                ..
+            }) => prefix_items
+                .iter()
+                .flat_map(|prefix_items| indexed(Relation::PrefixItem, prefix_items))
+                .chain(items.iter().map(|items| (Relation::Items, items)))
+                .collect(),
+            SchemaletValue::Object(SchemaletValueObject {
+                properties,
+                additional_properties,
+                property_names,
+                pattern_properties,
+                unevaluated_properties,
+                ..
+            }) => properties
+                .iter()
+                .map(|(name, schema_ref)| (Relation::Property(name.clone()), schema_ref))
+                .chain(
+                    additional_properties
+                        .iter()
+                        .map(|schema_ref| (Relation::AdditionalProperties, schema_ref)),
+                )
+                .chain(
+                    property_names
+                        .iter()
+                        .map(|schema_ref| (Relation::PropertyNames, schema_ref)),
+                )
+                .chain(
+                    pattern_properties
+                        .iter()
+                        .flatten()
+                        .map(|(pattern, schema_ref)| {
+                            (Relation::PatternProperty(pattern.clone()), schema_ref)
+                        }),
+                )
+                .chain(
+                    unevaluated_properties
+                        .iter()
+                        .map(|schema_ref| (Relation::UnevaluatedProperties, schema_ref)),
+                )
+                .collect(),
+            SchemaletValue::Boolean
+            | SchemaletValue::String { .. }
+            | SchemaletValue::Integer { .. }
+            | SchemaletValue::Number { .. }
+            | SchemaletValue::Null => Vec::new(),
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut SchemaRef> {
+        match self {
+            SchemaletValue::Array(SchemaletValueArray {
+                items,
+                prefix_items,
+                ..
+            }) => prefix_items.iter_mut().flatten().chain(items).collect(),
+            SchemaletValue::Object(SchemaletValueObject {
+                properties,
+                additional_properties,
+                property_names,
+                pattern_properties,
+                unevaluated_properties,
+                ..
+            }) => properties
+                .values_mut()
+                .chain(additional_properties)
+                .chain(property_names)
+                .chain(pattern_properties.iter_mut().flat_map(|pp| pp.values_mut()))
+                .chain(unevaluated_properties)
+                .collect(),
+            SchemaletValue::Boolean
+            | SchemaletValue::String { .. }
+            | SchemaletValue::Integer { .. }
+            | SchemaletValue::Number { .. }
+            | SchemaletValue::Null => Vec::new(),
+        }
+    }
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -311,6 +534,11 @@ pub struct SchemaletValueObject {
     /// Map from a regex to a schema
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pattern_properties: Option<BTreeMap<String, SchemaRef>>,
+
+    /// Properties not evaluated by this object or by the objects merged
+    /// with it; once merged, this is equivalent to `additional_properties`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unevaluated_properties: Option<SchemaRef>,
 }
 
 pub struct CanonicalSchemaletValueObject {
@@ -344,6 +572,118 @@ pub struct SchemaletValueArray {
     pub unique_items: Option<bool>,
 }
 
+/// The relationship between a schemalet and one of its subordinate
+/// schemalets; this labels the edges of the schemalet graph.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Relation {
+    Property(String),
+    AdditionalProperties,
+    PropertyNames,
+    PatternProperty(String),
+    UnevaluatedProperties,
+    Items,
+    PrefixItem(usize),
+    /// A component of an `allOf` (or a synthetic merge).
+    Merge(usize),
+    AnyOf(usize),
+    OneOf(usize),
+    /// A variant of an exclusive one-of.
+    Variant(usize),
+    Yes,
+    No(usize),
+    Not,
+    If,
+    Then,
+    Else,
+    Reference,
+    Note,
+}
+
+impl Display for Relation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Relation::Property(name) => write!(f, "property {name}"),
+            Relation::AdditionalProperties => f.write_str("additionalProperties"),
+            Relation::PropertyNames => f.write_str("propertyNames"),
+            Relation::PatternProperty(pattern) => write!(f, "patternProperty {pattern}"),
+            Relation::UnevaluatedProperties => f.write_str("unevaluatedProperties"),
+            Relation::Items => f.write_str("items"),
+            Relation::PrefixItem(ii) => write!(f, "prefixItems[{ii}]"),
+            Relation::Merge(ii) => write!(f, "merge[{ii}]"),
+            Relation::AnyOf(ii) => write!(f, "anyOf[{ii}]"),
+            Relation::OneOf(ii) => write!(f, "oneOf[{ii}]"),
+            Relation::Variant(ii) => write!(f, "xor[{ii}]"),
+            Relation::Yes => f.write_str("yes"),
+            Relation::No(ii) => write!(f, "no[{ii}]"),
+            Relation::Not => f.write_str("not"),
+            Relation::If => f.write_str("if"),
+            Relation::Then => f.write_str("then"),
+            Relation::Else => f.write_str("else"),
+            Relation::Reference => f.write_str("reference"),
+            Relation::Note => f.write_str("note"),
+        }
+    }
+}
+
+impl Serialize for Relation {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+fn indexed(
+    relation: impl Fn(usize) -> Relation,
+    schema_refs: &[SchemaRef],
+) -> Vec<(Relation, &SchemaRef)> {
+    schema_refs
+        .iter()
+        .enumerate()
+        .map(|(ii, schema_ref)| (relation(ii), schema_ref))
+        .collect()
+}
+
+impl SchemaletDetails {
+    /// References to all subordinate schemalets along with their relation to
+    /// this schemalet. Unresolved references have no edges.
+    pub fn edges(&self) -> Vec<(Relation, &SchemaRef)> {
+        match self {
+            SchemaletDetails::Anything
+            | SchemaletDetails::Nothing
+            | SchemaletDetails::RawRef(_)
+            | SchemaletDetails::RawDynamicRef(_)
+            | SchemaletDetails::Constant(_) => Vec::new(),
+            SchemaletDetails::OneOf(schema_refs) => indexed(Relation::OneOf, schema_refs),
+            SchemaletDetails::AnyOf(schema_refs) => indexed(Relation::AnyOf, schema_refs),
+            SchemaletDetails::AllOf(schema_refs) | SchemaletDetails::Merge(schema_refs) => {
+                indexed(Relation::Merge, schema_refs)
+            }
+            SchemaletDetails::ExclusiveOneOf(schema_refs) => {
+                indexed(Relation::Variant, schema_refs)
+            }
+            SchemaletDetails::Not(schema_ref) => vec![(Relation::Not, schema_ref)],
+            SchemaletDetails::IfThen(if_ref, then_ref) => {
+                vec![(Relation::If, if_ref), (Relation::Then, then_ref)]
+            }
+            SchemaletDetails::IfThenElse(if_ref, then_ref, else_ref) => vec![
+                (Relation::If, if_ref),
+                (Relation::Then, then_ref),
+                (Relation::Else, else_ref),
+            ],
+            SchemaletDetails::Value(value) => value.edges(),
+            SchemaletDetails::ResolvedRef(schema_ref)
+            | SchemaletDetails::ResolvedDynamicRef(schema_ref) => {
+                vec![(Relation::Reference, schema_ref)]
+            }
+            SchemaletDetails::YesNo { yes, no } => std::iter::once((Relation::Yes, yes))
+                .chain(indexed(Relation::No, no))
+                .collect(),
+        }
+    }
+}
+
 impl Schemalet {
     pub fn new(details: SchemaletDetails, metadata: SchemaletMetadata) -> Self {
         Self { metadata, details }
@@ -359,7 +699,28 @@ impl Schemalet {
     pub fn simplify(self, done: &BTreeMap<SchemaRef, CanonicalSchemalet>) -> State {
         let Self { metadata, details } = self;
         match details {
-            SchemaletDetails::OneOf(..) => todo!(),
+            SchemaletDetails::OneOf(schema_refs) => {
+                // Once the subschemas are canonical we treat this as an
+                // exclusive oneOf. That's only accurate if no value is valid
+                // for more than one of them; when we can't show that (see
+                // `one_of_disjoint`), the converter notes that the resulting
+                // enum takes the first variant that matches.
+                if schema_refs
+                    .iter()
+                    .all(|schema_ref| resolve_note(done, schema_ref).is_some())
+                {
+                    Schemalet {
+                        metadata,
+                        details: SchemaletDetails::ExclusiveOneOf(schema_refs),
+                    }
+                    .simplify(done)
+                } else {
+                    State::Stuck(Schemalet {
+                        metadata,
+                        details: SchemaletDetails::OneOf(schema_refs),
+                    })
+                }
+            }
             SchemaletDetails::Not(..) => todo!(),
             SchemaletDetails::IfThen(..) => todo!(),
             SchemaletDetails::IfThenElse(..) => todo!(),
@@ -368,7 +729,7 @@ impl Schemalet {
             SchemaletDetails::AllOf(schema_refs) => {
                 if let Some(subschemas) = resolve_all(done, &schema_refs) {
                     println!("{}", serde_json::to_string_pretty(&subschemas).unwrap());
-                    merge_all(metadata, subschemas, done)
+                    merge_all(metadata, subschemas, done, true)
                 } else {
                     State::Stuck(Schemalet {
                         metadata,
@@ -376,6 +737,16 @@ impl Schemalet {
                     })
                 }
             }
+            SchemaletDetails::Merge(schema_refs) => {
+                if let Some(subschemas) = resolve_all(done, &schema_refs) {
+                    merge_all(metadata, subschemas, done, false)
+                } else {
+                    State::Stuck(Schemalet {
+                        metadata,
+                        details: SchemaletDetails::Merge(schema_refs),
+                    })
+                }
+            }
             SchemaletDetails::AnyOf(schema_refs) => {
                 if let Some(subschemas) = resolve_all(done, &schema_refs) {
                     println!(
@@ -491,6 +862,90 @@ impl Schemalet {
     }
 }
 
+/// Whether the subschemas of a oneOf are pairwise disjoint; `None` if we
+/// can't tell yet because some schemas aren't canonical. We're not trying
+/// very hard here: subschemas must differ by type, by constant value, or by
+/// the constant value of a required property (i.e. a tag).
+pub(crate) fn one_of_disjoint(
+    done: &BTreeMap<SchemaRef, CanonicalSchemalet>,
+    schema_refs: &[SchemaRef],
+) -> Option<bool> {
+    let subschemas = schema_refs
+        .iter()
+        .map(|schema_ref| resolve_note(done, schema_ref))
+        .collect::<Option<Vec<_>>>()?;
+
+    for (ii, aa) in subschemas.iter().enumerate() {
+        for bb in &subschemas[ii + 1..] {
+            if !disjoint(done, aa, bb)? {
+                return Some(false);
+            }
+        }
+    }
+    Some(true)
+}
+
+fn disjoint(
+    done: &BTreeMap<SchemaRef, CanonicalSchemalet>,
+    aa: &CanonicalSchemalet,
+    bb: &CanonicalSchemalet,
+) -> Option<bool> {
+    match (&aa.details, &bb.details) {
+        (CanonicalSchemaletDetails::Constant(a), CanonicalSchemaletDetails::Constant(b)) => {
+            Some(a != b)
+        }
+        (
+            CanonicalSchemaletDetails::Value(SchemaletValue::Object(a)),
+            CanonicalSchemaletDetails::Value(SchemaletValue::Object(b)),
+        ) => {
+            for prop_name in a.required.iter().filter(|name| b.required.contains(name)) {
+                let (Some(a_ref), Some(b_ref)) =
+                    (a.properties.get(prop_name), b.properties.get(prop_name))
+                else {
+                    continue;
+                };
+                if let (
+                    CanonicalSchemaletDetails::Constant(a_value),
+                    CanonicalSchemaletDetails::Constant(b_value),
+                ) = (
+                    &resolve_note(done, a_ref)?.details,
+                    &resolve_note(done, b_ref)?.details,
+                ) {
+                    if a_value != b_value {
+                        return Some(true);
+                    }
+                }
+            }
+            Some(false)
+        }
+        (CanonicalSchemaletDetails::Nothing, _) | (_, CanonicalSchemaletDetails::Nothing) => {
+            Some(true)
+        }
+        (CanonicalSchemaletDetails::Constant(_), _)
+        | (_, CanonicalSchemaletDetails::Constant(_)) => Some(false),
+        (a, b) => match (a.get_type(), b.get_type()) {
+            (Some(a_type), Some(b_type)) => Some(a_type != b_type),
+            _ => Some(false),
+        },
+    }
+}
+
+/// Follow references and notes to the canonical schemalet they describe.
+pub(crate) fn resolve_note<'a>(
+    done: &'a BTreeMap<SchemaRef, CanonicalSchemalet>,
+    schema_ref: &SchemaRef,
+) -> Option<&'a CanonicalSchemalet> {
+    let mut schemalet = done.get(schema_ref)?;
+    loop {
+        match &schemalet.details {
+            CanonicalSchemaletDetails::Reference(next) | CanonicalSchemaletDetails::Note(next) => {
+                schemalet = done.get(next)?;
+            }
+            _ => break Some(schemalet),
+        }
+    }
+}
+
 fn merge_yes_no(
     yes: (SchemaRef, &CanonicalSchemalet),
     no: Vec<(SchemaRef, &CanonicalSchemalet)>,
@@ -634,7 +1089,7 @@ fn expand_any_of(
                 let merge_ref = SchemaRef::Merge(schema_refs.clone());
                 let merge = Schemalet {
                     metadata: Default::default(),
-                    details: SchemaletDetails::AllOf(schema_refs),
+                    details: SchemaletDetails::Merge(schema_refs),
                 };
 
                 new_work.push((merge_ref.clone(), merge));
@@ -665,11 +1120,21 @@ fn expand_any_of(
 }
 
 // TODO 6/14/2025 not fully sure why we need the done map...
+/// Merge `subschemas`; if they're the conjuncts of an `allOf` (rather than a
+/// merge we constructed) the merged schema takes on their metadata.
 fn merge_all(
     metadata: SchemaletMetadata,
     subschemas: Vec<(SchemaRef, &CanonicalSchemalet)>,
     done: &BTreeMap<SchemaRef, CanonicalSchemalet>,
+    conjuncts: bool,
 ) -> State {
+    // Every conjunct contributes its own metadata in order, including those
+    // that are disjunctions.
+    let mut metadata = metadata;
+    if conjuncts {
+        metadata.merge_conjuncts(subschemas.iter().map(|(_, schema)| &schema.metadata));
+    }
+
     // Separate out xors (disjunctions) from other schemas.
     let mut xors = Vec::new();
     let mut rest = Vec::new();
@@ -743,9 +1208,19 @@ fn merge_all(
         for group in merge_groups {
             let refs = group.into_iter().cloned().collect::<Vec<_>>();
             let new_schemaref = SchemaRef::Merge(refs.clone());
+
+            // Each variant is described by its branches, but not by the
+            // rest of the conjuncts which describe the whole.
+            let mut variant_metadata = SchemaletMetadata::default();
+            variant_metadata.merge_conjuncts(
+                refs[..refs.len() - rest.len()]
+                    .iter()
+                    .filter_map(|schema_ref| resolve(done, schema_ref))
+                    .map(|(_, schema)| &schema.metadata),
+            );
             let new_schemalet = Schemalet {
-                metadata: Default::default(),
-                details: SchemaletDetails::AllOf(refs.clone()),
+                metadata: variant_metadata,
+                details: SchemaletDetails::Merge(refs.clone()),
             };
 
             new_work.push((new_schemaref.clone(), new_schemalet));
@@ -768,15 +1243,11 @@ fn merge_all(
             .map(|(_, schemalet)| schemalet)
             .collect::<Vec<_>>();
 
-        // TODO 6/14/2025
-        // I need to be thoughtful about when I can and don't preserve
-        // metadata. For example, some metadata might become comments on struct
-        // fields.
-
         let mut merged_details = CanonicalSchemaletDetails::Anything;
+        let mut new_work = Vec::new();
 
         for subschema in subschemas {
-            merged_details = merge_two(&merged_details, &subschema.details);
+            merged_details = merge_two(&merged_details, &subschema.details, &mut new_work);
         }
 
         println!(
@@ -784,18 +1255,30 @@ fn merge_all(
             serde_json::to_string_pretty(&merged_details).unwrap()
         );
 
-        let new_schemalet = CanonicalSchemalet {
-            metadata,
-            details: merged_details,
-        };
-
-        State::Canonical(new_schemalet)
+        // If merging required new merges of subschemas (only values have
+        // subschemas) we come back once those are underway.
+        match merged_details {
+            CanonicalSchemaletDetails::Value(value) if !new_work.is_empty() => State::Simplified(
+                Schemalet {
+                    metadata,
+                    details: SchemaletDetails::Value(value),
+                },
+                new_work,
+            ),
+            merged_details => State::Canonical(CanonicalSchemalet {
+                metadata,
+                details: merged_details,
+            }),
+        }
     }
 }
 
+/// Merge two canonical schemalets; merges of their subschemas that we
+/// construct along the way go in `new_work`.
 fn merge_two(
     a: &CanonicalSchemaletDetails,
     b: &CanonicalSchemaletDetails,
+    new_work: &mut Vec<(SchemaRef, Schemalet)>,
 ) -> CanonicalSchemaletDetails {
     match (a.get_type(), b.get_type()) {
         (Some(aa), Some(bb)) if aa != bb => return CanonicalSchemaletDetails::Nothing,
@@ -817,7 +1300,53 @@ fn merge_two(
         (
             CanonicalSchemaletDetails::Value(SchemaletValue::Object(aa)),
             CanonicalSchemaletDetails::Value(SchemaletValue::Object(bb)),
-        ) => merge_two_objects(aa, bb),
+        ) => merge_two_objects(aa, bb, new_work),
+
+        (
+            CanonicalSchemaletDetails::Value(SchemaletValue::String {
+                pattern: a_pattern,
+                format: a_format,
+                min_length: a_min_length,
+                max_length: a_max_length,
+            }),
+            CanonicalSchemaletDetails::Value(SchemaletValue::String {
+                pattern: b_pattern,
+                format: b_format,
+                min_length: b_min_length,
+                max_length: b_max_length,
+            }),
+        ) if compatible(a_pattern, b_pattern) && compatible(a_format, b_format) => {
+            CanonicalSchemaletDetails::Value(SchemaletValue::String {
+                pattern: a_pattern.clone().or_else(|| b_pattern.clone()),
+                format: a_format.clone().or_else(|| b_format.clone()),
+                min_length: (*a_min_length).max(*b_min_length),
+                max_length: match (a_max_length, b_max_length) {
+                    (Some(aa), Some(bb)) => Some(*aa.min(bb)),
+                    (aa, bb) => aa.or(*bb),
+                },
+            })
+        }
+
+        // The types already match, so a constant satisfies an unconstrained
+        // string e.g. an enum value used as a property name.
+        (
+            constant @ CanonicalSchemaletDetails::Constant(_),
+            CanonicalSchemaletDetails::Value(SchemaletValue::String {
+                pattern: None,
+                format: None,
+                min_length: None,
+                max_length: None,
+            }),
+        )
+        | (
+            CanonicalSchemaletDetails::Value(SchemaletValue::String {
+                pattern: None,
+                format: None,
+                min_length: None,
+                max_length: None,
+            }),
+            constant @ CanonicalSchemaletDetails::Constant(_),
+        ) => constant.clone(),
 
         _ => todo!(
             "merge_two {}",
@@ -826,9 +1355,19 @@ fn merge_two(
     }
 }
 
+/// Whether two optional constraints can both hold; we can't represent the
+/// conjunction of differing patterns or formats.
+fn compatible<T: PartialEq>(a: &Option<T>, b: &Option<T>) -> bool {
+    match (a, b) {
+        (Some(aa), Some(bb)) => aa == bb,
+        _ => true,
+    }
+}
+
 fn merge_two_objects(
     aa: &SchemaletValueObject,
     bb: &SchemaletValueObject,
+    new_work: &mut Vec<(SchemaRef, Schemalet)>,
 ) -> CanonicalSchemaletDetails {
     let prop_names = aa.properties.keys().chain(bb.properties.keys());
     let properties = prop_names
@@ -853,9 +1392,37 @@ fn merge_two_objects(
 
     assert!(additional_properties.is_none());
 
+    // Properties of either object have been evaluated, so what remains
+    // unevaluated is what neither object names; those properties must
+    // satisfy both schemas.
+    let unevaluated_properties = match (&aa.unevaluated_properties, &bb.unevaluated_properties) {
+        (None, None) => None,
+        (None, Some(other)) | (Some(other), None) => Some(other.clone()),
+        (Some(a), Some(b)) if a == b => Some(a.clone()),
+        (Some(a), Some(b)) => {
+            let refs = vec![a.clone(), b.clone()];
+            let merged = SchemaRef::Merge(refs.clone());
+            new_work.push((
+                merged.clone(),
+                Schemalet::from_details(SchemaletDetails::Merge(refs)),
+            ));
+            Some(merged)
+        }
+    };
+
+    // A property required by either object is required by the merged one.
+    let mut required = aa.required.clone();
+    for prop_name in &bb.required {
+        if !required.contains(prop_name) {
+            required.push(prop_name.clone());
+        }
+    }
+
     CanonicalSchemaletDetails::Value(SchemaletValue::Object(SchemaletValueObject {
         properties,
+        required,
         additional_properties,
+        unevaluated_properties,
         ..Default::default()
     }))
 }
@@ -881,7 +1448,7 @@ pub fn to_schemalets(resolved: &Resolved<'_>) -> anyhow::Result<Vec<(SchemaRef,
     }
 }
 
-trait Refers {
+pub(crate) trait Refers {
     fn refers(&self) -> Option<&SchemaRef>;
 }
 
@@ -905,7 +1472,7 @@ impl Refers for CanonicalSchemalet {
     }
 }
 
-fn resolve<'a, T>(
+pub(crate) fn resolve<'a, T>(
     wip: &'a BTreeMap<SchemaRef, T>,
     schema_ref: &SchemaRef,
 ) -> Option<(SchemaRef, &'a T)>
@@ -966,15 +1533,9 @@ fn schemalet_to_type_value(
         SchemaletValue::Array(array) => todo!(),
 
         SchemaletValue::Object(object) => schemalet_to_type_value_object(metadata, object, graph),
-        SchemaletValue::String { pattern, format } => todo!(),
-        SchemaletValue::Integer {
-            minimum,
-            exclusive_minimum,
-        } => todo!(),
-        SchemaletValue::Number {
-            minimum,
-            exclusive_minimum,
-        } => todo!(),
+        SchemaletValue::String { .. } => todo!(),
+        SchemaletValue::Integer { .. } => todo!(),
+        SchemaletValue::Number { .. } => todo!(),
         SchemaletValue::Null => todo!(),
     }
 }
@@ -1263,7 +1824,13 @@ impl Serialize for ThingPrinter<'_> {
         let SchemaletMetadata {
             title,
             description,
+            default,
             examples,
+            deprecated,
+            read_only,
+            write_only,
+            comment,
+            rust_type,
         } = metadata;
 
         if let Some(title) = title {
@@ -1272,9 +1839,27 @@ impl Serialize for ThingPrinter<'_> {
         if let Some(description) = description {
             map.serialize_entry("description", description)?;
         }
+        if let Some(default) = default {
+            map.serialize_entry("default", default)?;
+        }
         if !examples.is_empty() {
             map.serialize_entry("examples", examples)?;
         }
+        if *deprecated {
+            map.serialize_entry("deprecated", deprecated)?;
+        }
+        if *read_only {
+            map.serialize_entry("readOnly", read_only)?;
+        }
+        if *write_only {
+            map.serialize_entry("writeOnly", write_only)?;
+        }
+        if let Some(comment) = comment {
+            map.serialize_entry("$comment", comment)?;
+        }
+        if let Some(rust_type) = rust_type {
+            map.serialize_entry("x-rust-type", rust_type)?;
+        }
 
         match details {
             CanonicalSchemaletDetails::Anything => {
@@ -1335,7 +1920,12 @@ impl Serialize for ThingPrinter<'_> {
                         map.serialize_entry("additionalProperties", &additional_properties)?;
                     }
                 }
-                SchemaletValue::String { pattern, format } => {
+                SchemaletValue::String {
+                    pattern,
+                    format,
+                    min_length,
+                    max_length,
+                } => {
                     map.serialize_entry("type", "string")?;
                     if let Some(pattern) = pattern {
                         map.serialize_entry("pattern", pattern)?;
@@ -1343,17 +1933,17 @@ impl Serialize for ThingPrinter<'_> {
                     if let Some(format) = format {
                         map.serialize_entry("format", format)?;
                     }
+                    if let Some(min_length) = min_length {
+                        map.serialize_entry("minLength", min_length)?;
+                    }
+                    if let Some(max_length) = max_length {
+                        map.serialize_entry("maxLength", max_length)?;
+                    }
                 }
-                SchemaletValue::Integer {
-                    minimum,
-                    exclusive_minimum,
-                } => {
+                SchemaletValue::Integer { .. } => {
                     map.serialize_entry("type", "integer")?;
                 }
-                SchemaletValue::Number {
-                    minimum,
-                    exclusive_minimum,
-                } => {
+                SchemaletValue::Number { .. } => {
                     map.serialize_entry("type", "number")?;
                 }
                 SchemaletValue::Null => todo!(),
@@ -1372,3 +1962,134 @@ pub fn schemalet_print(graph: &BTreeMap<SchemaRef, CanonicalSchemalet>, schema_r
 
     println!("{}", serde_json::to_string_pretty(&tp).unwrap());
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{typify::Typify, Bundle};
+
+    use super::{resolve_note, CanonicalSchemaletDetails, SchemaRef, SchemaletMetadata};
+
+    #[test]
+    fn test_merge_conjuncts() {
+        let conjunct = |title: &str, default: Option<serde_json::Value>| SchemaletMetadata {
+            title: Some(title.to_string()),
+            default,
+            examples: vec![json!(title)],
+            read_only: title == "b",
+            ..Default::default()
+        };
+
+        // The first title wins and agreeing defaults survive.
+        let mut metadata = SchemaletMetadata::default();
+        metadata.merge_conjuncts([
+            &conjunct("a", Some(json!(1))),
+            &conjunct("b", None),
+            &conjunct("c", Some(json!(1))),
+        ]);
+        assert_eq!(metadata.title.as_deref(), Some("a"));
+        assert_eq!(metadata.default, Some(json!(1)));
+        assert_eq!(metadata.examples, [json!("a"), json!("b"), json!("c")]);
+        assert!(metadata.read_only);
+
+        // Conflicting defaults are dropped...
+        let mut metadata = SchemaletMetadata::default();
+        metadata.merge_conjuncts([
+            &conjunct("a", Some(json!(1))),
+            &conjunct("b", Some(json!(2))),
+        ]);
+        assert_eq!(metadata.default, None);
+
+        // ... unless the outer schema has a default of its own.
+        let mut metadata = SchemaletMetadata {
+            title: Some("outer".to_string()),
+            default: Some(json!(3)),
+            ..Default::default()
+        };
+        metadata.merge_conjuncts([
+            &conjunct("a", Some(json!(1))),
+            &conjunct("b", Some(json!(2))),
+        ]);
+        assert_eq!(metadata.title.as_deref(), Some("outer"));
+        assert_eq!(metadata.default, Some(json!(3)));
+    }
+
+    #[test]
+    fn test_merge_metadata_from_conjuncts() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://example.com/shape",
+            "allOf": [
+                {
+                    "title": "Base",
+                    "type": "object",
+                    "properties": { "name": { "type": "string" } }
+                },
+                {
+                    "title": "Kinds",
+                    "description": "a circle or a square",
+                    "default": { "kind": "circle" },
+                    "oneOf": [
+                        {
+                            "title": "Circle",
+                            "default": { "kind": "circle" },
+                            "type": "object",
+                            "properties": { "kind": { "const": "circle" } },
+                            "required": ["kind"]
+                        },
+                        {
+                            "title": "Square",
+                            "type": "object",
+                            "properties": { "kind": { "const": "square" } },
+                            "required": ["kind"]
+                        }
+                    ]
+                }
+            ]
+        });
+
+        let mut bundle = Bundle::default();
+        bundle.add_content(schema.to_string()).unwrap();
+        let mut typify = Typify::new_with_bundle(bundle);
+        let root = typify.normalize_by_id("https://example.com/shape").unwrap();
+        let graph = typify.canonical_graph();
+
+        // The merged schema is described by its conjuncts in order,
+        // including the oneOf, but not by the oneOf's branches.
+        let shape = graph.get(&root).unwrap();
+        assert_eq!(shape.metadata.title.as_deref(), Some("Base"));
+        assert_eq!(
+            shape.metadata.description.as_deref(),
+            Some("a circle or a square")
+        );
+        assert_eq!(shape.metadata.default, Some(json!({ "kind": "circle" })));
+
+        // Each variant is described by its branch alone.
+        let CanonicalSchemaletDetails::ExclusiveOneOf { subschemas, .. } = &shape.details else {
+            panic!("expected a oneOf {:#?}", shape.details);
+        };
+        let variants = subschemas
+            .iter()
+            .map(|schema_ref: &SchemaRef| {
+                let variant = resolve_note(graph, schema_ref).unwrap();
+                (
+                    variant.metadata.title.clone(),
+                    variant.metadata.description.clone(),
+                    variant.metadata.default.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            variants,
+            [
+                (
+                    Some("Circle".to_string()),
+                    None,
+                    Some(json!({ "kind": "circle" }))
+                ),
+                (Some("Square".to_string()), None, None),
+            ]
+        );
+    }
+}
@@ -1,11 +1,20 @@
+mod modules;
+mod traits;
 mod type_common;
 mod type_enum;
+mod type_string;
 mod type_struct;
+mod type_tuple;
+mod value;
 
-use syn::parse_quote;
+pub use modules::ModuleSplit;
+use syn::{ext::IdentExt, parse_quote};
+pub use traits::*;
 pub use type_common::*;
 pub use type_enum::*;
+pub use type_string::*;
 pub use type_struct::*;
+pub use type_tuple::*;
 
 use std::collections::{btree_map::Entry, BTreeMap, BTreeSet, VecDeque};
 
@@ -17,13 +26,18 @@ use crate::{namespace::Namespace, schemalet::SchemaRef};
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum NameBuilder {
     Unset,
-    Fixed(String),
     Hints(Vec<NameBuilderHint>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum NameBuilderHint {
+    /// The name by which the schema is known, such as its `$defs` key; other
+    /// types give way to it.
+    Known(String),
     Title(String),
+    /// A name to use absent a title, such as for the root of a document or
+    /// one passed on by a containing type.
+    Fallback(String),
     Parent(SchemaRef, String),
 }
 
@@ -59,12 +73,95 @@ pub enum NameBuilderHint {
 
 pub struct Typespace {
     types: BTreeMap<SchemaRef, Type>,
+    traits: BTreeMap<SchemaRef, BTreeSet<TypeTrait>>,
+    derives: Vec<String>,
+    /// Names of the functions for property defaults by the path of the
+    /// property's container and the property's name.
+    default_fns: BTreeMap<(String, String), String>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// A default value (for a type or for a struct property) that isn't
+    /// valid for the type.
+    InvalidDefault { id: SchemaRef, message: String },
+    /// Types that need names but have nothing from which to make one: no
+    /// fixed name, no title, and no named parent.
+    MissingNames(Vec<SchemaRef>),
+    /// A string format whose `validate` isn't a Rust expression; `name` is
+    /// that of the validated string or, if combined with other constraints,
+    /// the format.
+    InvalidValidator { name: String, message: String },
 }
 // TODO this impl is intended just for goofing around. I'm sort of wondering if
 // these types aren't just "builders"
 impl Typespace {
     pub fn render(&self) -> String {
-        let types = self.types.iter().map(|(id, typ)| match typ {
+        let items = self.render_items(&self.types.keys().collect(), true);
+        let file = parse_quote! {
+            #items
+        };
+        prettyplease::unparse(&file)
+    }
+
+    /// The types with the given ids along with the functions they need.
+    /// Validated strings aren't associated with any particular schema so
+    /// the caller decides where they go.
+    fn render_items(&self, ids: &BTreeSet<&SchemaRef>, validated_strings: bool) -> TokenStream {
+        let types = ids
+            .iter()
+            .map(|id| self.render_type(id, self.types.get(id).unwrap()));
+
+        // Validated strings are shared by name so we emit each only once.
+        let validated_strings = self
+            .types
+            .iter()
+            .filter(|_| validated_strings)
+            .filter_map(|(id, typ)| match typ {
+                Type::ValidatedString(validated) => Some((
+                    validated.built.as_ref().unwrap().name.to_string(),
+                    (id, validated),
+                )),
+                _ => None,
+            })
+            .collect::<BTreeMap<_, _>>()
+            .into_values()
+            .map(|(id, validated)| {
+                render_validated_string(
+                    validated,
+                    self.render_derive(id, &[TypeTrait::Deserialize]),
+                    self.traits.get(id).unwrap(),
+                )
+            });
+
+        let deserialize_some = self.needs_deserialize_some(ids).then(|| {
+            quote! {
+                /// Deserialize a value that's present (even if it's null) as
+                /// `Some`; used for `Option<Option<T>>` properties.
+                fn deserialize_some<'de, T, D>(
+                    deserializer: D,
+                ) -> ::std::result::Result<::std::option::Option<T>, D::Error>
+                where
+                    T: ::serde::Deserialize<'de>,
+                    D: ::serde::Deserializer<'de>,
+                {
+                    T::deserialize(deserializer).map(Some)
+                }
+            }
+        });
+
+        let defaults = self.render_default_fns(ids);
+
+        quote! {
+            #( #types )*
+            #( #validated_strings )*
+            #deserialize_some
+            #defaults
+        }
+    }
+
+    fn render_type(&self, id: &SchemaRef, typ: &Type) -> TokenStream {
+        match typ {
             Type::Enum(type_enum) => {
                 let TypeEnum {
                     name,
@@ -88,6 +185,11 @@ impl Typespace {
                         #[serde(untagged)]
                     },
                 };
+                // Untagged variants are tried in turn so unknown fields are
+                // what let serde move on to the next variant.
+                let deny_unknown_fields = (*deny_unknown_fields
+                    && *tag_type != EnumTagType::Untagged)
+                    .then(|| quote! { #[serde(deny_unknown_fields)] });
 
                 let variants = variants.iter().map(|variant| {
                     let EnumVariant {
@@ -112,11 +214,24 @@ impl Typespace {
                                 (#item_ident)
                             }
                         }
-                        VariantDetails::Tuple(items) => todo!(),
+                        VariantDetails::Tuple(items) => {
+                            let item_idents = items.iter().map(|item| self.render_ident(item));
+                            quote! {
+                                ( #( #item_idents ),* )
+                            }
+                        }
                         VariantDetails::Struct(properties) => {
-                            let properties = properties
-                                .iter()
-                                .map(|struct_prop| self.render_struct_property(struct_prop));
+                            let container = format!(
+                                "{}::{rust_name}",
+                                built.as_ref().unwrap().name.to_string()
+                            );
+                            let properties = properties.iter().map(|struct_prop| {
+                                self.render_struct_property(
+                                    &container,
+                                    &TokenStream::new(),
+                                    struct_prop,
+                                )
+                            });
                             quote! {
                                 {
                                     #( #properties, )*
@@ -134,27 +249,432 @@ impl Typespace {
 
                 let name = built.as_ref().unwrap().name.to_string();
                 let name_ident = format_ident!("{name}");
+                let impl_default = self.render_impl_default(id, &name_ident);
+                let derive = self.render_derive(id, &[TypeTrait::Default]);
+                let builders = type_enum.variants.iter().filter_map(|variant| {
+                    let builder_name = built.as_ref().unwrap().builders.get(&variant.rust_name)?;
+                    let VariantDetails::Struct(properties) = &variant.details else {
+                        unreachable!()
+                    };
+                    Some(self.render_builder(
+                        id,
+                        &builder_name.to_string(),
+                        properties,
+                        &name_ident,
+                        &format!("{name}::{}", variant.rust_name),
+                    ))
+                });
 
                 quote! {
                     // TODO I want to have the original unique id available
                     #description
-                    #[derive(::serde::Deserialize, ::serde::Serialize)]
+                    #derive
                     #serde
+                    #deny_unknown_fields
                     pub enum #name_ident {
                         #( #variants, )*
                     }
+
+                    #impl_default
+                    #( #builders )*
                 }
             }
-            Type::Struct(type_struct) => {
-                println!("{:#?}", type_struct);
-                todo!()
-            }
+            Type::Struct(type_struct) => self.render_struct(id, type_struct),
+            Type::ConstrainedString(type_string) => render_constrained_string(
+                type_string,
+                self.render_derive(id, &[TypeTrait::Deserialize]),
+                self.traits.get(id).unwrap(),
+            ),
+            Type::TupleStruct(type_tuple) => self.render_tuple_struct(id, type_tuple),
+            Type::UniqueVec(type_unique) => self.render_unique_vec(id, type_unique),
+            Type::Constant(type_constant) => render_constant(
+                type_constant,
+                self.render_derive(id, &[TypeTrait::Serialize, TypeTrait::Deserialize]),
+                self.traits.get(id).unwrap(),
+            ),
             _ => quote! {},
+        }
+    }
+
+    fn render_struct(&self, id: &SchemaRef, type_struct: &TypeStruct) -> TokenStream {
+        let TypeStruct {
+            description,
+            properties,
+            built,
+            ..
+        } = type_struct;
+        let name = built.as_ref().unwrap().name.to_string();
+        let name_ident = format_ident!("{name}");
+        let description = description.as_ref().map(|desc| quote! { #[doc = #desc] });
+
+        let vis = quote! { pub };
+        let properties = properties
+            .iter()
+            .map(|struct_prop| self.render_struct_property(&name, &vis, struct_prop));
+        // Serde doesn't support `deny_unknown_fields` alongside `flatten`, but
+        // a struct only has a flattened property to gather up the properties
+        // it doesn't name so there are never unknown fields to deny.
+        let deny_unknown_fields = (type_struct.deny_unknown_fields
+            && self.has_trait(id, TypeTrait::Deserialize))
+        .then(|| quote! { #[serde(deny_unknown_fields)] });
+        let impl_default = self.render_impl_default(id, &name_ident);
+        let derive = self.render_derive(id, &[TypeTrait::Default]);
+        let builder_name = built
+            .as_ref()
+            .unwrap()
+            .builder
+            .as_ref()
+            .map(|builder_name| builder_name.to_string());
+        let builder = builder_name.map(|builder_name| {
+            let builder = self.render_builder(
+                id,
+                &builder_name,
+                &type_struct.properties,
+                &name_ident,
+                &name,
+            );
+            let builder_ident = format_ident!("{builder_name}");
+            let fields = type_struct.properties.iter().map(|prop| &prop.rust_name);
+            quote! {
+                #builder
+
+                impl #name_ident {
+                    pub fn builder() -> #builder_ident {
+                        ::std::default::Default::default()
+                    }
+                }
+
+                impl ::std::convert::From<#name_ident> for #builder_ident {
+                    fn from(value: #name_ident) -> Self {
+                        Self {
+                            #( #fields: Ok(value.#fields), )*
+                        }
+                    }
+                }
+            }
         });
-        let file = parse_quote! {
-            #( #types )*
+
+        quote! {
+            #description
+            #derive
+            #deny_unknown_fields
+            pub struct #name_ident {
+                #( #properties, )*
+            }
+
+            #impl_default
+            #builder
+        }
+    }
+
+    /// A builder named `builder_name` for a struct or for a struct-like enum
+    /// variant (`target` is the path of the struct or variant whose type is
+    /// `target_ident`). Each field holds either a value or the reason there
+    /// isn't one; fields start with the default from the schema if there is
+    /// one, and building reports every field that's missing or whose value
+    /// couldn't be converted.
+    fn render_builder(
+        &self,
+        id: &SchemaRef,
+        builder_name: &str,
+        properties: &[StructProperty],
+        target_ident: &syn::Ident,
+        target: &str,
+    ) -> TokenStream {
+        let builder_ident = format_ident!("{builder_name}");
+        let doc = format!(" A builder for [`{target}`].");
+        let target_path = syn::parse_str::<syn::Path>(target).unwrap();
+        let derive = [TypeTrait::Clone, TypeTrait::Debug]
+            .into_iter()
+            .filter(|type_trait| self.has_trait(id, *type_trait))
+            .collect::<Vec<_>>();
+        let derive = (!derive.is_empty()).then(|| quote! { #[derive( #( #derive ),* )] });
+
+        let rust_names = properties
+            .iter()
+            .map(|prop| &prop.rust_name)
+            .collect::<Vec<_>>();
+        let types = properties
+            .iter()
+            .map(|prop| self.render_property_type(prop))
+            .collect::<Vec<_>>();
+        let initial = properties.iter().map(|prop| match &prop.state {
+            // A flattened map may be empty.
+            StructPropertyState::Required if prop.json_name != StructPropertySerde::Flatten => {
+                let message = format!("no value supplied for {}", prop.rust_name.unraw());
+                quote! { Err(#message.to_string()) }
+            }
+            StructPropertyState::Default(_) => {
+                let fn_name = format_ident!("{}", self.default_fn_name(target, prop));
+                quote! { Ok(defaults::#fn_name()) }
+            }
+            _ => quote! { Ok(::std::default::Default::default()) },
+        });
+        let messages = properties.iter().map(|prop| {
+            format!(
+                "error converting supplied value for {}: {{e}}",
+                prop.rust_name.unraw()
+            )
+        });
+
+        // The setters and `build` share a namespace.
+        let build = (!rust_names.iter().any(|rust_name| *rust_name == "build")).then(|| {
+            quote! {
+                pub fn build(self) -> ::std::result::Result<#target_ident, String> {
+                    self.try_into()
+                }
+            }
+        });
+
+        quote! {
+            #[doc = #doc]
+            #derive
+            pub struct #builder_ident {
+                #( #rust_names: ::std::result::Result<#types, String>, )*
+            }
+
+            impl ::std::default::Default for #builder_ident {
+                fn default() -> Self {
+                    Self {
+                        #( #rust_names: #initial, )*
+                    }
+                }
+            }
+
+            impl #builder_ident {
+                #(
+                    pub fn #rust_names<T>(mut self, value: T) -> Self
+                    where
+                        T: ::std::convert::TryInto<#types>,
+                        T::Error: ::std::fmt::Display,
+                    {
+                        self.#rust_names = value
+                            .try_into()
+                            .map_err(|e| format!(#messages));
+                        self
+                    }
+                )*
+
+                #build
+            }
+
+            impl ::std::convert::TryFrom<#builder_ident> for #target_ident {
+                type Error = String;
+                fn try_from(value: #builder_ident) -> ::std::result::Result<Self, Self::Error> {
+                    let #builder_ident { #( #rust_names, )* } = value;
+                    match ( #( #rust_names, )* ) {
+                        ( #( Ok(#rust_names), )* ) => Ok(#target_path { #( #rust_names, )* }),
+                        ( #( #rust_names, )* ) => Err([ #( #rust_names.err(), )* ]
+                            .into_iter()
+                            .flatten()
+                            .collect::<::std::vec::Vec<_>>()
+                            .join("; ")),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Struct properties of the given types along with the path of their
+    /// container i.e. the struct or the enum variant.
+    fn struct_properties<'a>(
+        &'a self,
+        ids: impl IntoIterator<Item = &'a SchemaRef>,
+    ) -> Vec<(String, &'a StructProperty)> {
+        ids.into_iter()
+            .flat_map(|id| match self.types.get(id).unwrap() {
+                Type::Struct(type_struct) => {
+                    let container = type_struct.built.as_ref().unwrap().name.to_string();
+                    type_struct
+                        .properties
+                        .iter()
+                        .map(|prop| (container.clone(), prop))
+                        .collect()
+                }
+                Type::Enum(type_enum) => {
+                    let name = type_enum.built.as_ref().unwrap().name.to_string();
+                    type_enum
+                        .variants
+                        .iter()
+                        .flat_map(|variant| {
+                            let properties = match &variant.details {
+                                VariantDetails::Struct(properties) => properties.as_slice(),
+                                _ => &[],
+                            };
+                            let container = format!("{name}::{}", variant.rust_name);
+                            properties.iter().map(move |prop| (container.clone(), prop))
+                        })
+                        .collect()
+                }
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
+    /// Functions for `#[serde(default = "...")]` that construct the default
+    /// values of struct properties.
+    fn render_default_fns(&self, ids: &BTreeSet<&SchemaRef>) -> Option<TokenStream> {
+        let fns = self
+            .struct_properties(ids.iter().copied())
+            .into_iter()
+            .filter_map(|(container, prop)| {
+                let StructPropertyState::Default(default) = &prop.state else {
+                    return None;
+                };
+                let fn_name = format_ident!("{}", self.default_fn_name(&container, prop));
+                let ty_ident = self.render_ident(&prop.type_id);
+                // We checked the defaults when we finalized the typespace.
+                let value = self.render_value(&prop.type_id, &default.0).unwrap();
+                Some(quote! {
+                    pub(super) fn #fn_name() -> #ty_ident {
+                        #value
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        (!fns.is_empty()).then(|| {
+            quote! {
+                /// Default values for struct properties.
+                mod defaults {
+                    use super::*;
+
+                    #( #fns )*
+                }
+            }
+        })
+    }
+
+    /// `impl Default` for a type with a default value or for a struct whose
+    /// properties can all be omitted.
+    fn render_impl_default(&self, id: &SchemaRef, name_ident: &syn::Ident) -> Option<TokenStream> {
+        if !self.has_trait(id, TypeTrait::Default) {
+            return None;
+        }
+
+        let value = match self.types.get(id).unwrap() {
+            Type::Enum(TypeEnum {
+                default: Some(default),
+                ..
+            })
+            | Type::Struct(TypeStruct {
+                default: Some(default),
+                ..
+            }) => self.render_value(id, &default.0).unwrap(),
+
+            Type::Struct(
+                type_struct @ TypeStruct {
+                    properties, built, ..
+                },
+            ) if struct_has_default(type_struct) => {
+                let container = built.as_ref().unwrap().name.to_string();
+                let fields = properties.iter().map(|prop| {
+                    let rust_name = &prop.rust_name;
+                    match &prop.state {
+                        StructPropertyState::Default(_) => {
+                            let fn_name =
+                                format_ident!("{}", self.default_fn_name(&container, prop));
+                            quote! { #rust_name: defaults::#fn_name() }
+                        }
+                        // i.e. a flattened map
+                        StructPropertyState::Required => {
+                            quote! { #rust_name: ::std::default::Default::default() }
+                        }
+                        _ => quote! { #rust_name: None },
+                    }
+                });
+                quote! {
+                    Self {
+                        #( #fields, )*
+                    }
+                }
+            }
+
+            _ => return None,
         };
-        prettyplease::unparse(&file)
+
+        Some(quote! {
+            impl ::std::default::Default for #name_ident {
+                fn default() -> Self {
+                    #value
+                }
+            }
+        })
+    }
+
+    fn has_trait(&self, id: &SchemaRef, type_trait: TypeTrait) -> bool {
+        self.traits.get(id).unwrap().contains(&type_trait)
+    }
+
+    /// `#[derive(...)]` for the type's traits (other than those in `manual`
+    /// which we implement by hand) along with any user-specified derives.
+    fn render_derive(&self, id: &SchemaRef, manual: &[TypeTrait]) -> Option<TokenStream> {
+        let traits = self
+            .traits
+            .get(id)
+            .unwrap()
+            .iter()
+            .filter(|type_trait| !manual.contains(type_trait))
+            .map(ToTokens::to_token_stream);
+        let derives = self
+            .derives
+            .iter()
+            .map(|path| syn::parse_str::<syn::Path>(path).unwrap().to_token_stream());
+        let derives = traits.chain(derives).collect::<Vec<_>>();
+
+        (!derives.is_empty()).then(|| {
+            quote! {
+                #[derive( #( #derives ),* )]
+            }
+        })
+    }
+
+    /// Make sure that every default value is valid for its type.
+    fn check_defaults(&self) -> Result<(), Error> {
+        let type_defaults = self.types.iter().filter_map(|(id, typ)| match typ {
+            Type::Enum(TypeEnum {
+                default: Some(default),
+                ..
+            })
+            | Type::Struct(TypeStruct {
+                default: Some(default),
+                ..
+            }) => Some((id, default)),
+            _ => None,
+        });
+        let property_defaults = self
+            .struct_properties(self.types.keys())
+            .into_iter()
+            .filter_map(|(_, prop)| match &prop.state {
+                StructPropertyState::Default(default) => Some((&prop.type_id, default)),
+                _ => None,
+            });
+
+        for (id, default) in type_defaults.chain(property_defaults) {
+            self.render_value(id, &default.0)
+                .map_err(|message| Error::InvalidDefault {
+                    id: id.clone(),
+                    message,
+                })?;
+        }
+        Ok(())
+    }
+
+    fn needs_deserialize_some(&self, ids: &BTreeSet<&SchemaRef>) -> bool {
+        ids.iter().any(|id| {
+            let mut properties: Box<dyn Iterator<Item = &StructProperty>> =
+                match self.types.get(*id).unwrap() {
+                    Type::Struct(type_struct) => Box::new(type_struct.properties.iter()),
+                    Type::Enum(type_enum) => Box::new(type_enum.variants.iter().flat_map(
+                        |variant| match &variant.details {
+                            VariantDetails::Struct(properties) => properties.as_slice(),
+                            _ => &[],
+                        },
+                    )),
+                    _ => return false,
+                };
+            properties.any(|prop| prop.state == StructPropertyState::OptionalNullable)
+        })
     }
 
     fn render_ident(&self, id: &SchemaRef) -> TokenStream {
@@ -165,11 +685,17 @@ impl Typespace {
                 let name_ident = format_ident!("{name}");
                 name_ident.into_token_stream()
             }
-            Type::Struct(_) => {
-                quote! { Ref<"???"> }
+            Type::Struct(type_struct) => {
+                let name = type_struct.built.as_ref().unwrap().name.to_string();
+                format_ident!("{name}").into_token_stream()
             }
             // Type::Native(_) => todo!(),
-            // Type::Option(_) => todo!(),
+            Type::Option(inner_id) => {
+                let inner_ident = self.render_ident(inner_id);
+                quote! {
+                    ::std::option::Option<#inner_ident>
+                }
+            }
             Type::Box(boxed_id) => {
                 let boxed_ident = self.render_ident(boxed_id);
                 quote! {
@@ -189,30 +715,265 @@ impl Typespace {
                     ::std::collections::BTreeMap<#key_ident, #value_ident>
                 }
             }
-            // Type::Set(_) => todo!(),
-            // Type::Array(_, _) => todo!(),
-            // Type::Tuple(items) => todo!(),
-            // Type::Unit => todo!(),
+            Type::Set(item_id) => {
+                let item_ident = self.render_ident(item_id);
+                if self.has_trait(item_id, TypeTrait::Ord) {
+                    quote! {
+                        ::std::collections::BTreeSet<#item_ident>
+                    }
+                } else {
+                    quote! {
+                        ::std::collections::HashSet<#item_ident>
+                    }
+                }
+            }
+            Type::UniqueVec(type_unique) => {
+                let name = type_unique.built.as_ref().unwrap().name.to_string();
+                format_ident!("{name}").into_token_stream()
+            }
+            Type::Constant(type_constant) => {
+                let name = type_constant.built.as_ref().unwrap().name.to_string();
+                format_ident!("{name}").into_token_stream()
+            }
+            Type::Array(item_id, len) => {
+                let item_ident = self.render_ident(item_id);
+                let len = proc_macro2::Literal::usize_unsuffixed(*len);
+                quote! {
+                    [#item_ident; #len]
+                }
+            }
+            Type::Tuple(items) => {
+                let item_idents = items.iter().map(|item| self.render_ident(item));
+                quote! {
+                    ( #( #item_idents, )* )
+                }
+            }
+            Type::TupleStruct(type_tuple) => {
+                let name = type_tuple.built.as_ref().unwrap().name.to_string();
+                format_ident!("{name}").into_token_stream()
+            }
+            Type::Unit => quote! { () },
             Type::Boolean => quote! { bool },
             Type::Integer(name) | Type::Float(name) => syn::parse_str::<syn::TypePath>(name)
                 .unwrap()
                 .to_token_stream(),
             Type::String => quote! { String },
+            Type::ValidatedString(validated) => {
+                let name = validated.built.as_ref().unwrap().name.to_string();
+                format_ident!("{name}").into_token_stream()
+            }
+            Type::ConstrainedString(type_string) => {
+                let name = type_string.built.as_ref().unwrap().name.to_string();
+                format_ident!("{name}").into_token_stream()
+            }
+            Type::Native(TypeNative { path, .. }) => {
+                syn::parse_str::<syn::Type>(path).unwrap().to_token_stream()
+            }
             Type::JsonValue => quote! { ::serde_json::Value },
-            _ => quote! { () },
         }
     }
 
+    /// A tuple struct whose last field holds the trailing items; we need our
+    /// own serde impls to flatten that last field into the sequence.
+    fn render_tuple_struct(&self, id: &SchemaRef, type_tuple: &TypeTupleStruct) -> TokenStream {
+        let TypeTupleStruct {
+            description,
+            prefix,
+            rest,
+            built,
+            ..
+        } = type_tuple;
+        let name = built.as_ref().unwrap().name.to_string();
+        let name_ident = format_ident!("{name}");
+        let description = description.as_ref().map(|desc| quote! { #[doc = #desc] });
+        let expecting = format!("a sequence of at least {} items", prefix.len());
+
+        let prefix_idents = prefix
+            .iter()
+            .map(|item| self.render_ident(item))
+            .collect::<Vec<_>>();
+        let rest_ident = self.render_ident(rest);
+        let prefix_indices = (0..prefix.len()).map(syn::Index::from).collect::<Vec<_>>();
+        let rest_index = syn::Index::from(prefix.len());
+        let prefix_len = prefix.len();
+        let derive = self.render_derive(id, &[TypeTrait::Serialize, TypeTrait::Deserialize]);
+
+        let impl_serialize = self.has_trait(id, TypeTrait::Serialize).then(|| {
+            quote! {
+                impl ::serde::Serialize for #name_ident {
+                    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+                    where
+                        S: ::serde::Serializer,
+                    {
+                        use ::serde::ser::SerializeSeq;
+                        let mut seq =
+                            serializer.serialize_seq(Some(#prefix_len + self.#rest_index.len()))?;
+                        #( seq.serialize_element(&self.#prefix_indices)?; )*
+                        for item in &self.#rest_index {
+                            seq.serialize_element(item)?;
+                        }
+                        seq.end()
+                    }
+                }
+            }
+        });
+
+        let impl_deserialize = self.has_trait(id, TypeTrait::Deserialize).then(|| {
+            quote! {
+                impl<'de> ::serde::Deserialize<'de> for #name_ident {
+                    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+                    where
+                        D: ::serde::Deserializer<'de>,
+                    {
+                        struct Visitor;
+
+                        impl<'de> ::serde::de::Visitor<'de> for Visitor {
+                            type Value = #name_ident;
+
+                            fn expecting(
+                                &self,
+                                formatter: &mut ::std::fmt::Formatter,
+                            ) -> ::std::fmt::Result {
+                                formatter.write_str(#expecting)
+                            }
+
+                            fn visit_seq<A>(
+                                self,
+                                mut seq: A,
+                            ) -> ::std::result::Result<Self::Value, A::Error>
+                            where
+                                A: ::serde::de::SeqAccess<'de>,
+                            {
+                                Ok(#name_ident(
+                                    #(
+                                        seq.next_element()?.ok_or_else(|| {
+                                            ::serde::de::Error::invalid_length(#prefix_indices, &self)
+                                        })?,
+                                    )*
+                                    {
+                                        let mut rest = ::std::vec::Vec::new();
+                                        while let Some(item) = seq.next_element()? {
+                                            rest.push(item);
+                                        }
+                                        rest
+                                    },
+                                ))
+                            }
+                        }
+
+                        deserializer.deserialize_seq(Visitor)
+                    }
+                }
+            }
+        });
+
+        quote! {
+            #description
+            #derive
+            pub struct #name_ident(
+                #( pub #prefix_idents, )*
+                pub ::std::vec::Vec<#rest_ident>,
+            );
+
+            #impl_serialize
+            #impl_deserialize
+        }
+    }
+
+    /// A Vec newtype that rejects duplicate items. As with JSON Schema, items
+    /// are compared as JSON values so the item type needn't implement any
+    /// comparison traits.
+    fn render_unique_vec(&self, id: &SchemaRef, type_unique: &TypeUniqueVec) -> TokenStream {
+        let TypeUniqueVec { item, built, .. } = type_unique;
+        let name = built.as_ref().unwrap().name.to_string();
+        let name_ident = format_ident!("{name}");
+        let item_ident = self.render_ident(item);
+        let derive = self.render_derive(id, &[TypeTrait::Deserialize]);
+        let transparent = self
+            .has_trait(id, TypeTrait::Serialize)
+            .then(|| quote! { #[serde(transparent)] });
+
+        // We compare items as JSON values so we need to be able to serialize
+        // them.
+        let impl_try_from = self.has_trait(item, TypeTrait::Serialize).then(|| {
+            quote! {
+                impl ::std::convert::TryFrom<::std::vec::Vec<#item_ident>> for #name_ident {
+                    type Error = String;
+                    fn try_from(
+                        value: ::std::vec::Vec<#item_ident>,
+                    ) -> ::std::result::Result<Self, Self::Error> {
+                        let values = value
+                            .iter()
+                            .map(::serde_json::to_value)
+                            .collect::<::std::result::Result<::std::vec::Vec<_>, _>>()
+                            .map_err(|e| e.to_string())?;
+                        for (ii, item) in values.iter().enumerate() {
+                            if values[..ii].contains(item) {
+                                return Err(format!("duplicate item {item}"));
+                            }
+                        }
+                        Ok(Self(value))
+                    }
+                }
+            }
+        });
+
+        let impl_deserialize = self.has_trait(id, TypeTrait::Deserialize).then(|| {
+            quote! {
+                impl<'de> ::serde::Deserialize<'de> for #name_ident {
+                    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+                    where
+                        D: ::serde::Deserializer<'de>,
+                    {
+                        Self::try_from(
+                            <::std::vec::Vec<#item_ident> as ::serde::Deserialize>::deserialize(
+                                deserializer,
+                            )?,
+                        )
+                        .map_err(<D::Error as ::serde::de::Error>::custom)
+                    }
+                }
+            }
+        });
+
+        quote! {
+            #derive
+            #transparent
+            pub struct #name_ident(::std::vec::Vec<#item_ident>);
+
+            impl ::std::ops::Deref for #name_ident {
+                type Target = ::std::vec::Vec<#item_ident>;
+                fn deref(&self) -> &Self::Target {
+                    &self.0
+                }
+            }
+
+            impl ::std::convert::From<#name_ident> for ::std::vec::Vec<#item_ident> {
+                fn from(value: #name_ident) -> Self {
+                    value.0
+                }
+            }
+
+            #impl_try_from
+            #impl_deserialize
+        }
+    }
+
+    /// A field of a struct or of a struct-like enum variant; `vis` is the
+    /// visibility (which enum variant fields don't have).
     fn render_struct_property(
         &self,
-        StructProperty {
+        container: &str,
+        vis: &TokenStream,
+        struct_prop: &StructProperty,
+    ) -> TokenStream {
+        let StructProperty {
             rust_name,
             json_name,
             state,
             description,
-            type_id,
-        }: &StructProperty,
-    ) -> TokenStream {
+            type_id: _,
+        } = struct_prop;
         let description = description.as_ref().map(|text| {
             quote! {
                 #[doc = #text]
@@ -230,32 +991,52 @@ impl Typespace {
             }
             StructPropertySerde::Flatten => {
                 serde_options.push(quote! {
-                    #[serde(flatten)]
+                    flatten
                 });
             }
         };
 
-        let ty_ident = self.render_ident(type_id);
-
-        let ty_ident = match state {
-            StructPropertyState::Required => ty_ident,
+        match state {
+            // Serde would otherwise treat a missing Option as None.
+            StructPropertyState::Required
+                if *json_name != StructPropertySerde::Flatten
+                    && matches!(self.types.get(&struct_prop.type_id), Some(Type::Option(_))) =>
+            {
+                serde_options.push(quote! {
+                    deserialize_with = "::serde::Deserialize::deserialize"
+                });
+            }
+            StructPropertyState::Required => {}
             StructPropertyState::Optional => {
+                serde_options.push(quote! {
+                    default
+                });
                 serde_options.push(quote! {
                     skip_serializing_if = "::std::option::Option::is_none"
                 });
-                // TODO 7/10/2025
-                // This is interesting and may present an opportunity for
-                // customization. Say the type itself is an Option (e.g.
-                // because there's a oneOf[null, object]). In this case we've
-                // traditionally compressed this down to a single Option, but
-                // we could potentially model this as some other type.
-                quote! {
-                    ::std::option::Option<#ty_ident>
-                }
             }
-            StructPropertyState::Default(json_value) => todo!(),
+            StructPropertyState::OptionalNullable => {
+                // Serde would otherwise deserialize null as the outer None.
+                serde_options.push(quote! {
+                    default
+                });
+                serde_options.push(quote! {
+                    skip_serializing_if = "::std::option::Option::is_none"
+                });
+                serde_options.push(quote! {
+                    deserialize_with = "deserialize_some"
+                });
+            }
+            StructPropertyState::Default(_) => {
+                let fn_name = format!("defaults::{}", self.default_fn_name(container, struct_prop));
+                serde_options.push(quote! {
+                    default = #fn_name
+                });
+            }
         };
 
+        let ty_ident = self.render_property_type(struct_prop);
+
         let serde = (!serde_options.is_empty()).then(|| {
             quote! {
                 #[serde(
@@ -267,19 +1048,321 @@ impl Typespace {
         quote! {
             #description
             #serde
-            #rust_name: #ty_ident
+            #vis #rust_name: #ty_ident
+        }
+    }
+
+    /// The type of a struct property given whether it's optional.
+    fn render_property_type(&self, struct_prop: &StructProperty) -> TokenStream {
+        let StructProperty { state, type_id, .. } = struct_prop;
+        let ty_ident = self.render_ident(type_id);
+
+        match state {
+            StructPropertyState::Required | StructPropertyState::Default(_) => ty_ident,
+            // If the type is already an Option (e.g. because there's a
+            // oneOf[null, object]) we compress this down to a single Option:
+            // absent and null are both None. Users who need to tell them
+            // apart can opt into OptionalNullable.
+            StructPropertyState::Optional => match self.types.get(type_id) {
+                Some(Type::Option(_)) => ty_ident,
+                _ => quote! {
+                    ::std::option::Option<#ty_ident>
+                },
+            },
+            StructPropertyState::OptionalNullable => quote! {
+                ::std::option::Option<#ty_ident>
+            },
+        }
+    }
+}
+
+/// A newtype around `String` that checks values on construction and
+/// deserialization. `check` is the body of a block that evaluates to
+/// `Result<(), String>` with `value: &str` in scope. `derive` omits
+/// Deserialize which we implement by hand.
+fn render_string_newtype(
+    name: &str,
+    description: Option<&String>,
+    check: TokenStream,
+    derive: Option<TokenStream>,
+    traits: &BTreeSet<TypeTrait>,
+) -> TokenStream {
+    let name_ident = format_ident!("{name}");
+    let description = description.map(|desc| quote! { #[doc = #desc] });
+    let transparent = traits
+        .contains(&TypeTrait::Serialize)
+        .then(|| quote! { #[serde(transparent)] });
+    let impl_deserialize = traits.contains(&TypeTrait::Deserialize).then(|| {
+        quote! {
+            impl<'de> ::serde::Deserialize<'de> for #name_ident {
+                fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+                where
+                    D: ::serde::Deserializer<'de>,
+                {
+                    Self::try_from(<String as ::serde::Deserialize>::deserialize(deserializer)?)
+                        .map_err(<D::Error as ::serde::de::Error>::custom)
+                }
+            }
+        }
+    });
+
+    quote! {
+        #description
+        #derive
+        #transparent
+        pub struct #name_ident(String);
+
+        impl ::std::ops::Deref for #name_ident {
+            type Target = str;
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl ::std::fmt::Display for #name_ident {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl ::std::convert::From<#name_ident> for String {
+            fn from(value: #name_ident) -> Self {
+                value.0
+            }
+        }
+
+        impl ::std::convert::TryFrom<String> for #name_ident {
+            type Error = String;
+            fn try_from(value: String) -> ::std::result::Result<Self, Self::Error> {
+                let valid: ::std::result::Result<(), String> = {
+                    let value: &str = &value;
+                    #check
+                };
+                valid?;
+                Ok(Self(value))
+            }
+        }
+
+        impl ::std::str::FromStr for #name_ident {
+            type Err = String;
+            fn from_str(value: &str) -> ::std::result::Result<Self, Self::Err> {
+                Self::try_from(value.to_string())
+            }
+        }
+
+        impl ::std::convert::TryFrom<&str> for #name_ident {
+            type Error = String;
+            fn try_from(value: &str) -> ::std::result::Result<Self, Self::Error> {
+                Self::try_from(value.to_string())
+            }
+        }
+
+        #impl_deserialize
+    }
+}
+
+fn render_validated_string(
+    TypeValidatedString {
+        validate, built, ..
+    }: &TypeValidatedString,
+    derive: Option<TokenStream>,
+    traits: &BTreeSet<TypeTrait>,
+) -> TokenStream {
+    let name = built.as_ref().unwrap().name.to_string();
+    // Checked when finalizing the typespace.
+    let validate = syn::parse_str::<syn::Expr>(validate).unwrap();
+    let message = format!("invalid {name}: {{value:?}}");
+    let check = quote! {
+        let validate: fn(&str) -> bool = #validate;
+        if validate(value) {
+            Ok(())
+        } else {
+            Err(format!(#message))
+        }
+    };
+    render_string_newtype(&name, None, check, derive, traits)
+}
+
+fn render_constrained_string(
+    type_string: &TypeConstrainedString,
+    derive: Option<TokenStream>,
+    traits: &BTreeSet<TypeTrait>,
+) -> TokenStream {
+    let TypeConstrainedString {
+        description,
+        pattern,
+        min_length,
+        max_length,
+        format,
+        built,
+        ..
+    } = type_string;
+    let name = built.as_ref().unwrap().name.to_string();
+
+    let min_length = min_length.map(|min_length| {
+        let min_length = min_length as usize;
+        let message = format!("must be at least {min_length} characters");
+        quote! {
+            if value.chars().count() < #min_length {
+                return Err(#message.to_string());
+            }
+        }
+    });
+    let max_length = max_length.map(|max_length| {
+        let max_length = max_length as usize;
+        let message = format!("must be at most {max_length} characters");
+        quote! {
+            if value.chars().count() > #max_length {
+                return Err(#message.to_string());
+            }
+        }
+    });
+    // Compile the regex once rather than each time we check a value.
+    let pattern = pattern.as_ref().map(|pattern| {
+        let message = format!("must match \"{pattern}\"");
+        quote! {
+            static PATTERN: ::std::sync::LazyLock<::regex::Regex> =
+                ::std::sync::LazyLock::new(|| ::regex::Regex::new(#pattern).unwrap());
+            if !PATTERN.is_match(value) {
+                return Err(#message.to_string());
+            }
+        }
+    });
+
+    // Checked when finalizing the typespace.
+    let format = format.as_ref().map(
+        |StringFormatCheck {
+             format, validate, ..
+         }| {
+            let validate = syn::parse_str::<syn::Expr>(validate).unwrap();
+            let message = format!("must be a valid {format}");
+            quote! {
+                let validate: fn(&str) -> bool = #validate;
+                if !validate(value) {
+                    return Err(#message.to_string());
+                }
+            }
+        },
+    );
+
+    let check = quote! {
+        #min_length
+        #max_length
+        #pattern
+        #format
+        Ok(())
+    };
+
+    render_string_newtype(&name, description.as_ref(), check, derive, traits)
+}
+
+/// A unit struct for a single value. Strings are compared directly; other
+/// values go by way of `serde_json::Value`. `derive` omits the serde traits
+/// which we implement by hand.
+fn render_constant(
+    type_constant: &TypeConstant,
+    derive: Option<TokenStream>,
+    traits: &BTreeSet<TypeTrait>,
+) -> TokenStream {
+    let TypeConstant {
+        description,
+        value,
+        built,
+        ..
+    } = type_constant;
+    let name = built.as_ref().unwrap().name.to_string();
+    let name_ident = format_ident!("{name}");
+    let description = description.as_ref().map(|desc| quote! { #[doc = #desc] });
+
+    let (serialize, deserialize) = match &value.0 {
+        serde_json::Value::String(value) => {
+            let expecting = format!("{value:?}");
+            (
+                quote! {
+                    serializer.serialize_str(#value)
+                },
+                quote! {
+                    let value = <String as ::serde::Deserialize>::deserialize(deserializer)?;
+                    if value == #value {
+                        Ok(Self)
+                    } else {
+                        Err(<D::Error as ::serde::de::Error>::invalid_value(
+                            ::serde::de::Unexpected::Str(&value),
+                            &#expecting,
+                        ))
+                    }
+                },
+            )
+        }
+        value => {
+            let json = value.to_string();
+            let message = format!("expected {json}");
+            (
+                quote! {
+                    let value: ::serde_json::Value = ::serde_json::from_str(#json).unwrap();
+                    ::serde::Serialize::serialize(&value, serializer)
+                },
+                quote! {
+                    let value =
+                        <::serde_json::Value as ::serde::Deserialize>::deserialize(deserializer)?;
+                    let expected: ::serde_json::Value = ::serde_json::from_str(#json).unwrap();
+                    if value == expected {
+                        Ok(Self)
+                    } else {
+                        Err(<D::Error as ::serde::de::Error>::custom(#message))
+                    }
+                },
+            )
         }
+    };
+
+    let impl_serialize = traits.contains(&TypeTrait::Serialize).then(|| {
+        quote! {
+            impl ::serde::Serialize for #name_ident {
+                fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+                where
+                    S: ::serde::Serializer,
+                {
+                    #serialize
+                }
+            }
+        }
+    });
+    let impl_deserialize = traits.contains(&TypeTrait::Deserialize).then(|| {
+        quote! {
+            impl<'de> ::serde::Deserialize<'de> for #name_ident {
+                fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+                where
+                    D: ::serde::Deserializer<'de>,
+                {
+                    #deserialize
+                }
+            }
+        }
+    });
+
+    quote! {
+        #description
+        #derive
+        pub struct #name_ident;
+
+        #impl_serialize
+        #impl_deserialize
     }
 }
 
 pub struct TypespaceBuilder {
     types: BTreeMap<SchemaRef, Type>,
+    traits: TraitSettings,
+    builders: bool,
 }
 
 impl Default for TypespaceBuilder {
     fn default() -> Self {
         Self {
             types: Default::default(),
+            traits: Default::default(),
+            builders: false,
         }
     }
 }
@@ -462,15 +1545,60 @@ impl TypespaceBuilder {
         self.types.contains_key(id)
     }
 
-    pub fn finalize(self) -> Result<Typespace, ()> {
+    /// The traits that generated types should implement.
+    pub fn traits_mut(&mut self) -> &mut TraitSettings {
+        &mut self.traits
+    }
+
+    /// Generate a builder type for each struct and for each struct-like enum
+    /// variant.
+    pub fn set_builders(&mut self, builders: bool) {
+        self.builders = builders;
+    }
+
+    pub fn finalize(self) -> Result<Typespace, Error> {
         // Basic steps:
-        // 1. Construct the parent and child adjacency lists
-        // 2. Figure out names for all types that need them
-        // 3. Break containment cycles with Box types
-        // 4. Propagate trait impls
+        // 1. Break containment cycles with Box types
+        // 2. Propagate trait impls
+        // 3. Construct the parent and child adjacency lists
+        // 4. Figure out names for all types that need them
         // 5. Type-specific finalization
 
-        let Self { mut types } = self;
+        let Self {
+            mut types,
+            traits: trait_settings,
+            builders,
+        } = self;
+
+        // Break cycles. Boxes don't contribute to names so we can do this
+        // before naming, and we need to before figuring out traits because
+        // Box<T> isn't Copy.
+        break_cycles(&mut types);
+
+        let traits = propagate_traits(&types, &trait_settings);
+
+        // A set's items must be Ord (for a BTreeSet) or Hash + Eq (for a
+        // HashSet). If they're neither, we use a Vec that checks for
+        // duplicates instead; trait propagation already accounts for this.
+        let unique_vecs = types
+            .iter()
+            .filter_map(|(id, typ)| match typ {
+                Type::Set(item) => {
+                    let item_traits = traits.get(item).unwrap();
+                    let ord = item_traits.contains(&TypeTrait::Ord);
+                    let hash_eq = item_traits.contains(&TypeTrait::Hash)
+                        && item_traits.contains(&TypeTrait::Eq);
+                    (!ord && !hash_eq).then(|| (id.clone(), item.clone()))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (id, item) in unique_vecs {
+            types.insert(
+                id,
+                Type::UniqueVec(TypeUniqueVec::new(NameBuilder::Unset, item)),
+            );
+        }
 
         // TODO 7/2/2025
         // It's all graphs. Think about everything as a graph traversal.
@@ -521,11 +1649,12 @@ impl TypespaceBuilder {
                     .push(NameBuilderHint::Parent(parent_id.clone(), child_sigil));
             } else {
                 for (grandchild_id, grandchild_sigil) in child_typ.children_with_context() {
-                    work.push_back((
-                        parent_id.clone(),
-                        grandchild_id,
-                        format!("{child_sigil}-=-{grandchild_sigil}"),
-                    ))
+                    let sigil = if grandchild_sigil.is_empty() {
+                        child_sigil.clone()
+                    } else {
+                        format!("{child_sigil}-=-{grandchild_sigil}")
+                    };
+                    work.push_back((parent_id.clone(), grandchild_id, sigil))
                 }
             }
         }
@@ -540,54 +1669,153 @@ impl TypespaceBuilder {
 
         let mut namespace = Namespace::<SchemaRef>::default();
 
+        // Validated strings are shared by name, so each name gets a single
+        // entry in the namespace. Its name is only a default: any other type
+        // that wants that name (e.g. from `$defs` or a title) takes it.
+        let mut validated_names = BTreeMap::new();
+
         for (id, typ) in &mut types {
             match typ {
+                Type::ValidatedString(validated) => {
+                    let name = validated_names
+                        .entry(validated.name.clone())
+                        .or_insert_with(|| {
+                            let name = namespace.make_name(id.clone());
+                            name.default_name(&validated.name);
+                            name
+                        })
+                        .clone();
+                    validated.built = Some(TypeValidatedStringBuilt { name });
+                }
                 Type::Enum(type_enum) => {
-                    let name = match &type_enum.name {
-                        NameBuilder::Unset => unreachable!(),
-                        NameBuilder::Fixed(s) => {
-                            let nn = namespace.make_name(id.clone());
-                            nn.set_name(s);
-                            nn
-                        }
-                        NameBuilder::Hints(hints) => {
-                            let nn = namespace.make_name(id.clone());
-
-                            for hint in hints {
-                                match hint {
-                                    NameBuilderHint::Title(_) => todo!(),
-                                    NameBuilderHint::Parent(id, s) => {
-                                        nn.derive_name(id, s);
-                                    }
-                                }
-                            }
-                            nn
-                        }
-                    };
-                    type_enum.built = Some(TypeEnumBuilt { name });
+                    let name = make_name(&mut namespace, id, &type_enum.name);
+                    let builders = type_enum
+                        .variants
+                        .iter()
+                        .filter(|_| builders)
+                        .filter(|variant| matches!(variant.details, VariantDetails::Struct(_)))
+                        .map(|variant| {
+                            let builder =
+                                make_builder_name(&mut namespace, id, Some(&variant.rust_name));
+                            (variant.rust_name.clone(), builder)
+                        })
+                        .collect();
+                    type_enum.built = Some(TypeEnumBuilt { name, builders });
+                }
+                Type::Struct(type_struct) => {
+                    let name = make_name(&mut namespace, id, &type_struct.name);
+                    let builder = (builders && !type_struct.properties.is_empty())
+                        .then(|| make_builder_name(&mut namespace, id, None));
+                    type_struct.built = Some(TypeStructBuilt { name, builder });
+                }
+                Type::ConstrainedString(type_string) => {
+                    let name = make_name(&mut namespace, id, &type_string.name);
+                    type_string.built = Some(TypeConstrainedStringBuilt { name });
+                }
+                Type::TupleStruct(type_tuple) => {
+                    let name = make_name(&mut namespace, id, &type_tuple.name);
+                    type_tuple.built = Some(TypeTupleStructBuilt { name });
+                }
+                Type::UniqueVec(type_unique) => {
+                    let name = make_name(&mut namespace, id, &type_unique.name);
+                    type_unique.built = Some(TypeUniqueVecBuilt { name });
+                }
+                Type::Constant(type_constant) => {
+                    let name = make_name(&mut namespace, id, &type_constant.name);
+                    type_constant.built = Some(TypeConstantBuilt { name });
                 }
-                Type::Struct(type_struct) => todo!(),
                 _ => {}
             }
 
             println!("{:#?}", typ);
         }
 
-        let n2 = namespace.finalize().unwrap();
+        namespace
+            .finalize()
+            .map_err(|crate::namespace::Error::MissingHints(ids)| Error::MissingNames(ids))?;
 
         // TODO 7/1/2025
         // Let's do names first.
 
         // TODO Make sure that all referenced schemas are present.
-        // TODO break cycles
         // TODO resolve names
-        // TODO propagate trait impls
 
-        // Break cycles
-        break_cycles(&mut types);
+        for typ in types.values() {
+            let (name, validate) = match typ {
+                Type::ValidatedString(TypeValidatedString { name, validate, .. }) => {
+                    (name, validate)
+                }
+                Type::ConstrainedString(TypeConstrainedString {
+                    format:
+                        Some(StringFormatCheck {
+                            format, validate, ..
+                        }),
+                    ..
+                }) => (format, validate),
+                _ => continue,
+            };
+            syn::parse_str::<syn::Expr>(validate).map_err(|e| Error::InvalidValidator {
+                name: name.clone(),
+                message: e.to_string(),
+            })?;
+        }
+
+        let mut typespace = Typespace {
+            types,
+            traits,
+            derives: trait_settings.derives().to_vec(),
+            default_fns: Default::default(),
+        };
+        typespace.default_fns = typespace.default_fn_names();
+        typespace.check_defaults()?;
+
+        Ok(typespace)
+    }
+}
 
-        Ok(Typespace { types })
+fn make_name(
+    namespace: &mut Namespace<SchemaRef>,
+    id: &SchemaRef,
+    name: &NameBuilder,
+) -> crate::namespace::Name<SchemaRef> {
+    let nn = namespace.make_name(id.clone());
+    match name {
+        // With no hints, finalizing the namespace fails.
+        NameBuilder::Unset => {}
+        NameBuilder::Hints(hints) => {
+            for hint in hints {
+                match hint {
+                    NameBuilderHint::Known(s) => nn.set_name(s),
+                    NameBuilderHint::Title(s) | NameBuilderHint::Fallback(s) => nn.suggest_name(s),
+                    NameBuilderHint::Parent(id, s) => nn.derive_name(id, s),
+                }
+            }
+        }
+    }
+    if let Some(document) = modules::document_name(id) {
+        nn.qualify(document);
     }
+    nn
+}
+
+/// Builders share the namespace with types so that, for example, the builder
+/// for `Foo` doesn't collide with a `FooBuilder` from `$defs`. A variant's
+/// builder is named for its enum and the variant.
+fn make_builder_name(
+    namespace: &mut Namespace<SchemaRef>,
+    id: &SchemaRef,
+    variant: Option<&str>,
+) -> crate::namespace::Name<SchemaRef> {
+    let (builder_id, addition) = match variant {
+        Some(variant) => (
+            format!("builder/{id}/{variant}"),
+            format!("{variant}Builder"),
+        ),
+        None => (format!("builder/{id}"), "Builder".to_string()),
+    };
+    let nn = namespace.make_name(SchemaRef::Internal(builder_id));
+    nn.derive_name(id, addition);
+    nn
 }
 
 fn break_cycles(types: &mut BTreeMap<SchemaRef, Type>) {
@@ -720,7 +1948,8 @@ pub enum Type {
     Enum(TypeEnum),
     Struct(TypeStruct),
 
-    Native(String),
+    /// An existing type that we refer to by path.
+    Native(TypeNative),
     Option(SchemaRef),
 
     Box(SchemaRef),
@@ -729,6 +1958,12 @@ pub enum Type {
     Set(SchemaRef),
     Array(SchemaRef, usize),
     Tuple(Vec<SchemaRef>),
+    /// A tuple followed by any number of trailing items; these are named.
+    TupleStruct(TypeTupleStruct),
+    /// A Vec whose items must be unique; these are named.
+    UniqueVec(TypeUniqueVec),
+    /// A single value; these are named.
+    Constant(TypeConstant),
     Unit,
     Boolean,
     /// Integers
@@ -737,6 +1972,10 @@ pub enum Type {
     Float(String),
     /// Strings... which we handle a little specially.
     String,
+    /// Strings of a particular format that we check when deserializing.
+    ValidatedString(TypeValidatedString),
+    /// Strings with constraints such as a pattern; these are named.
+    ConstrainedString(TypeConstrainedString),
     /// serde_json::Value which we also handle specially.
     JsonValue,
 }
@@ -746,12 +1985,15 @@ impl Type {
         if let Some(name) = match self {
             Type::Enum(type_enum) => Some(&mut type_enum.name),
             Type::Struct(type_struct) => Some(&mut type_struct.name),
+            Type::ConstrainedString(type_string) => Some(&mut type_string.name),
+            Type::TupleStruct(type_tuple) => Some(&mut type_tuple.name),
+            Type::UniqueVec(type_unique) => Some(&mut type_unique.name),
+            Type::Constant(type_constant) => Some(&mut type_constant.name),
             _ => None,
         } {
             match name {
                 NameBuilder::Unset => *name = NameBuilder::Hints(hints),
-                NameBuilder::Fixed(_) => {}
-                NameBuilder::Hints(_) => unreachable!(),
+                NameBuilder::Hints(existing) => existing.extend(hints),
             }
         }
     }
@@ -760,13 +2002,21 @@ impl Type {
         match self {
             Type::Enum(type_enum) => Some(&type_enum.name),
             Type::Struct(type_struct) => Some(&type_struct.name),
+            Type::ConstrainedString(type_string) => Some(&type_string.name),
+            Type::TupleStruct(type_tuple) => Some(&type_tuple.name),
+            Type::UniqueVec(type_unique) => Some(&type_unique.name),
+            Type::Constant(type_constant) => Some(&type_constant.name),
             _ => None,
         }
     }
-    fn is_named(&self) -> bool {
+    pub(crate) fn is_named(&self) -> bool {
         match self {
             Type::Enum(type_enum) => true,
             Type::Struct(type_struct) => true,
+            Type::ConstrainedString(_) => true,
+            Type::TupleStruct(_) => true,
+            Type::UniqueVec(_) => true,
+            Type::Constant(_) => true,
             _ => false,
         }
     }
@@ -777,6 +2027,8 @@ impl Type {
             Type::Struct(type_struct) => type_struct.children(),
             Type::Boolean => Vec::new(),
             Type::String => Vec::new(),
+            Type::ValidatedString(_) => Vec::new(),
+            Type::ConstrainedString(_) => Vec::new(),
             Type::Native(_) => Vec::new(),
 
             Type::Option(id)
@@ -784,11 +2036,16 @@ impl Type {
             | Type::Vec(id)
             | Type::Set(id)
             | Type::Array(id, _) => vec![id.clone()],
+            Type::UniqueVec(TypeUniqueVec { item, .. }) => vec![item.clone()],
 
             Type::Map(key_id, value_id) => vec![key_id.clone(), value_id.clone()],
             Type::Tuple(items) => items.clone(),
+            Type::TupleStruct(TypeTupleStruct { prefix, rest, .. }) => {
+                prefix.iter().chain([rest]).cloned().collect()
+            }
 
             Type::Unit => Vec::new(),
+            Type::Constant(_) => Vec::new(),
             Type::Integer(_) => Vec::new(),
             Type::Float(_) => Vec::new(),
             Type::JsonValue => Vec::new(),
@@ -799,23 +2056,38 @@ impl Type {
         match self {
             Type::Enum(type_enum) => type_enum.children_with_context(),
             Type::Struct(type_struct) => type_struct.children_with_context(),
-            Type::Native(_) => todo!(),
-            Type::Option(_) => todo!(),
-            Type::Box(_) => todo!(),
+            Type::Native(_) => Vec::new(),
+            // These don't contribute to the names of the types they wrap.
+            Type::Option(id) | Type::Box(id) => vec![(id.clone(), String::new())],
             Type::Vec(id) => vec![(id.clone(), "item".to_string())],
             Type::Map(key_id, value_id) => vec![
                 (key_id.clone(), "key".to_string()),
                 (value_id.clone(), "value".to_string()),
             ],
-            Type::Set(_) => todo!(),
-            Type::Array(_, _) => todo!(),
-            Type::Tuple(items) => todo!(),
+            Type::Set(id) | Type::UniqueVec(TypeUniqueVec { item: id, .. }) => {
+                vec![(id.clone(), "item".to_string())]
+            }
+            Type::Array(id, _) => vec![(id.clone(), "item".to_string())],
+            Type::Tuple(items) => items
+                .iter()
+                .enumerate()
+                .map(|(ii, id)| (id.clone(), ii.to_string()))
+                .collect(),
+            Type::TupleStruct(TypeTupleStruct { prefix, rest, .. }) => prefix
+                .iter()
+                .enumerate()
+                .map(|(ii, id)| (id.clone(), ii.to_string()))
+                .chain([(rest.clone(), "rest".to_string())])
+                .collect(),
 
             Type::Unit => Vec::new(),
+            Type::Constant(_) => Vec::new(),
             Type::Boolean => Vec::new(),
             Type::Integer(_) => Vec::new(),
             Type::Float(_) => Vec::new(),
             Type::String => Vec::new(),
+            Type::ValidatedString(_) => Vec::new(),
+            Type::ConstrainedString(_) => Vec::new(),
             Type::JsonValue => Vec::new(),
         }
     }
@@ -842,21 +2114,29 @@ impl Type {
                 }
                 out
             }
-            Type::Struct(TypeStruct { properties, .. }) => todo!(),
-            Type::Native(_) => todo!(),
-            Type::Option(_) => todo!(),
-            Type::Array(_, _) => todo!(),
-            Type::Tuple(items) => todo!(),
+            Type::Struct(TypeStruct { properties, .. }) => properties
+                .iter_mut()
+                .map(|StructProperty { type_id, .. }| type_id)
+                .collect(),
+            Type::Option(id) | Type::Array(id, _) => vec![id],
+            Type::Tuple(items) => items.iter_mut().collect(),
+            // The trailing items are in a Vec so they aren't contained.
+            Type::TupleStruct(TypeTupleStruct { prefix, .. }) => prefix.iter_mut().collect(),
 
             Type::Box(_)
             | Type::Vec(_)
             | Type::Map(_, _)
             | Type::Set(_)
+            | Type::UniqueVec(_)
+            | Type::Constant(_)
             | Type::Unit
             | Type::Boolean
             | Type::Integer(_)
             | Type::Float(_)
             | Type::String
+            | Type::ValidatedString(_)
+            | Type::ConstrainedString(_)
+            | Type::Native(_)
             | Type::JsonValue => Default::default(),
         }
     }
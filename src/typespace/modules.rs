@@ -0,0 +1,296 @@
+//! Splitting generated code into a tree of modules written as separate files.
+//! Type names are unique across the typespace so each module can simply
+//! import, by name, any type it mentions that lives in another module.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use heck::ToSnakeCase;
+use proc_macro2::{Spacing, TokenStream, TokenTree};
+use quote::{format_ident, quote};
+use unicode_ident::is_xid_continue;
+use url::Url;
+
+use crate::{
+    schemalet::SchemaRef,
+    typespace::{Type, Typespace},
+};
+
+/// How to divide generated types among modules.
+#[derive(Debug, Clone, Default)]
+pub enum ModuleSplit {
+    /// All types in a single module.
+    #[default]
+    None,
+    /// A module for each source document, named for the last segment of the
+    /// document's path.
+    Document,
+    /// A module for each `$defs` entry holding the types derived from it;
+    /// other types stay in the root module.
+    Defs,
+    /// Modules by schema id prefix e.g. `https://example.com/common.json` to
+    /// `models::common`; the longest matching prefix wins and types that
+    /// match no prefix stay in the root module.
+    Mapping(BTreeMap<String, String>),
+}
+
+impl Typespace {
+    /// Render generated code as a tree of files: `mod.rs` for the root module
+    /// and e.g. `a/b.rs` for module `a::b`. Paths are relative to the
+    /// directory that holds the root module.
+    pub fn render_files(&self, split: &ModuleSplit) -> BTreeMap<PathBuf, String> {
+        let mut modules = BTreeMap::<Vec<String>, BTreeSet<&SchemaRef>>::new();
+        modules.insert(Vec::new(), BTreeSet::new());
+        for (id, typ) in &self.types {
+            if type_name(typ).is_none() || matches!(typ, Type::ValidatedString(_)) {
+                continue;
+            }
+            let path = module_path(split, id);
+            for depth in 0..path.len() {
+                modules.entry(path[..depth].to_vec()).or_default();
+            }
+            modules.entry(path).or_default().insert(id);
+        }
+
+        // Where to find each named type; validated strings live in the root.
+        let locations = modules
+            .iter()
+            .flat_map(|(path, ids)| {
+                ids.iter().map(move |id| {
+                    let name = type_name(self.types.get(id).unwrap()).unwrap();
+                    (name, path.clone())
+                })
+            })
+            .chain(self.types.values().filter_map(|typ| match typ {
+                Type::ValidatedString(_) => Some((type_name(typ)?, Vec::new())),
+                _ => None,
+            }))
+            .collect::<BTreeMap<_, _>>();
+
+        modules
+            .iter()
+            .map(|(path, ids)| {
+                let items = self.render_items(ids, path.is_empty());
+
+                let mut mentioned = BTreeSet::new();
+                mentioned_idents(items.clone(), &mut mentioned);
+                let uses = mentioned.iter().filter_map(|name| {
+                    let type_path = locations.get(name).filter(|type_path| *type_path != path)?;
+                    let prefix = if path.is_empty() {
+                        vec![format_ident!("self")]
+                    } else {
+                        vec![format_ident!("super"); path.len()]
+                    };
+                    let segments = prefix
+                        .into_iter()
+                        .chain(type_path.iter().map(|segment| format_ident!("{segment}")))
+                        .chain([format_ident!("{name}")]);
+                    Some(quote! { use #( #segments )::*; })
+                });
+
+                let children = modules
+                    .keys()
+                    .filter(|child| child.len() == path.len() + 1 && child.starts_with(path))
+                    .map(|child| format_ident!("{}", child.last().unwrap()));
+
+                let file = syn::parse2(quote! {
+                    #( #uses )*
+                    #( pub mod #children; )*
+                    #items
+                })
+                .unwrap();
+
+                let file_path = match path.split_last() {
+                    None => PathBuf::from("mod.rs"),
+                    Some((last, parents)) => parents
+                        .iter()
+                        .collect::<PathBuf>()
+                        .join(format!("{last}.rs")),
+                };
+                (file_path, prettyplease::unparse(&file))
+            })
+            .collect()
+    }
+
+    /// Write the files from [`Self::render_files`] under `dir`.
+    pub fn write_files(&self, dir: impl AsRef<Path>, split: &ModuleSplit) -> std::io::Result<()> {
+        for (file_path, contents) in self.render_files(split) {
+            let file_path = dir.as_ref().join(file_path);
+            if let Some(parent) = file_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(file_path, contents)?;
+        }
+        Ok(())
+    }
+}
+
+/// The name of a type that we generate.
+fn type_name(typ: &Type) -> Option<String> {
+    let name = match typ {
+        Type::Enum(type_enum) => &type_enum.built.as_ref()?.name,
+        Type::Struct(type_struct) => &type_struct.built.as_ref()?.name,
+        Type::ConstrainedString(type_string) => &type_string.built.as_ref()?.name,
+        Type::TupleStruct(type_tuple) => &type_tuple.built.as_ref()?.name,
+        Type::UniqueVec(type_unique) => &type_unique.built.as_ref()?.name,
+        Type::Constant(type_constant) => &type_constant.built.as_ref()?.name,
+        Type::ValidatedString(validated) => &validated.built.as_ref()?.name,
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// The module for the type with the given id.
+fn module_path(split: &ModuleSplit, id: &SchemaRef) -> Vec<String> {
+    let Some(id) = base_id(id) else {
+        return Vec::new();
+    };
+    let (document, fragment) = id.split_once('#').unwrap_or((id, ""));
+
+    match split {
+        ModuleSplit::None => Vec::new(),
+        ModuleSplit::Document => vec![document_stem(document)
+            .map(|stem| module_name(&stem))
+            .unwrap_or_else(|| "document".to_string())],
+        ModuleSplit::Defs => fragment
+            .strip_prefix("/$defs/")
+            .map(|rest| {
+                let def = rest.split('/').next().unwrap();
+                vec![module_name(def)]
+            })
+            .unwrap_or_default(),
+        ModuleSplit::Mapping(mapping) => mapping
+            .iter()
+            .filter(|(prefix, _)| id.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, path)| path.split("::").map(module_name).collect())
+            .unwrap_or_default(),
+    }
+}
+
+/// The name of the document holding the type with the given id e.g.
+/// `common-types` for `https://example.com/common-types.json#/$defs/Money`.
+pub(super) fn document_name(id: &SchemaRef) -> Option<String> {
+    let id = base_id(id)?;
+    let (document, _) = id.split_once('#').unwrap_or((id, ""));
+    document_stem(document)
+}
+
+fn document_stem(document: &str) -> Option<String> {
+    let url = Url::parse(document).ok()?;
+    let segment = url.path_segments()?.next_back()?;
+    let stem = segment.split('.').next().unwrap_or_default();
+    (!stem.is_empty()).then(|| stem.to_string())
+}
+
+/// The schema from which a type was derived; for a merged schema, the first
+/// of its constituents.
+fn base_id(id: &SchemaRef) -> Option<&str> {
+    match id {
+        SchemaRef::Id(id) | SchemaRef::Partial(id, _) => Some(id),
+        SchemaRef::Merge(ids) => ids.first().and_then(base_id),
+        SchemaRef::YesNo { yes, .. } => base_id(yes),
+        SchemaRef::Box(id) => base_id(id),
+        SchemaRef::Internal(_) => None,
+    }
+}
+
+/// A module name for a document or definition; `defaults` is taken by the
+/// functions for default property values.
+fn module_name(name: &str) -> String {
+    let mut out = name.replace(|ch| !is_xid_continue(ch), "-").to_snake_case();
+    if out.starts_with(|ch: char| ch.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    if out == "defaults" || syn::parse_str::<syn::Ident>(&out).is_err() {
+        out.push('_');
+    }
+    out
+}
+
+/// Identifiers at the start of a path (i.e. not preceded by `::`); these are
+/// the names that need to be in scope.
+fn mentioned_idents(tokens: TokenStream, out: &mut BTreeSet<String>) {
+    let mut colons = 0;
+    for token in tokens {
+        match &token {
+            TokenTree::Group(group) => mentioned_idents(group.stream(), out),
+            TokenTree::Ident(ident) if colons < 2 => {
+                out.insert(ident.to_string());
+            }
+            _ => {}
+        }
+        colons = match &token {
+            TokenTree::Punct(punct) if punct.as_char() == ':' => {
+                if punct.spacing() == Spacing::Joint {
+                    1
+                } else {
+                    colons + 1
+                }
+            }
+            _ => 0,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use quote::quote;
+
+    use crate::schemalet::SchemaRef;
+
+    use super::{mentioned_idents, module_path, ModuleSplit};
+
+    #[test]
+    fn test_module_path() {
+        let id = SchemaRef::Id("https://example.com/api/common-types.json#/$defs/Money".into());
+        let nested =
+            SchemaRef::Id("https://example.com/api/common-types.json#/$defs/Money/amount".into());
+        let root = SchemaRef::Id("https://example.com/api/common-types.json#".into());
+
+        assert!(module_path(&ModuleSplit::None, &id).is_empty());
+        assert_eq!(module_path(&ModuleSplit::Document, &id), ["common_types"]);
+        assert_eq!(module_path(&ModuleSplit::Defs, &id), ["money"]);
+        assert_eq!(module_path(&ModuleSplit::Defs, &nested), ["money"]);
+        assert!(module_path(&ModuleSplit::Defs, &root).is_empty());
+
+        let mapping = ModuleSplit::Mapping(
+            [
+                ("https://example.com/api/".to_string(), "api".to_string()),
+                (
+                    "https://example.com/api/common-types.json#/$defs/".to_string(),
+                    "api::defs".to_string(),
+                ),
+            ]
+            .into(),
+        );
+        assert_eq!(module_path(&mapping, &id), ["api", "defs"]);
+        assert_eq!(module_path(&mapping, &root), ["api"]);
+        assert!(module_path(&mapping, &SchemaRef::Internal("string".into())).is_empty());
+    }
+
+    #[test]
+    fn test_mentioned_idents() {
+        let mut mentioned = BTreeSet::new();
+        mentioned_idents(
+            quote! {
+                pub struct Order {
+                    pub color: ::std::option::Option<Color>,
+                    pub lines: Vec<Line>,
+                }
+                const X: Shape = Shape::Circle;
+            },
+            &mut mentioned,
+        );
+        for name in ["Order", "Color", "Line", "Shape", "color", "lines"] {
+            assert!(mentioned.contains(name), "{name}");
+        }
+        for name in ["std", "option", "Option", "Circle"] {
+            assert!(!mentioned.contains(name), "{name}");
+        }
+    }
+}
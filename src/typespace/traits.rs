@@ -0,0 +1,471 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+
+use crate::{
+    schemalet::SchemaRef,
+    typespace::{StructPropertySerde, StructPropertyState, Type, TypeEnum, TypeStruct},
+};
+
+/// The traits that we know how to reason about for generated types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TypeTrait {
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Deserialize,
+    Serialize,
+}
+
+impl TypeTrait {
+    pub const ALL: [TypeTrait; 11] = [
+        TypeTrait::Clone,
+        TypeTrait::Copy,
+        TypeTrait::Debug,
+        TypeTrait::Default,
+        TypeTrait::PartialEq,
+        TypeTrait::Eq,
+        TypeTrait::PartialOrd,
+        TypeTrait::Ord,
+        TypeTrait::Hash,
+        TypeTrait::Deserialize,
+        TypeTrait::Serialize,
+    ];
+
+    /// Traits that are prerequisites for this one e.g. `Eq: PartialEq`.
+    fn supertraits(self) -> &'static [TypeTrait] {
+        match self {
+            TypeTrait::Copy => &[TypeTrait::Clone],
+            TypeTrait::Eq | TypeTrait::PartialOrd => &[TypeTrait::PartialEq],
+            TypeTrait::Ord => &[TypeTrait::Eq, TypeTrait::PartialOrd],
+            _ => &[],
+        }
+    }
+}
+
+impl ToTokens for TypeTrait {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match self {
+            TypeTrait::Clone => quote! { Clone },
+            TypeTrait::Copy => quote! { Copy },
+            TypeTrait::Debug => quote! { Debug },
+            TypeTrait::Default => quote! { Default },
+            TypeTrait::PartialEq => quote! { PartialEq },
+            TypeTrait::Eq => quote! { Eq },
+            TypeTrait::PartialOrd => quote! { PartialOrd },
+            TypeTrait::Ord => quote! { Ord },
+            TypeTrait::Hash => quote! { Hash },
+            TypeTrait::Deserialize => quote! { ::serde::Deserialize },
+            TypeTrait::Serialize => quote! { ::serde::Serialize },
+        })
+    }
+}
+
+/// The traits that generated types should implement. Each type implements
+/// as many of the desired traits as it can; types may also implement traits
+/// that aren't desired if they're required by their context e.g. `Ord` for
+/// items in a `BTreeSet`.
+#[derive(Debug, Clone)]
+pub struct TraitSettings {
+    desired: BTreeSet<TypeTrait>,
+    derives: Vec<String>,
+}
+
+impl Default for TraitSettings {
+    fn default() -> Self {
+        Self {
+            desired: TypeTrait::ALL.into(),
+            derives: Vec::new(),
+        }
+    }
+}
+
+impl TraitSettings {
+    /// Settings with no desired traits i.e. types only implement the traits
+    /// their context requires.
+    pub fn empty() -> Self {
+        Self {
+            desired: Default::default(),
+            derives: Vec::new(),
+        }
+    }
+
+    pub fn insert(&mut self, type_trait: TypeTrait) -> &mut Self {
+        self.desired.insert(type_trait);
+        self
+    }
+
+    pub fn remove(&mut self, type_trait: TypeTrait) -> &mut Self {
+        self.desired.remove(&type_trait);
+        self
+    }
+
+    /// Add a trait (by path e.g. `::schemars::JsonSchema`) for all generated
+    /// types to derive. We don't know anything about these traits so it's up
+    /// to the user to make sure that all types can derive them.
+    pub fn add_derive(&mut self, path: impl ToString) -> &mut Self {
+        self.derives.push(path.to_string());
+        self
+    }
+
+    pub(crate) fn derives(&self) -> &[String] {
+        &self.derives
+    }
+}
+
+/// Figure out the traits for each type. We start with the desired traits for
+/// every type; we propagate forward traits that are required (e.g. `Ord` for
+/// the items of a set) to the types that need them and to the types they
+/// contain. Then we poison backward the traits that types can't implement:
+/// a type only implements a trait if all of its children do. Since types may
+/// be recursive, we iterate until nothing changes.
+pub(crate) fn propagate_traits(
+    types: &BTreeMap<SchemaRef, Type>,
+    settings: &TraitSettings,
+) -> BTreeMap<SchemaRef, BTreeSet<TypeTrait>> {
+    // Validated strings are shared by all the schemas with a given format so
+    // they implement all the traits they can regardless of the settings.
+    // Native types implement exactly the traits they implement.
+    let mut traits = types
+        .iter()
+        .map(|(id, typ)| {
+            let desired = match typ {
+                Type::ValidatedString(_) => TypeTrait::ALL.into(),
+                Type::Native(type_native) => type_native.traits.clone(),
+                _ => settings.desired.clone(),
+            };
+            (id.clone(), desired)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    // Forward propagation
+    let mut work = types
+        .values()
+        .flat_map(|typ| match typ {
+            Type::Set(item_id) => vec![(
+                item_id.clone(),
+                BTreeSet::from([TypeTrait::Ord, TypeTrait::Hash]),
+            )],
+            Type::Map(key_id, _) => vec![(key_id.clone(), BTreeSet::from([TypeTrait::Ord]))],
+            _ => Vec::new(),
+        })
+        .collect::<VecDeque<_>>();
+
+    while let Some((id, required)) = work.pop_front() {
+        let mut required = required;
+        let mut stack = required.iter().copied().collect::<Vec<_>>();
+        while let Some(type_trait) = stack.pop() {
+            for supertrait in type_trait.supertraits() {
+                if required.insert(*supertrait) {
+                    stack.push(*supertrait);
+                }
+            }
+        }
+        // Only a type's own impl of Default matters, not its children's.
+        required.remove(&TypeTrait::Default);
+
+        let type_traits = traits.get_mut(&id).unwrap();
+        let added = required
+            .into_iter()
+            .filter(|type_trait| type_traits.insert(*type_trait))
+            .collect::<BTreeSet<_>>();
+
+        if !added.is_empty() {
+            for child_id in types.get(&id).unwrap().children() {
+                work.push_back((child_id, added.clone()));
+            }
+        }
+    }
+
+    // Backward poisoning
+    loop {
+        let mut changed = false;
+
+        for (id, typ) in types {
+            let supported = supported_traits(typ, &traits);
+            let type_traits = traits.get(id).unwrap();
+            let mut remaining = type_traits
+                .intersection(&supported)
+                .copied()
+                .collect::<BTreeSet<_>>();
+            remaining.retain(|type_trait| {
+                type_trait
+                    .supertraits()
+                    .iter()
+                    .all(|supertrait| type_traits.contains(supertrait))
+            });
+
+            if remaining.len() != type_traits.len() {
+                traits.insert(id.clone(), remaining);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    traits
+}
+
+/// The traits that a type can implement given the traits of its children.
+fn supported_traits(
+    typ: &Type,
+    traits: &BTreeMap<SchemaRef, BTreeSet<TypeTrait>>,
+) -> BTreeSet<TypeTrait> {
+    let all = || TypeTrait::ALL.into_iter().collect::<BTreeSet<_>>();
+    let except = |excluded: &[TypeTrait]| {
+        TypeTrait::ALL
+            .into_iter()
+            .filter(|type_trait| !excluded.contains(type_trait))
+            .collect::<BTreeSet<_>>()
+    };
+    let of_children = |ids: &mut dyn Iterator<Item = &SchemaRef>| {
+        ids.fold(all(), |acc, id| {
+            acc.intersection(traits.get(id).unwrap()).copied().collect()
+        })
+    };
+
+    match typ {
+        Type::Boolean | Type::Integer(_) | Type::Unit => all(),
+        Type::Float(_) => except(&[TypeTrait::Eq, TypeTrait::Ord, TypeTrait::Hash]),
+        Type::String => except(&[TypeTrait::Copy]),
+        Type::ValidatedString(_) | Type::ConstrainedString(_) => {
+            except(&[TypeTrait::Copy, TypeTrait::Default])
+        }
+        Type::Native(type_native) => type_native.traits.clone(),
+        Type::JsonValue => except(&[
+            TypeTrait::Copy,
+            TypeTrait::Eq,
+            TypeTrait::PartialOrd,
+            TypeTrait::Ord,
+            TypeTrait::Hash,
+        ]),
+        Type::Constant(_) => all(),
+
+        Type::Option(id) => with(of_children(&mut [id].into_iter()), TypeTrait::Default),
+        Type::Box(id) => without(of_children(&mut [id].into_iter()), &[TypeTrait::Copy]),
+        Type::Vec(id) => with(
+            without(of_children(&mut [id].into_iter()), &[TypeTrait::Copy]),
+            TypeTrait::Default,
+        ),
+        // The standard library only implements Default for arrays of up to
+        // 32 items.
+        Type::Array(id, len) if *len > 32 => {
+            without(of_children(&mut [id].into_iter()), &[TypeTrait::Default])
+        }
+        Type::Array(id, _) => of_children(&mut [id].into_iter()),
+        // ... and only implements traits for tuples of up to 12 items.
+        Type::Tuple(items) if items.len() > 12 => of_children(&mut items.iter())
+            .intersection(&[TypeTrait::Deserialize, TypeTrait::Serialize].into())
+            .copied()
+            .collect(),
+        Type::Tuple(items) => of_children(&mut items.iter()),
+        Type::Map(key_id, value_id) => with(
+            without(
+                of_children(&mut [key_id, value_id].into_iter()),
+                &[TypeTrait::Copy],
+            ),
+            TypeTrait::Default,
+        ),
+
+        // A BTreeSet if the items are Ord; failing that a HashSet if they're
+        // Hash and Eq; failing that it will become a UniqueVec.
+        Type::Set(id) => {
+            let item = traits.get(id).unwrap();
+            if item.contains(&TypeTrait::Ord) {
+                with(
+                    without(item.clone(), &[TypeTrait::Copy]),
+                    TypeTrait::Default,
+                )
+            } else if item.contains(&TypeTrait::Hash) && item.contains(&TypeTrait::Eq) {
+                with(
+                    without(
+                        item.clone(),
+                        &[
+                            TypeTrait::Copy,
+                            TypeTrait::PartialOrd,
+                            TypeTrait::Ord,
+                            TypeTrait::Hash,
+                        ],
+                    ),
+                    TypeTrait::Default,
+                )
+            } else {
+                unique_vec_traits(item)
+            }
+        }
+        Type::UniqueVec(type_unique) => unique_vec_traits(traits.get(&type_unique.item).unwrap()),
+
+        Type::TupleStruct(_) => {
+            without(of_children(&mut typ.children().iter()), &[TypeTrait::Copy])
+        }
+
+        // We implement Default for structs and enums ourselves (if there's a
+        // default value to use) rather than deriving it.
+        Type::Struct(type_struct) => {
+            let children = without(
+                of_children(&mut typ.children().iter()),
+                &[TypeTrait::Default],
+            );
+            if struct_has_default(type_struct) {
+                with(children, TypeTrait::Default)
+            } else {
+                children
+            }
+        }
+        Type::Enum(type_enum) => {
+            let children = without(
+                of_children(&mut typ.children().iter()),
+                &[TypeTrait::Default],
+            );
+            if let TypeEnum {
+                default: Some(_), ..
+            } = type_enum
+            {
+                with(children, TypeTrait::Default)
+            } else {
+                children
+            }
+        }
+    }
+}
+
+/// The UniqueVec checks for duplicates by serializing its items.
+fn unique_vec_traits(item: &BTreeSet<TypeTrait>) -> BTreeSet<TypeTrait> {
+    let mut out = with(
+        without(item.clone(), &[TypeTrait::Copy]),
+        TypeTrait::Default,
+    );
+    if !item.contains(&TypeTrait::Serialize) {
+        out.remove(&TypeTrait::Deserialize);
+    }
+    out
+}
+
+/// Whether we can implement Default for the struct: either it has a default
+/// value or none of its properties are required.
+pub(crate) fn struct_has_default(type_struct: &TypeStruct) -> bool {
+    type_struct.default.is_some()
+        || type_struct.properties.iter().all(|prop| {
+            prop.state != StructPropertyState::Required
+                || prop.json_name == StructPropertySerde::Flatten
+        })
+}
+
+fn with(mut traits: BTreeSet<TypeTrait>, type_trait: TypeTrait) -> BTreeSet<TypeTrait> {
+    traits.insert(type_trait);
+    traits
+}
+
+fn without(mut traits: BTreeSet<TypeTrait>, excluded: &[TypeTrait]) -> BTreeSet<TypeTrait> {
+    traits.retain(|type_trait| !excluded.contains(type_trait));
+    traits
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{
+        schemalet::SchemaRef,
+        typespace::{Type, TypeNative},
+    };
+
+    use super::{propagate_traits, TraitSettings, TypeTrait};
+
+    #[test]
+    fn test_propagate_traits() {
+        let id = |s: &str| SchemaRef::Internal(s.to_string());
+        let types = [
+            (id("int"), Type::Integer("u32".to_string())),
+            (id("float"), Type::Float("f64".to_string())),
+            (id("vec_float"), Type::Vec(id("float"))),
+            (id("set_int"), Type::Set(id("int"))),
+            (id("set_vec_float"), Type::Set(id("vec_float"))),
+            (
+                id("native"),
+                Type::Native(TypeNative::new(
+                    "my_crate::Id",
+                    [
+                        TypeTrait::Clone,
+                        TypeTrait::PartialEq,
+                        TypeTrait::Eq,
+                        TypeTrait::Hash,
+                    ],
+                )),
+            ),
+            (id("set_native"), Type::Set(id("native"))),
+        ]
+        .into();
+
+        // All the traits we can.
+        let traits = propagate_traits(&types, &TraitSettings::default());
+        assert_eq!(
+            traits.get(&id("int")).unwrap(),
+            &TypeTrait::ALL.into_iter().collect::<BTreeSet<_>>()
+        );
+        assert!(!traits.get(&id("float")).unwrap().contains(&TypeTrait::Eq));
+        assert!(traits.get(&id("float")).unwrap().contains(&TypeTrait::Copy));
+        assert!(!traits
+            .get(&id("vec_float"))
+            .unwrap()
+            .contains(&TypeTrait::Copy));
+        assert!(!traits
+            .get(&id("set_vec_float"))
+            .unwrap()
+            .contains(&TypeTrait::Eq));
+        assert!(traits
+            .get(&id("set_vec_float"))
+            .unwrap()
+            .contains(&TypeTrait::Deserialize));
+        // Native types implement only what they say they do.
+        assert_eq!(
+            traits.get(&id("native")).unwrap(),
+            &[
+                TypeTrait::Clone,
+                TypeTrait::PartialEq,
+                TypeTrait::Eq,
+                TypeTrait::Hash,
+            ]
+            .into()
+        );
+        assert_eq!(
+            traits.get(&id("set_native")).unwrap(),
+            &[
+                TypeTrait::Clone,
+                TypeTrait::Default,
+                TypeTrait::PartialEq,
+                TypeTrait::Eq,
+            ]
+            .into()
+        );
+
+        // Only the traits that are required.
+        let traits = propagate_traits(&types, &TraitSettings::empty());
+        assert_eq!(
+            traits.get(&id("int")).unwrap(),
+            &[
+                TypeTrait::PartialEq,
+                TypeTrait::Eq,
+                TypeTrait::PartialOrd,
+                TypeTrait::Ord,
+                TypeTrait::Hash,
+            ]
+            .into()
+        );
+        assert_eq!(
+            traits.get(&id("vec_float")).unwrap(),
+            &[TypeTrait::PartialEq, TypeTrait::PartialOrd].into()
+        );
+        assert!(traits.get(&id("set_int")).unwrap().is_empty());
+    }
+}
@@ -1,3 +1,116 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    convert::FormatCheck,
+    namespace::Name,
+    schemalet::SchemaRef,
+    typespace::{NameBuilder, TypeTrait},
+};
+
+/// An existing type that we refer to by path rather than generate, such as
+/// `::uuid::Uuid` for a string format or a user's own type.
+#[derive(Debug, Clone)]
+pub struct TypeNative {
+    pub path: String,
+    /// The traits the type implements; we don't derive traits for generated
+    /// types that contain it if it doesn't implement them.
+    pub traits: BTreeSet<TypeTrait>,
+    /// For a type from a string format, a check of default values.
+    pub check: Option<FormatCheck>,
+}
+
+impl TypeNative {
+    pub fn new(path: impl ToString, traits: impl IntoIterator<Item = TypeTrait>) -> Self {
+        Self {
+            path: path.to_string(),
+            traits: traits.into_iter().collect(),
+            check: None,
+        }
+    }
+}
+
+/// A newtype around `String` for values of a particular string format. These
+/// are shared by name rather than being generated for each schema.
+#[derive(Debug, Clone)]
+pub struct TypeValidatedString {
+    pub name: String,
+    /// An expression of type `fn(&str) -> bool`.
+    pub validate: String,
+    /// The same check, for default values.
+    pub check: FormatCheck,
+
+    pub(crate) built: Option<TypeValidatedStringBuilt>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TypeValidatedStringBuilt {
+    pub name: Name<SchemaRef>,
+}
+
+impl TypeValidatedString {
+    pub fn new(name: impl ToString, validate: impl ToString, check: FormatCheck) -> Self {
+        Self {
+            name: name.to_string(),
+            validate: validate.to_string(),
+            check,
+            built: None,
+        }
+    }
+}
+
+/// An array with unique items whose item type can't be put in a set (e.g.
+/// because it's a float); a newtype around `Vec` that checks for duplicates
+/// when deserializing.
+#[derive(Debug, Clone)]
+pub struct TypeUniqueVec {
+    pub name: NameBuilder,
+    pub item: SchemaRef,
+
+    pub(crate) built: Option<TypeUniqueVecBuilt>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TypeUniqueVecBuilt {
+    pub name: Name<SchemaRef>,
+}
+
+impl TypeUniqueVec {
+    pub fn new(name: NameBuilder, item: SchemaRef) -> Self {
+        Self {
+            name,
+            item,
+            built: None,
+        }
+    }
+}
+
+/// A single value (e.g. from `const`); a unit struct that serializes as that
+/// value and rejects any other value when deserializing.
+#[derive(Debug, Clone)]
+pub struct TypeConstant {
+    pub name: NameBuilder,
+    pub description: Option<String>,
+    pub value: JsonValue,
+
+    pub(crate) built: Option<TypeConstantBuilt>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TypeConstantBuilt {
+    pub name: Name<SchemaRef>,
+}
+
+impl TypeConstant {
+    pub fn new(name: NameBuilder, description: Option<String>, value: serde_json::Value) -> Self {
+        Self {
+            name,
+            description,
+            value: JsonValue::new(value),
+            built: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct JsonValue(pub serde_json::Value);
 impl JsonValue {
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use crate::{
     namespace::Name,
     schemalet::SchemaRef,
@@ -19,6 +21,9 @@ pub struct TypeEnum {
 #[derive(Debug, Clone)]
 pub(crate) struct TypeEnumBuilt {
     pub name: Name<SchemaRef>,
+    /// The names of the builders for struct-like variants, by variant name,
+    /// if we generate them.
+    pub builders: BTreeMap<String, Name<SchemaRef>>,
 }
 
 impl TypeEnum {
@@ -42,6 +47,13 @@ impl TypeEnum {
         }
     }
 
+    /// An enum whose variants have no data.
+    pub fn is_simple(&self) -> bool {
+        self.variants
+            .iter()
+            .all(|variant| matches!(variant.details, VariantDetails::Simple))
+    }
+
     pub(crate) fn children(&self) -> Vec<SchemaRef> {
         self.variants
             .iter()
@@ -0,0 +1,53 @@
+use crate::{convert::FormatCheck, namespace::Name, schemalet::SchemaRef, typespace::NameBuilder};
+
+/// A newtype around `String` whose values must satisfy the constraints of
+/// the schema.
+#[derive(Debug, Clone)]
+pub struct TypeConstrainedString {
+    pub name: NameBuilder,
+    pub description: Option<String>,
+    pub pattern: Option<String>,
+    /// Lengths are in characters rather than bytes as per JSON Schema.
+    pub min_length: Option<u64>,
+    pub max_length: Option<u64>,
+    /// The check for the string's format when it has one that we know.
+    pub format: Option<StringFormatCheck>,
+
+    pub(crate) built: Option<TypeConstrainedStringBuilt>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TypeConstrainedStringBuilt {
+    pub name: Name<SchemaRef>,
+}
+
+impl TypeConstrainedString {
+    pub fn new(
+        name: NameBuilder,
+        description: Option<String>,
+        pattern: Option<String>,
+        min_length: Option<u64>,
+        max_length: Option<u64>,
+        format: Option<StringFormatCheck>,
+    ) -> Self {
+        Self {
+            name,
+            description,
+            pattern,
+            min_length,
+            max_length,
+            format,
+            built: None,
+        }
+    }
+}
+
+/// A check that a string is valid for its `format`.
+#[derive(Debug, Clone)]
+pub struct StringFormatCheck {
+    pub format: String,
+    /// An expression of type `fn(&str) -> bool`.
+    pub validate: String,
+    /// The same check, for default values, if we have one.
+    pub check: Option<FormatCheck>,
+}
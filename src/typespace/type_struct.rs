@@ -18,8 +18,10 @@ pub struct TypeStruct {
 }
 
 #[derive(Debug, Clone)]
-struct TypeStructBuilt {
-    name: Name<SchemaRef>,
+pub(crate) struct TypeStructBuilt {
+    pub name: Name<SchemaRef>,
+    /// The name of the struct's builder if we generate one.
+    pub builder: Option<Name<SchemaRef>>,
 }
 
 impl TypeStruct {
@@ -83,6 +85,14 @@ impl StructProperty {
             type_id: type_id.into(),
         }
     }
+
+    /// The name of the property in the serialized form.
+    pub(crate) fn serialized_name(&self) -> String {
+        match &self.json_name {
+            StructPropertySerde::Rename(json_name) => json_name.clone(),
+            StructPropertySerde::None | StructPropertySerde::Flatten => self.rust_name.to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -96,5 +106,9 @@ pub enum StructPropertySerde {
 pub enum StructPropertyState {
     Required,
     Optional,
+    /// An optional property whose schema also admits `null`, rendered so as
+    /// to distinguish an absent value from an explicit `null` (i.e.
+    /// `Option<Option<T>>`).
+    OptionalNullable,
     Default(JsonValue),
 }
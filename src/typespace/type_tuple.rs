@@ -0,0 +1,37 @@
+use crate::{namespace::Name, schemalet::SchemaRef, typespace::NameBuilder};
+
+/// A tuple with a fixed prefix followed by any number of trailing items e.g.
+/// from `prefixItems` along with `items`. Serde can't express this shape as a
+/// plain Rust tuple so it's a named tuple struct whose last field holds the
+/// trailing items.
+#[derive(Debug, Clone)]
+pub struct TypeTupleStruct {
+    pub name: NameBuilder,
+    pub description: Option<String>,
+    pub prefix: Vec<SchemaRef>,
+    pub rest: SchemaRef,
+
+    pub(crate) built: Option<TypeTupleStructBuilt>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TypeTupleStructBuilt {
+    pub name: Name<SchemaRef>,
+}
+
+impl TypeTupleStruct {
+    pub fn new(
+        name: NameBuilder,
+        description: Option<String>,
+        prefix: Vec<SchemaRef>,
+        rest: SchemaRef,
+    ) -> Self {
+        Self {
+            name,
+            description,
+            prefix,
+            rest,
+            built: None,
+        }
+    }
+}
@@ -0,0 +1,572 @@
+//! Rendering of JSON values (i.e. schema defaults) as Rust expressions of the
+//! corresponding generated type. Along the way we make sure that the value
+//! is actually valid for the type.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use heck::ToSnakeCase;
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+
+use crate::{
+    schemalet::SchemaRef,
+    typespace::{
+        EnumTagType, StringFormatCheck, StructProperty, StructPropertySerde, StructPropertyState,
+        Type, TypeConstrainedString, TypeEnum, TypeNative, TypeStruct, TypeTrait, TypeTupleStruct,
+        Typespace, VariantDetails,
+    },
+};
+
+impl Typespace {
+    /// Produce an expression that constructs `value` as the type `id`, or a
+    /// description of why the value isn't valid for the type.
+    pub(crate) fn render_value(
+        &self,
+        id: &SchemaRef,
+        value: &serde_json::Value,
+    ) -> Result<TokenStream, String> {
+        let typ = self.types.get(id).unwrap();
+
+        match (typ, value) {
+            (Type::Boolean, serde_json::Value::Bool(b)) => Ok(quote! { #b }),
+
+            (Type::Integer(name), serde_json::Value::Number(n)) => {
+                let (inner, nonzero) = match name.strip_prefix("::std::num::NonZero") {
+                    Some(inner) => (inner.to_lowercase(), true),
+                    None => (name.clone(), false),
+                };
+                let n = n
+                    .as_i64()
+                    .map(i128::from)
+                    .or_else(|| n.as_u64().map(i128::from))
+                    .ok_or_else(|| format!("{n} is not an integer"))?;
+                let (min, max) = integer_bounds(&inner);
+                if n < min || n > max || (nonzero && n == 0) {
+                    return Err(format!("{n} is out of range for {name}"));
+                }
+                let lit = syn::LitInt::new(&format!("{n}{inner}"), proc_macro2::Span::call_site());
+                if nonzero {
+                    let ty = syn::parse_str::<syn::TypePath>(name).unwrap();
+                    Ok(quote! { #ty::new(#lit).unwrap() })
+                } else {
+                    Ok(quote! { #lit })
+                }
+            }
+
+            (Type::Float(_), serde_json::Value::Number(n)) => {
+                let lit = Literal::f64_suffixed(n.as_f64().unwrap());
+                Ok(quote! { #lit })
+            }
+
+            (Type::String, serde_json::Value::String(s)) => Ok(quote! { #s.to_string() }),
+
+            (Type::ValidatedString(validated), serde_json::Value::String(s)) => {
+                let name = validated.built.as_ref().unwrap().name.to_string();
+                if !(validated.check)(s) {
+                    return Err(format!("{s:?} is not a valid {name}"));
+                }
+                let name = format_ident!("{name}");
+                Ok(quote! { #name::try_from(#s.to_string()).unwrap() })
+            }
+
+            (Type::ConstrainedString(type_string), serde_json::Value::String(s)) => {
+                let TypeConstrainedString {
+                    pattern,
+                    min_length,
+                    max_length,
+                    format,
+                    built,
+                    ..
+                } = type_string;
+                let len = s.chars().count() as u64;
+                if min_length.is_some_and(|min| len < min)
+                    || max_length.is_some_and(|max| len > max)
+                {
+                    return Err(format!("{s:?} has the wrong length"));
+                }
+                if let Some(pattern) = pattern {
+                    let regex = regex::Regex::new(pattern)
+                        .map_err(|e| format!("invalid pattern {pattern:?}: {e}"))?;
+                    if !regex.is_match(s) {
+                        return Err(format!("{s:?} doesn't match {pattern:?}"));
+                    }
+                }
+                if let Some(StringFormatCheck {
+                    format,
+                    check: Some(check),
+                    ..
+                }) = format
+                {
+                    if !check(s) {
+                        return Err(format!("{s:?} is not a valid {format}"));
+                    }
+                }
+                let name = format_ident!("{}", built.as_ref().unwrap().name.to_string());
+                Ok(quote! { #name::try_from(#s.to_string()).unwrap() })
+            }
+
+            (
+                Type::Native(TypeNative {
+                    path,
+                    traits,
+                    check,
+                }),
+                _,
+            ) => {
+                // Beyond what we can check for string formats, we leave it to
+                // the type's Deserialize implementation.
+                if !traits.contains(&TypeTrait::Deserialize) {
+                    return Err(format!("{path} doesn't implement Deserialize"));
+                }
+                if let Some(check) = check {
+                    if !value.as_str().is_some_and(check) {
+                        return Err(format!("{value} is not a valid {path}"));
+                    }
+                }
+                let ty = syn::parse_str::<syn::Type>(path).unwrap();
+                let json = value.to_string();
+                Ok(quote! { ::serde_json::from_str::<#ty>(#json).unwrap() })
+            }
+
+            (Type::JsonValue, _) => {
+                let json = value.to_string();
+                Ok(quote! { ::serde_json::from_str(#json).unwrap() })
+            }
+
+            (Type::Unit, serde_json::Value::Null) => Ok(quote! { () }),
+
+            (Type::Constant(type_constant), _) if type_constant.value.0 == *value => {
+                let name =
+                    format_ident!("{}", type_constant.built.as_ref().unwrap().name.to_string());
+                Ok(quote! { #name })
+            }
+
+            (Type::Option(_), serde_json::Value::Null) => Ok(quote! { None }),
+            (Type::Option(inner), _) => {
+                let inner = self.render_value(inner, value)?;
+                Ok(quote! { Some(#inner) })
+            }
+
+            (Type::Box(inner), _) => {
+                let inner = self.render_value(inner, value)?;
+                Ok(quote! { Box::new(#inner) })
+            }
+
+            (Type::Vec(inner), serde_json::Value::Array(items)) => {
+                let items = self.render_values(inner, items)?;
+                Ok(quote! { vec![#( #items ),*] })
+            }
+
+            (Type::Set(inner), serde_json::Value::Array(items)) => {
+                check_unique(items)?;
+                let items = self.render_values(inner, items)?;
+                Ok(quote! { [#( #items ),*].into_iter().collect() })
+            }
+
+            (Type::UniqueVec(type_unique), serde_json::Value::Array(items)) => {
+                check_unique(items)?;
+                let name =
+                    format_ident!("{}", type_unique.built.as_ref().unwrap().name.to_string());
+                let items = self.render_values(&type_unique.item, items)?;
+                Ok(quote! { #name::try_from(vec![#( #items ),*]).unwrap() })
+            }
+
+            (Type::Array(inner, len), serde_json::Value::Array(items)) if items.len() == *len => {
+                let items = self.render_values(inner, items)?;
+                Ok(quote! { [#( #items ),*] })
+            }
+
+            (Type::Tuple(types), serde_json::Value::Array(items)) if items.len() == types.len() => {
+                let items = types
+                    .iter()
+                    .zip(items)
+                    .map(|(id, item)| self.render_value(id, item))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(quote! { (#( #items, )*) })
+            }
+
+            (Type::TupleStruct(type_tuple), serde_json::Value::Array(items))
+                if items.len() >= type_tuple.prefix.len() =>
+            {
+                let TypeTupleStruct {
+                    prefix,
+                    rest,
+                    built,
+                    ..
+                } = type_tuple;
+                let name = format_ident!("{}", built.as_ref().unwrap().name.to_string());
+                let (prefix_values, rest_values) = items.split_at(prefix.len());
+                let prefix_values = prefix
+                    .iter()
+                    .zip(prefix_values)
+                    .map(|(id, item)| self.render_value(id, item))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let rest_values = self.render_values(rest, rest_values)?;
+                Ok(quote! { #name(#( #prefix_values, )* vec![#( #rest_values ),*]) })
+            }
+
+            (Type::Map(key_id, value_id), serde_json::Value::Object(map)) => {
+                let entries = map
+                    .iter()
+                    .map(|(key, value)| {
+                        let key =
+                            self.render_value(key_id, &serde_json::Value::String(key.clone()))?;
+                        let value = self.render_value(value_id, value)?;
+                        Ok(quote! { (#key, #value) })
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                Ok(quote! { [#( #entries ),*].into_iter().collect() })
+            }
+
+            (Type::Struct(type_struct), serde_json::Value::Object(_)) => {
+                let name =
+                    format_ident!("{}", type_struct.built.as_ref().unwrap().name.to_string());
+                let TypeStruct {
+                    properties,
+                    deny_unknown_fields,
+                    ..
+                } = type_struct;
+                let fields = self.render_properties(properties, *deny_unknown_fields, value)?;
+                Ok(quote! { #name { #( #fields, )* } })
+            }
+
+            (Type::Enum(type_enum), _) => self.render_enum_value(type_enum, value),
+
+            _ => Err(format!("{value} is not a valid value for this type")),
+        }
+    }
+
+    fn render_values(
+        &self,
+        id: &SchemaRef,
+        items: &[serde_json::Value],
+    ) -> Result<Vec<TokenStream>, String> {
+        items
+            .iter()
+            .map(|item| self.render_value(id, item))
+            .collect()
+    }
+
+    fn render_properties(
+        &self,
+        properties: &[StructProperty],
+        deny_unknown_fields: bool,
+        value: &serde_json::Value,
+    ) -> Result<Vec<TokenStream>, String> {
+        let serde_json::Value::Object(map) = value else {
+            return Err(format!("{value} is not an object"));
+        };
+
+        // Any flattened property decides for itself which fields it accepts.
+        let flattened = properties
+            .iter()
+            .any(|prop| prop.json_name == StructPropertySerde::Flatten);
+        if deny_unknown_fields && !flattened {
+            if let Some(unknown) = map.keys().find(|key| {
+                !properties
+                    .iter()
+                    .any(|prop| prop.serialized_name() == **key)
+            }) {
+                return Err(format!("unknown property {unknown:?}"));
+            }
+        }
+
+        properties
+            .iter()
+            .map(|prop| {
+                let rust_name = &prop.rust_name;
+                if prop.json_name == StructPropertySerde::Flatten {
+                    // The flattened map gets whatever the other properties
+                    // don't claim.
+                    let rest = map
+                        .iter()
+                        .filter(|(key, _)| {
+                            !properties.iter().any(|other| {
+                                other.json_name != StructPropertySerde::Flatten
+                                    && other.serialized_name() == **key
+                            })
+                        })
+                        .map(|(key, value)| (key.clone(), value.clone()))
+                        .collect();
+                    let value =
+                        self.render_value(&prop.type_id, &serde_json::Value::Object(rest))?;
+                    return Ok(quote! { #rust_name: #value });
+                }
+                let name = prop.serialized_name();
+                let value = match (&prop.state, map.get(&name)) {
+                    (StructPropertyState::Required, Some(value))
+                    | (StructPropertyState::Default(_), Some(value)) => {
+                        self.render_value(&prop.type_id, value)?
+                    }
+                    (StructPropertyState::Required, None) => {
+                        return Err(format!("required property {name:?} is missing"))
+                    }
+                    (StructPropertyState::Default(default), None) => {
+                        self.render_value(&prop.type_id, &default.0)?
+                    }
+                    (
+                        StructPropertyState::Optional | StructPropertyState::OptionalNullable,
+                        None,
+                    ) => quote! { None },
+                    (StructPropertyState::Optional, Some(value)) => {
+                        // As when rendering the property, an Option type
+                        // isn't wrapped in a second Option.
+                        match self.types.get(&prop.type_id) {
+                            Some(Type::Option(_)) => self.render_value(&prop.type_id, value)?,
+                            _ => {
+                                let value = self.render_value(&prop.type_id, value)?;
+                                quote! { Some(#value) }
+                            }
+                        }
+                    }
+                    (StructPropertyState::OptionalNullable, Some(value)) => {
+                        let value = self.render_value(&prop.type_id, value)?;
+                        quote! { Some(#value) }
+                    }
+                };
+                Ok(quote! { #rust_name: #value })
+            })
+            .collect()
+    }
+
+    fn render_enum_value(
+        &self,
+        type_enum: &TypeEnum,
+        value: &serde_json::Value,
+    ) -> Result<TokenStream, String> {
+        let name = format_ident!("{}", type_enum.built.as_ref().unwrap().name.to_string());
+
+        let render_variant = |variant: &crate::typespace::EnumVariant,
+                              content: Option<&serde_json::Value>|
+         -> Result<TokenStream, String> {
+            let variant_name = format_ident!("{}", variant.rust_name);
+            match (&variant.details, content) {
+                (VariantDetails::Simple, _) => Ok(quote! { #name::#variant_name }),
+                (VariantDetails::Item(id), Some(content)) => {
+                    let content = self.render_value(id, content)?;
+                    Ok(quote! { #name::#variant_name(#content) })
+                }
+                (VariantDetails::Tuple(types), Some(serde_json::Value::Array(items)))
+                    if types.len() == items.len() =>
+                {
+                    let items = types
+                        .iter()
+                        .zip(items)
+                        .map(|(id, item)| self.render_value(id, item))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok(quote! { #name::#variant_name(#( #items ),*) })
+                }
+                (VariantDetails::Struct(properties), Some(content)) => {
+                    let fields = self.render_properties(properties, false, content)?;
+                    Ok(quote! { #name::#variant_name { #( #fields, )* } })
+                }
+                _ => Err(format!("invalid value for variant {}", variant.rust_name)),
+            }
+        };
+
+        let serde_name = |variant: &crate::typespace::EnumVariant| {
+            variant
+                .rename
+                .clone()
+                .unwrap_or_else(|| variant.rust_name.clone())
+        };
+
+        match (&type_enum.tag_type, value) {
+            (EnumTagType::Untagged, _) => {
+                // The first variant that accepts the value wins, just as
+                // with serde.
+                type_enum
+                    .variants
+                    .iter()
+                    .find_map(|variant| match (&variant.details, value) {
+                        (VariantDetails::Simple, serde_json::Value::Null) => {
+                            render_variant(variant, None).ok()
+                        }
+                        (VariantDetails::Simple, _) => None,
+                        _ => render_variant(variant, Some(value)).ok(),
+                    })
+                    .ok_or_else(|| format!("{value} doesn't match any variant"))
+            }
+
+            (_, serde_json::Value::String(s)) => {
+                let variant = type_enum
+                    .variants
+                    .iter()
+                    .find(|variant| {
+                        matches!(variant.details, VariantDetails::Simple)
+                            && serde_name(variant) == *s
+                    })
+                    .ok_or_else(|| format!("{s:?} is not a variant"))?;
+                render_variant(variant, None)
+            }
+
+            (EnumTagType::External, serde_json::Value::Object(map)) if map.len() == 1 => {
+                let (tag, content) = map.iter().next().unwrap();
+                let variant = type_enum
+                    .variants
+                    .iter()
+                    .find(|variant| serde_name(variant) == *tag)
+                    .ok_or_else(|| format!("{tag:?} is not a variant"))?;
+                render_variant(variant, Some(content))
+            }
+
+            (EnumTagType::Adjacent { tag, content }, serde_json::Value::Object(map)) => {
+                let tag_value = map
+                    .get(tag)
+                    .and_then(serde_json::Value::as_str)
+                    .ok_or_else(|| format!("missing tag {tag:?}"))?;
+                let variant = type_enum
+                    .variants
+                    .iter()
+                    .find(|variant| serde_name(variant) == tag_value)
+                    .ok_or_else(|| format!("{tag_value:?} is not a variant"))?;
+                render_variant(variant, map.get(content))
+            }
+
+            (EnumTagType::Internal { tag }, serde_json::Value::Object(map)) => {
+                let tag_value = map
+                    .get(tag)
+                    .and_then(serde_json::Value::as_str)
+                    .ok_or_else(|| format!("missing tag {tag:?}"))?;
+                let variant = type_enum
+                    .variants
+                    .iter()
+                    .find(|variant| serde_name(variant) == tag_value)
+                    .ok_or_else(|| format!("{tag_value:?} is not a variant"))?;
+                let mut content = map.clone();
+                content.remove(tag);
+                render_variant(variant, Some(&serde_json::Value::Object(content)))
+            }
+
+            _ => Err(format!("{value} is not a valid value for this enum")),
+        }
+    }
+
+    /// Name of the generated function for a property's default value; the
+    /// container is the path of the struct or of the enum variant.
+    pub(crate) fn default_fn_name(&self, container: &str, prop: &StructProperty) -> String {
+        self.default_fns
+            .get(&(container.to_string(), prop.rust_name.to_string()))
+            .unwrap()
+            .clone()
+    }
+
+    /// Names for the functions that produce property defaults. Snake-casing
+    /// the container and property can make the same name from different
+    /// properties (e.g. `A.b_c` and `AB.c`) so we number any repeats.
+    pub(crate) fn default_fn_names(&self) -> BTreeMap<(String, String), String> {
+        let mut taken = BTreeSet::new();
+        self.struct_properties(self.types.keys())
+            .into_iter()
+            .filter(|(_, prop)| matches!(prop.state, StructPropertyState::Default(_)))
+            .map(|(container, prop)| {
+                let base = format!(
+                    "{}_{}",
+                    container.replace("::", "_"),
+                    prop.rust_name.unraw()
+                )
+                .to_snake_case();
+                let name = std::iter::once(base.clone())
+                    .chain((2..).map(|n| format!("{base}_{n}")))
+                    .find(|name| taken.insert(name.clone()))
+                    .unwrap();
+                ((container, prop.rust_name.to_string()), name)
+            })
+            .collect()
+    }
+}
+
+fn check_unique(items: &[serde_json::Value]) -> Result<(), String> {
+    match items
+        .iter()
+        .enumerate()
+        .find(|(ii, item)| items[..*ii].contains(item))
+    {
+        Some((_, item)) => Err(format!("duplicate item {item}")),
+        None => Ok(()),
+    }
+}
+
+fn integer_bounds(name: &str) -> (i128, i128) {
+    match name {
+        "u8" => (u8::MIN.into(), u8::MAX.into()),
+        "u16" => (u16::MIN.into(), u16::MAX.into()),
+        "u32" => (u32::MIN.into(), u32::MAX.into()),
+        "u64" => (u64::MIN.into(), u64::MAX.into()),
+        "i8" => (i8::MIN.into(), i8::MAX.into()),
+        "i16" => (i16::MIN.into(), i16::MAX.into()),
+        "i32" => (i32::MIN.into(), i32::MAX.into()),
+        _ => (i64::MIN.into(), i64::MAX.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        schemalet::SchemaRef,
+        typespace::{NameBuilder, Type, TypeConstrainedString, Typespace},
+    };
+
+    #[test]
+    fn test_render_value() {
+        let id = |s: &str| SchemaRef::Internal(s.to_string());
+        let typespace = Typespace {
+            types: [
+                (id("u8"), Type::Integer("u8".to_string())),
+                (
+                    id("nz"),
+                    Type::Integer("::std::num::NonZeroU16".to_string()),
+                ),
+                (id("string"), Type::String),
+                (id("vec"), Type::Vec(id("u8"))),
+                (id("option"), Type::Option(id("string"))),
+                (
+                    id("code"),
+                    Type::ConstrainedString(TypeConstrainedString::new(
+                        NameBuilder::Unset,
+                        None,
+                        None,
+                        Some(3),
+                        Some(3),
+                        None,
+                    )),
+                ),
+            ]
+            .into(),
+            traits: Default::default(),
+            derives: Default::default(),
+            default_fns: Default::default(),
+        };
+        let render = |name: &str, value: serde_json::Value| {
+            typespace
+                .render_value(&id(name), &value)
+                .map(|tokens| tokens.to_string())
+        };
+
+        assert_eq!(render("u8", json!(200)), Ok("200u8".to_string()));
+        assert!(render("u8", json!(256)).is_err());
+        assert!(render("u8", json!(-1)).is_err());
+        assert!(render("u8", json!("200")).is_err());
+        assert_eq!(
+            render("nz", json!(1)),
+            Ok(":: std :: num :: NonZeroU16 :: new (1u16) . unwrap ()".to_string())
+        );
+        assert!(render("nz", json!(0)).is_err());
+        assert_eq!(
+            render("vec", json!([1, 2])),
+            Ok("vec ! [1u8 , 2u8]".to_string())
+        );
+        assert!(render("vec", json!([1, 1000])).is_err());
+        assert_eq!(render("option", json!(null)), Ok("None".to_string()));
+        assert_eq!(
+            render("option", json!("x")),
+            Ok("Some (\"x\" . to_string ())".to_string())
+        );
+        assert!(render("code", json!("ab")).is_err());
+        assert!(render("code", json!("abcd")).is_err());
+        assert!(render("code", json!(123)).is_err());
+    }
+}
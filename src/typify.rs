@@ -1,15 +1,19 @@
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
 use url::Url;
 
 use crate::{
     bootstrap::to_schemalets,
-    convert::Converter,
+    convert::{
+        additional_properties_map_id, non_null_id, non_null_subschemas, pattern_key_id, Converter,
+        FormatTable, ReplacementTable,
+    },
+    dedup::Dedup,
     schemalet::{
-        CanonicalSchemalet, CanonicalSchemaletDetails, SchemaRef, Schemalet, SchemaletDetails,
-        SchemaletValue, State,
+        resolve_note, CanonicalSchemalet, CanonicalSchemaletDetails, SchemaRef, Schemalet,
+        SchemaletDetails, SchemaletValue, SchemaletValueObject, State,
     },
-    typespace::{Typespace, TypespaceBuilder},
+    typespace::{TraitSettings, Typespace, TypespaceBuilder},
     Bundle,
 };
 
@@ -17,15 +21,20 @@ pub struct Typify {
     bundle: Bundle,
     normalizer: Normalizer,
     typespace: TypespaceBuilder,
+    formats: FormatTable,
+    replacements: ReplacementTable,
+    distinguish_null: bool,
 }
 
 struct Normalizer {
     raw: BTreeMap<SchemaRef, Schemalet>,
     canonical: BTreeMap<SchemaRef, CanonicalSchemalet>,
+    dedup: Dedup,
 }
 #[derive(Debug)]
 pub enum Error {
     X,
+    Typespace(crate::typespace::Error),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -38,6 +47,9 @@ impl Typify {
             bundle,
             normalizer: Default::default(),
             typespace: Default::default(),
+            formats: Default::default(),
+            replacements: Default::default(),
+            distinguish_null: false,
         }
     }
 
@@ -46,19 +58,16 @@ impl Typify {
     /// specification is determined by the value in the document named by the
     /// provided id; to override that value, use facilities of the `Bundle`.
     pub fn add_type_by_id(&mut self, id: impl AsRef<str>) -> Result<TypeId> {
-        let typ_id = SchemaRef::Id(format!("{}#", id.as_ref()));
-
-        // Add the schemalets reachable from `id` to the graph, and then
-        // normalize those additions.
-        self.normalizer.add(&self.bundle, id.as_ref())?;
+        let typ_id = self.normalize_by_id(id)?;
 
-        // TODO
-        // This feels wrong; I think it should get a ref to the graph of types.
-        // I think the whole point of this type is to isolated and provide
-        // structure to the conversion methods.
-        let mut converter = Converter::new(self.normalizer.canonical.clone());
+        let mut converter = Converter::new(
+            &self.normalizer.canonical,
+            &self.formats,
+            &self.replacements,
+        );
+        converter.set_distinguish_null(self.distinguish_null);
 
-        converter.set_name(typ_id.clone(), "SchemaRoot".to_string());
+        converter.set_fallback_name(typ_id.clone(), "SchemaRoot".to_string());
 
         let mut work = VecDeque::from([typ_id.clone()]);
 
@@ -81,7 +90,10 @@ impl Typify {
                 let url = Url::parse(path).unwrap();
 
                 if let Some(fragment) = url.fragment() {
-                    if let Some(name) = fragment.strip_prefix("/$defs/") {
+                    if let Some(name) = fragment
+                        .strip_prefix("/$defs/")
+                        .filter(|name| !name.contains('/'))
+                    {
                         converter.set_name(work_id.clone(), name.to_string());
                     }
                 }
@@ -89,6 +101,12 @@ impl Typify {
 
             let typ = converter.convert(&work_id);
 
+            if !typ.is_named() {
+                for child_id in typ.children() {
+                    converter.inherit_names(&work_id, child_id);
+                }
+            }
+
             work.extend(typ.children());
 
             self.typespace.insert(work_id.clone(), typ);
@@ -97,6 +115,70 @@ impl Typify {
         Ok(TypeId(typ_id.clone()))
     }
 
+    /// Add the schema named by `id` (and everything it references) to the
+    /// canonical graph without generating types. Returns the node in
+    /// [`Self::canonical_graph`] for the schema, e.g. to pass to
+    /// [`crate::compat::diff`].
+    pub fn normalize_by_id(&mut self, id: impl AsRef<str>) -> Result<SchemaRef> {
+        // An id without a fragment refers to the root of its document.
+        let schema_ref = match id.as_ref().contains('#') {
+            true => SchemaRef::Id(id.as_ref().to_string()),
+            false => SchemaRef::Id(format!("{}#", id.as_ref())),
+        };
+
+        // Add the schemalets reachable from `id` to the graph, and then
+        // normalize those additions.
+        self.normalizer.add(&self.bundle, id.as_ref())?;
+
+        // If the requested schema is structurally identical to one we've
+        // already seen, we'll use that one.
+        Ok(self.normalizer.representative(&schema_ref).clone())
+    }
+
+    /// The table that maps string formats to Rust types; use this to
+    /// register additional formats or to change the built-in mappings.
+    pub fn formats_mut(&mut self) -> &mut FormatTable {
+        &mut self.formats
+    }
+
+    /// Existing types to use for particular schemas rather than generating
+    /// types for them.
+    pub fn replacements_mut(&mut self) -> &mut ReplacementTable {
+        &mut self.replacements
+    }
+
+    /// The traits that generated types should implement; by default, types
+    /// implement all the traits they can.
+    pub fn traits_mut(&mut self) -> &mut TraitSettings {
+        self.typespace.traits_mut()
+    }
+
+    /// Generate a builder type for each struct and struct-like enum variant
+    /// with setters that accept anything convertible to each field's type.
+    pub fn set_builders(&mut self, builders: bool) {
+        self.typespace.set_builders(builders);
+    }
+
+    /// By default, an optional property whose schema admits `null` becomes
+    /// `Option<T>` with both an absent value and `null` mapping to `None`.
+    /// Set this to generate `Option<Option<T>>` instead so that the two may
+    /// be distinguished.
+    pub fn set_distinguish_null(&mut self, distinguish_null: bool) {
+        self.distinguish_null = distinguish_null;
+    }
+
+    /// The graph of schemalets as extracted from the source documents, prior
+    /// to normalization. See [`crate::export`] to render it.
+    pub fn raw_graph(&self) -> &BTreeMap<SchemaRef, Schemalet> {
+        &self.normalizer.raw
+    }
+
+    /// The graph of schemalets after normalization. See [`crate::export`] to
+    /// render it.
+    pub fn canonical_graph(&self) -> &BTreeMap<SchemaRef, CanonicalSchemalet> {
+        &self.normalizer.canonical
+    }
+
     pub fn get_typespace_builder(&self) -> &TypespaceBuilder {
         &self.typespace
     }
@@ -105,50 +187,194 @@ impl Typify {
         self.typespace
     }
 
-    pub fn into_typespace(self) -> Typespace {
-        self.typespace.finalize().unwrap()
+    pub fn into_typespace(self) -> Result<Typespace> {
+        self.typespace.finalize().map_err(Error::Typespace)
     }
 }
 
 impl Default for Normalizer {
     fn default() -> Self {
-        let canonical = [(
-            SchemaRef::Internal("string".to_string()),
-            CanonicalSchemalet {
-                metadata: Default::default(),
-                details: CanonicalSchemaletDetails::Value(SchemaletValue::String {
-                    pattern: None,
-                    format: None,
-                }),
-            },
-        )]
+        let canonical = [
+            (
+                SchemaRef::Internal("string".to_string()),
+                CanonicalSchemalet {
+                    metadata: Default::default(),
+                    details: CanonicalSchemaletDetails::Value(SchemaletValue::String {
+                        pattern: None,
+                        format: None,
+                        min_length: None,
+                        max_length: None,
+                    }),
+                },
+            ),
+            (
+                SchemaRef::Internal("anything".to_string()),
+                CanonicalSchemalet {
+                    metadata: Default::default(),
+                    details: CanonicalSchemaletDetails::Anything,
+                },
+            ),
+        ]
         .into();
 
-        Self {
+        let mut normalizer = Self {
             raw: Default::default(),
             canonical,
-        }
+            dedup: Default::default(),
+        };
+        let added = normalizer.canonical.keys().cloned().collect();
+        normalizer.dedup.add(&mut normalizer.canonical, &added);
+        normalizer
     }
 }
 
 impl Normalizer {
     pub(crate) fn add(&mut self, bundle: &Bundle, id: impl AsRef<str>) -> Result<()> {
-        self.add_raw(bundle, id.as_ref())?;
+        // Only the schemalets we haven't seen before need normalization; the
+        // rest are already in the canonical graph.
+        let wip = self.add_raw(bundle, id.as_ref())?;
+
+        if wip.is_empty() {
+            return Ok(());
+        }
+
+        let added = self.normalize(id, wip)?;
 
-        // TODO 7.15.2025
-        // This is an extremely awful hack as I figure out this interface.
-        let wip = self.raw.clone();
+        // Collapse structurally identical schemalets so that we generate a
+        // single type for each distinct shape.
+        self.dedup.add(&mut self.canonical, &added);
 
-        self.normalize(id, wip)?;
+        self.add_pattern_keys(&added);
+        self.add_additional_properties_maps(&added);
+        self.add_non_null_variants(&added);
 
         Ok(())
     }
 
-    fn add_raw(&mut self, bundle: &Bundle, id: impl AsRef<str>) -> Result<()> {
-        // TODO 7.15.2025
-        // This use of "#" doesn't feel quite right
-        // Test the situation where the id is not the root of the document.
-        let mut references = vec![(bundle.resolve_root(id).unwrap().context, "#".to_string())];
+    /// The newly added canonical schemalets.
+    fn added<'a>(
+        &'a self,
+        added: &'a BTreeSet<SchemaRef>,
+    ) -> impl Iterator<Item = &'a CanonicalSchemalet> {
+        added.iter().filter_map(|id| self.canonical.get(id))
+    }
+
+    /// Maps from `patternProperties` have keys that are strings matching the
+    /// pattern; there's no schemalet for those keys so we make one.
+    fn add_pattern_keys(&mut self, added: &BTreeSet<SchemaRef>) {
+        let patterns = self
+            .added(added)
+            .filter_map(|schemalet| match &schemalet.details {
+                CanonicalSchemaletDetails::Value(SchemaletValue::Object(object)) => {
+                    object.pattern_properties.as_ref()
+                }
+                _ => None,
+            })
+            .flat_map(|pattern_properties| pattern_properties.keys().cloned())
+            .collect::<Vec<_>>();
+
+        for pattern in patterns {
+            self.canonical
+                .entry(pattern_key_id(&pattern))
+                .or_insert_with(|| CanonicalSchemalet {
+                    metadata: Default::default(),
+                    details: CanonicalSchemaletDetails::Value(SchemaletValue::String {
+                        pattern: Some(pattern),
+                        format: None,
+                        min_length: None,
+                        max_length: None,
+                    }),
+                });
+        }
+    }
+
+    /// A struct with fixed properties gathers any others into a flattened
+    /// map; we make a schemalet for that map.
+    fn add_additional_properties_maps(&mut self, added: &BTreeSet<SchemaRef>) {
+        let additional = self
+            .added(added)
+            .filter_map(|schemalet| match &schemalet.details {
+                CanonicalSchemaletDetails::Value(SchemaletValue::Object(object))
+                    if !object.properties.is_empty() =>
+                {
+                    object
+                        .additional_properties
+                        .clone()
+                        .or_else(|| object.unevaluated_properties.clone())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        for additional_properties in additional {
+            self.canonical
+                .entry(additional_properties_map_id(&additional_properties))
+                .or_insert_with(|| CanonicalSchemalet {
+                    metadata: Default::default(),
+                    details: CanonicalSchemaletDetails::Value(SchemaletValue::Object(
+                        SchemaletValueObject {
+                            additional_properties: Some(additional_properties),
+                            ..Default::default()
+                        },
+                    )),
+                });
+        }
+    }
+
+    /// A oneOf that includes `null` becomes an Option of a oneOf of the
+    /// other subschemas; we make a schemalet for that inner oneOf.
+    fn add_non_null_variants(&mut self, added: &BTreeSet<SchemaRef>) {
+        let non_nulls = self
+            .added(added)
+            .filter_map(|schemalet| match &schemalet.details {
+                CanonicalSchemaletDetails::ExclusiveOneOf { subschemas, .. } => {
+                    non_null_subschemas(&self.canonical, subschemas)
+                }
+                _ => None,
+            })
+            .filter(|non_null| non_null.len() > 1)
+            .collect::<Vec<_>>();
+
+        for non_null in non_nulls {
+            let typ = non_null
+                .iter()
+                .map(|id| resolve_note(&self.canonical, id).and_then(|ss| ss.details.get_type()))
+                .reduce(|a, b| match (a, b) {
+                    (Some(aa), Some(bb)) if aa == bb => Some(aa),
+                    _ => None,
+                })
+                .flatten();
+            self.canonical
+                .entry(non_null_id(&non_null))
+                .or_insert_with(|| CanonicalSchemalet {
+                    metadata: Default::default(),
+                    details: CanonicalSchemaletDetails::ExclusiveOneOf {
+                        typ,
+                        subschemas: non_null,
+                    },
+                });
+        }
+    }
+
+    fn representative<'a>(&'a self, schema_ref: &'a SchemaRef) -> &'a SchemaRef {
+        self.dedup.representative(schema_ref)
+    }
+
+    /// Add the schemalets reachable from `id` to the raw graph, returning
+    /// those that are newly discovered.
+    fn add_raw(
+        &mut self,
+        bundle: &Bundle,
+        id: impl AsRef<str>,
+    ) -> Result<BTreeMap<SchemaRef, Schemalet>> {
+        let mut added = BTreeMap::new();
+
+        // Start from the id itself, which may be the root of its document or
+        // some schema within it.
+        let mut references = vec![(
+            bundle.resolve_root(id.as_ref()).unwrap().context,
+            id.as_ref().to_string(),
+        )];
 
         while let Some((context, path)) = references.pop() {
             let resolved = bundle
@@ -208,18 +434,26 @@ impl Normalizer {
                     schemalet => schemalet,
                 };
 
-                let old = self.raw.insert(schema_ref, schemalet);
-                assert!(old.is_none());
+                // A schema within a document we've already seen some of
+                // may have been reached before.
+                if self.raw.contains_key(&schema_ref) {
+                    continue;
+                }
+                self.raw.insert(schema_ref.clone(), schemalet.clone());
+                added.insert(schema_ref, schemalet);
             }
         }
-        Ok(())
+        Ok(added)
     }
 
+    /// Simplify `wip` into canonical schemalets, returning the ids of those
+    /// added to the canonical graph.
     fn normalize(
         &mut self,
         id: impl AsRef<str>,
         mut wip: BTreeMap<SchemaRef, Schemalet>,
-    ) -> Result<()> {
+    ) -> Result<BTreeSet<SchemaRef>> {
+        let mut added = BTreeSet::new();
         let mut pass = 0;
 
         loop {
@@ -238,6 +472,7 @@ impl Normalizer {
                         println!("canonical");
                         println!("{}", serde_json::to_string_pretty(&schemalet).unwrap());
                         simplified = true;
+                        added.insert(k.clone());
                         self.canonical.insert(k, schemalet);
                     }
 
@@ -261,11 +496,1386 @@ impl Normalizer {
             }
         }
 
-        for (schema_ref, schemalet) in &self.canonical {
-            println!("canonical {}", schema_ref);
-            println!("{}", serde_json::to_string_pretty(schemalet).unwrap());
+        Ok(added)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        convert::{
+            additional_properties_map_id, non_null_id, pattern_key_id, FormatType, UserType,
+        },
+        schemalet::SchemaRef,
+        typespace, Bundle,
+    };
+
+    use super::{Error, Typify};
+
+    /// Generate code for the given `$defs` of `schema` (or for its root if
+    /// there are none) once `configure` has set up the `Typify`.
+    fn generate(
+        schema: &serde_json::Value,
+        defs: &[&str],
+        configure: impl FnOnce(&mut Typify),
+    ) -> Result<String, Error> {
+        Ok(generate_typespace(schema, defs, configure)?.render())
+    }
+
+    /// The typespace from which [`generate`] renders code.
+    fn generate_typespace(
+        schema: &serde_json::Value,
+        defs: &[&str],
+        configure: impl FnOnce(&mut Typify),
+    ) -> Result<typespace::Typespace, Error> {
+        let doc = schema["$id"].as_str().unwrap();
+        let mut bundle = Bundle::default();
+        bundle.add_content(schema.to_string()).unwrap();
+        let mut typify = Typify::new_with_bundle(bundle);
+        configure(&mut typify);
+        if defs.is_empty() {
+            typify.add_type_by_id(doc)?;
+        }
+        for def in defs {
+            typify.add_type_by_id(format!("{doc}#/$defs/{def}"))?;
+        }
+        typify.into_typespace()
+    }
+
+    /// Check `code` against `tests/output/{name}.rs`, which [`output`]
+    /// includes so that tests can exercise the generated code. Set
+    /// `BUNDLER_OVERWRITE` to update the file.
+    fn assert_output(name: &str, code: &str) {
+        let path = format!("{}/tests/output/{name}.rs", env!("CARGO_MANIFEST_DIR"));
+        if std::env::var_os("BUNDLER_OVERWRITE").is_some() {
+            std::fs::write(&path, code).unwrap();
+        } else {
+            let expected = std::fs::read_to_string(&path).unwrap();
+            assert!(
+                code == expected,
+                "{path} is out of date; set BUNDLER_OVERWRITE=1 to update it\n{code}"
+            );
+        }
+    }
+
+    /// Generated code as checked in by [`assert_output`].
+    #[allow(dead_code, unused_imports)]
+    mod output {
+        pub mod property_states {
+            include!("../tests/output/property_states.rs");
+        }
+        pub mod property_states_distinguish_null {
+            include!("../tests/output/property_states_distinguish_null.rs");
+        }
+        pub mod default_fn_names {
+            include!("../tests/output/default_fn_names.rs");
+        }
+        pub mod one_of_tagging {
+            include!("../tests/output/one_of_tagging.rs");
+        }
+        pub mod builders {
+            include!("../tests/output/builders.rs");
+        }
+    }
+
+    /// Check that `code` contains each of the `expected` snippets.
+    fn assert_contains(code: &str, expected: &[&str]) {
+        for expected in expected {
+            assert!(code.contains(expected), "{expected}\n{code}");
+        }
+    }
+
+    #[test]
+    fn test_add_type_by_id_repeatedly() {
+        let doc = "https://example.com/defs.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "common": {
+                    "type": "object",
+                    "properties": { "name": { "type": "string" } }
+                },
+                "a": {
+                    "type": "object",
+                    "properties": {
+                        "extensions": {
+                            "type": "object",
+                            "patternProperties": { "^x-": { "$ref": "#/$defs/common" } }
+                        }
+                    }
+                },
+                "b": {
+                    "type": "object",
+                    "properties": { "common": { "$ref": "#/$defs/common" } },
+                    "additionalProperties": { "type": "integer" }
+                },
+                "c": {
+                    "type": "object",
+                    "properties": {
+                        "value": {
+                            "oneOf": [
+                                { "$ref": "#/$defs/b" },
+                                { "type": "string" },
+                                { "type": "null" }
+                            ]
+                        }
+                    }
+                }
+            }
+        });
+
+        let mut bundle = Bundle::default();
+        bundle.add_content(schema.to_string()).unwrap();
+        let mut typify = Typify::new_with_bundle(bundle);
+
+        // Each entry point adds only what's reachable from it and that we
+        // haven't already seen.
+        let mut reachable = vec![format!("{doc}#/$defs/common")];
+        for def in ["a", "b", "c"] {
+            let id = format!("{doc}#/$defs/{def}");
+            let before = typify.raw_graph().len();
+            typify.add_type_by_id(&id).unwrap();
+            reachable.push(id);
+
+            let raw = typify.raw_graph();
+            assert!(raw.len() > before);
+            for schema_ref in raw.keys() {
+                let schema_ref = schema_ref.to_string();
+                assert!(
+                    reachable.iter().any(|id| schema_ref.starts_with(id)),
+                    "{schema_ref}"
+                );
+            }
+        }
+
+        // Adding one again does nothing.
+        let raw = typify.raw_graph().len();
+        let canonical = typify.canonical_graph().len();
+        typify.add_type_by_id(format!("{doc}#/$defs/a")).unwrap();
+        assert_eq!(typify.raw_graph().len(), raw);
+        assert_eq!(typify.canonical_graph().len(), canonical);
+
+        // The schemalets derived from each addition are present.
+        let graph = typify.canonical_graph();
+        let integer = SchemaRef::Id(format!("{doc}#/$defs/b/additionalProperties"));
+        assert!(graph.contains_key(&pattern_key_id("^x-")));
+        assert!(graph.contains_key(&additional_properties_map_id(&integer)));
+        assert!(graph.contains_key(&non_null_id(&[
+            SchemaRef::Id(format!("{doc}#/$defs/b")),
+            SchemaRef::Internal("string".to_string()),
+        ])));
+
+        let typespace = typify.into_typespace().unwrap();
+        let code = typespace.render();
+        for name in [
+            "pub struct A",
+            "pub struct B",
+            "pub struct C",
+            "pub struct Common",
+        ] {
+            assert!(code.contains(name), "{name}\n{code}");
         }
+    }
 
-        Ok(())
+    #[test]
+    fn test_validated_string_names() {
+        let doc = "https://example.com/contacts.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "Email": {
+                    "type": "object",
+                    "properties": {
+                        "to": { "type": "string", "format": "email" },
+                        "subject": { "type": "string" }
+                    },
+                    "required": ["to"]
+                },
+                "Contact": {
+                    "type": "object",
+                    "properties": {
+                        "email": { "type": "string", "format": "email" },
+                        "host": { "type": "string", "format": "hostname" },
+                        "server": {
+                            "title": "Hostname",
+                            "type": "object",
+                            "properties": { "port": { "type": "integer" } }
+                        }
+                    }
+                }
+            }
+        });
+
+        let code = generate(&schema, &["Email", "Contact"], |_| ()).unwrap();
+
+        // The types from $defs and from a title keep their names; the
+        // formats' newtypes, emitted once for both properties, take others.
+        assert_eq!(code.matches("pub struct Email {").count(), 1, "{code}");
+        assert_eq!(
+            code.matches("pub struct Email2(String);").count(),
+            1,
+            "{code}"
+        );
+        assert_eq!(code.matches("pub struct Hostname {").count(), 1, "{code}");
+        assert_eq!(
+            code.matches("pub struct Hostname2(String);").count(),
+            1,
+            "{code}"
+        );
+        assert!(code.contains("pub to: Email2,"), "{code}");
+        assert!(
+            code.contains("pub host: ::std::option::Option<Hostname2>,"),
+            "{code}"
+        );
+    }
+
+    #[test]
+    fn test_invalid_validator() {
+        let doc = "https://example.com/contact.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "type": "object",
+            "properties": {
+                "email": { "type": "string", "format": "email" }
+            }
+        });
+
+        let result = generate(&schema, &[], |typify| {
+            typify.formats_mut().insert(
+                "email",
+                FormatType::Validated {
+                    name: "Email".to_string(),
+                    validate: "|value: &str| value.contains(".to_string(),
+                    check: |value| value.contains('@'),
+                },
+            );
+        });
+
+        assert!(matches!(
+            result,
+            Err(Error::Typespace(typespace::Error::InvalidValidator { name, .. })) if name == "Email"
+        ));
+    }
+
+    #[test]
+    fn test_constrained_strings() {
+        let doc = "https://example.com/account.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "Account": {
+                    "type": "object",
+                    "properties": {
+                        "code": {
+                            "type": "string",
+                            "pattern": "^[A-Z]+$",
+                            "minLength": 3,
+                            "maxLength": 3
+                        },
+                        "id": { "type": "string", "format": "uuid", "maxLength": 36 },
+                        "contact": { "type": "string", "format": "email", "minLength": 6 }
+                    },
+                    "required": ["code", "id", "contact"]
+                }
+            }
+        });
+
+        let code = generate(&schema, &["Account"], |_| ()).unwrap();
+
+        assert_contains(
+            &code,
+            &[
+                "pub code: AccountCode,",
+                "pub id: AccountId,",
+                "pub contact: AccountContact,",
+                "pub struct AccountCode(String);",
+                "impl ::std::convert::TryFrom<String> for AccountCode",
+                "if value.chars().count() < 3usize {",
+                "if value.chars().count() > 3usize {",
+                r#"return Err("must match \"^[A-Z]+$\"".to_string());"#,
+                // A known format is checked along with the other constraints
+                // rather than replacing them.
+                "if value.chars().count() > 36usize {",
+                "<::uuid::Uuid as ::serde::Deserialize>::deserialize(",
+                r#"return Err("must be a valid uuid".to_string());"#,
+                "if value.chars().count() < 6usize {",
+                r#"return Err("must be a valid email".to_string());"#,
+            ],
+        );
+        assert!(!code.contains("pub struct Email"), "{code}");
+    }
+
+    #[test]
+    fn test_property_states() {
+        let doc = "https://example.com/person.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "Person": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "spouse": { "type": ["string", "null"] },
+                        "nickname": { "type": "string" },
+                        "middle": { "type": ["string", "null"] }
+                    },
+                    "required": ["name", "spouse"]
+                }
+            }
+        });
+
+        let render = |distinguish_null: bool| {
+            generate(&schema, &["Person"], |typify| {
+                typify.set_distinguish_null(distinguish_null)
+            })
+            .unwrap()
+        };
+
+        assert_output("property_states", &render(false));
+        assert_output("property_states_distinguish_null", &render(true));
+
+        // Required properties must be present even if they may be null;
+        // optional ones are skipped when absent.
+        use output::property_states::Person;
+        assert!(serde_json::from_value::<Person>(json!({ "spouse": null })).is_err());
+        assert!(serde_json::from_value::<Person>(json!({ "name": "Ann" })).is_err());
+        let person =
+            serde_json::from_value::<Person>(json!({ "name": "Ann", "spouse": null })).unwrap();
+        assert_eq!(person.spouse, None);
+        assert_eq!(person.nickname, None);
+        assert_eq!(
+            serde_json::to_value(&person).unwrap(),
+            json!({ "name": "Ann", "spouse": null })
+        );
+
+        // By default, an optional nullable property is a single Option so
+        // null and absent are the same.
+        let person = serde_json::from_value::<Person>(
+            json!({ "name": "Ann", "spouse": "Bob", "middle": null }),
+        )
+        .unwrap();
+        assert_eq!(person.middle, None);
+        assert_eq!(
+            serde_json::to_value(&person).unwrap(),
+            json!({ "name": "Ann", "spouse": "Bob" })
+        );
+
+        // Distinguishing null from absent takes two.
+        use output::property_states_distinguish_null::Person as DistinctPerson;
+        let absent =
+            serde_json::from_value::<DistinctPerson>(json!({ "name": "Ann", "spouse": null }))
+                .unwrap();
+        assert_eq!(absent.middle, None);
+        let null = json!({ "name": "Ann", "spouse": null, "middle": null });
+        let person = serde_json::from_value::<DistinctPerson>(null.clone()).unwrap();
+        assert_eq!(person.middle, Some(None));
+        assert_eq!(serde_json::to_value(&person).unwrap(), null);
+        let person = serde_json::from_value::<DistinctPerson>(
+            json!({ "name": "Ann", "spouse": null, "middle": "Jo" }),
+        )
+        .unwrap();
+        assert_eq!(person.middle, Some(Some("Jo".to_string())));
+    }
+
+    #[test]
+    fn test_invalid_defaults() {
+        let check = |property: serde_json::Value| {
+            let doc = "https://example.com/defaults.json";
+            let schema = json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$id": doc,
+                "$defs": {
+                    "Settings": {
+                        "type": "object",
+                        "properties": { "value": property }
+                    }
+                }
+            });
+            match generate(&schema, &["Settings"], |_| ()) {
+                Ok(_) => Ok(()),
+                Err(Error::Typespace(typespace::Error::InvalidDefault { .. })) => Err(()),
+                Err(e) => panic!("{e:?}"),
+            }
+        };
+
+        let pattern = json!({ "type": "string", "pattern": "^[A-Z]+$" });
+        let email = json!({ "type": "string", "format": "email" });
+        let uuid = json!({ "type": "string", "format": "uuid" });
+        let short_uuid = json!({ "type": "string", "format": "uuid", "maxLength": 36 });
+        let date = json!({ "type": "string", "format": "date" });
+        for (property, good, bad) in [
+            (pattern, json!("ABC"), json!("abc")),
+            (email, json!("me@example.com"), json!("me")),
+            (
+                uuid,
+                json!("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+                json!("67e55044"),
+            ),
+            (
+                short_uuid,
+                json!("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+                json!("not-a-uuid"),
+            ),
+            (date, json!("2024-02-29"), json!("2023-02-29")),
+        ] {
+            let with_default = |default: &serde_json::Value| {
+                let mut property = property.clone();
+                property["default"] = default.clone();
+                property
+            };
+            assert_eq!(check(with_default(&good)), Ok(()), "{good}");
+            assert_eq!(check(with_default(&bad)), Err(()), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_default_fn_names() {
+        let doc = "https://example.com/defaults.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "Foo": {
+                    "type": "object",
+                    "properties": {
+                        "bar_baz": { "type": "integer", "default": 1 }
+                    }
+                },
+                "FooBar": {
+                    "type": "object",
+                    "properties": {
+                        "baz": { "type": "integer", "default": 2 }
+                    }
+                }
+            }
+        });
+
+        let code = generate(&schema, &["Foo", "FooBar"], |_| ()).unwrap();
+
+        // Both would be `foo_bar_baz`.
+        assert_output("default_fn_names", &code);
+
+        // Absent properties take their defaults, as does `Default`.
+        use output::default_fn_names::{Foo, FooBar};
+        let foo = serde_json::from_value::<Foo>(json!({})).unwrap();
+        assert_eq!(foo.bar_baz, 1);
+        assert_eq!(foo, Foo::default());
+        let foo_bar = serde_json::from_value::<FooBar>(json!({})).unwrap();
+        assert_eq!(foo_bar.baz, 2);
+        assert_eq!(foo_bar, FooBar::default());
+        let foo_bar = serde_json::from_value::<FooBar>(json!({ "baz": 3 })).unwrap();
+        assert_eq!(foo_bar.baz, 3);
+    }
+
+    #[test]
+    fn test_one_of_tagging() {
+        let doc = "https://example.com/shapes.json";
+        let variant = |kind: &str, properties: serde_json::Value, required: &[&str]| {
+            let mut variant = json!({
+                "type": "object",
+                "properties": { "kind": { "const": kind } },
+                "required": ["kind"]
+            });
+            variant["properties"]
+                .as_object_mut()
+                .unwrap()
+                .extend(properties.as_object().unwrap().clone());
+            variant["required"]
+                .as_array_mut()
+                .unwrap()
+                .extend(required.iter().map(|name| json!(name)));
+            variant
+        };
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                // Every variant has the tag and at most one other property,
+                // with the same name.
+                "Adjacent": {
+                    "oneOf": [
+                        variant("circle", json!({ "data": { "type": "number" } }), &["data"]),
+                        variant("square", json!({ "data": { "type": "integer" } }), &["data"]),
+                        variant("point", json!({}), &[])
+                    ]
+                },
+                // Every variant has the tag.
+                "Internal": {
+                    "oneOf": [
+                        variant("circle", json!({ "radius": { "type": "number" } }), &["radius"]),
+                        variant("square", json!({ "side": { "type": "number" } }), &["side"])
+                    ]
+                },
+                // Nothing tells these apart; a value may even match both.
+                "Untagged": {
+                    "oneOf": [
+                        {
+                            "title": "by name",
+                            "type": "object",
+                            "properties": { "name": { "type": "string" } }
+                        },
+                        {
+                            "title": "by id",
+                            "type": "object",
+                            "properties": { "id": { "type": "integer" } }
+                        }
+                    ]
+                },
+                // Every long name is also a name.
+                "Name": {
+                    "oneOf": [
+                        { "type": "string" },
+                        { "type": "string", "minLength": 3 }
+                    ]
+                },
+                // Nothing tells these apart but their types.
+                "Scalar": {
+                    "oneOf": [{ "type": "string" }, { "type": "integer" }]
+                }
+            }
+        });
+
+        let code = generate(
+            &schema,
+            &["Adjacent", "Internal", "Untagged", "Name", "Scalar"],
+            |_| (),
+        )
+        .unwrap();
+
+        assert_output("one_of_tagging", &code);
+
+        use output::one_of_tagging::{Adjacent, Internal, Scalar, Untagged};
+        let round_trip = |value: serde_json::Value, expected| {
+            let adjacent = serde_json::from_value::<Adjacent>(value.clone()).unwrap();
+            assert_eq!(adjacent, expected);
+            assert_eq!(serde_json::to_value(adjacent).unwrap(), value);
+        };
+        round_trip(
+            json!({ "kind": "circle", "data": 1.5 }),
+            Adjacent::Circle(1.5),
+        );
+        round_trip(json!({ "kind": "square", "data": 2 }), Adjacent::Square(2));
+        round_trip(json!({ "kind": "point" }), Adjacent::Point);
+        assert!(
+            serde_json::from_value::<Adjacent>(json!({ "kind": "square", "data": 1.5 })).is_err()
+        );
+
+        assert_eq!(
+            serde_json::from_value::<Internal>(json!({ "kind": "square", "side": 2.0 })).unwrap(),
+            Internal::Square { side: 2.0 }
+        );
+        assert!(
+            serde_json::from_value::<Internal>(json!({ "kind": "square", "radius": 2.0 })).is_err()
+        );
+        assert!(serde_json::from_value::<Internal>(json!({ "side": 2.0 })).is_err());
+
+        // Untagged variants go by shape and the first that fits wins, even
+        // where the schema would reject the value as matching both.
+        assert_eq!(
+            serde_json::from_value::<Untagged>(json!({ "id": 7 })).unwrap(),
+            Untagged::ByName { name: None }
+        );
+        assert_eq!(
+            serde_json::from_value::<Scalar>(json!(7)).unwrap(),
+            Scalar::Integer(7)
+        );
+        assert_eq!(
+            serde_json::from_value::<Scalar>(json!("7")).unwrap(),
+            Scalar::String("7".to_string())
+        );
+
+        // Where variants may overlap, the enum says that it doesn't reject
+        // what the schema does.
+        let note = "///A value that's valid for more than one variant is taken as the first of them, though the schema rejects it.";
+        for (name, overlapping) in [("Untagged", true), ("Name", true), ("Scalar", false)] {
+            let doc = code[..code.find(&format!("pub enum {name} {{")).unwrap()]
+                .rsplit("}\n")
+                .next()
+                .unwrap();
+            assert_eq!(doc.contains(note), overlapping, "{name}\n{code}");
+        }
+    }
+
+    #[test]
+    fn test_tuples_and_arrays() {
+        let doc = "https://example.com/geometry.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "Geometry": {
+                    "type": "object",
+                    "properties": {
+                        "point": {
+                            "type": "array",
+                            "prefixItems": [{ "type": "number" }, { "type": "number" }],
+                            "items": false
+                        },
+                        "color": {
+                            "type": "array",
+                            "items": { "type": "integer", "minimum": 0, "maximum": 255 },
+                            "minItems": 3,
+                            "maxItems": 3
+                        },
+                        "label": {
+                            "type": "array",
+                            "prefixItems": [{ "type": "string" }, { "type": "integer" }],
+                            "items": { "type": "boolean" }
+                        },
+                        "path": {
+                            "type": "array",
+                            "prefixItems": [{ "type": "number" }],
+                            "items": { "type": "number" }
+                        },
+                        "matrix": {
+                            "type": "array",
+                            "items": { "type": "number" },
+                            "minItems": 64,
+                            "maxItems": 64
+                        }
+                    },
+                    "required": ["point", "color", "label", "path", "matrix"]
+                }
+            }
+        });
+
+        let code = generate(&schema, &["Geometry"], |_| ()).unwrap();
+
+        assert_contains(
+            &code,
+            &[
+                "pub point: (f64, f64),",
+                "pub color: [u8; 3],",
+                "pub label: GeometryLabel,",
+                "pub struct GeometryLabel(pub String, pub i64, pub ::std::vec::Vec<bool>);",
+                "pub path: ::std::vec::Vec<f64>,",
+                "pub matrix: ::std::vec::Vec<f64>,",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_unique_items() {
+        let doc = "https://example.com/survey.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "Survey": {
+                    "type": "object",
+                    "properties": {
+                        "tags": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "uniqueItems": true
+                        },
+                        "scores": {
+                            "type": "array",
+                            "items": { "type": "number" },
+                            "uniqueItems": true
+                        }
+                    },
+                    "required": ["tags", "scores"]
+                }
+            }
+        });
+
+        let code = generate(&schema, &["Survey"], |_| ()).unwrap();
+
+        // Strings are Ord so they go in a BTreeSet; floats are neither Ord
+        // nor Hash so they fall back to a Vec that checks for duplicates.
+        assert_contains(
+            &code,
+            &[
+                "pub tags: ::std::collections::BTreeSet<String>,",
+                "pub scores: SurveyScores,",
+                "pub struct SurveyScores(::std::vec::Vec<f64>);",
+                "impl ::std::convert::TryFrom<::std::vec::Vec<f64>> for SurveyScores {",
+                "return Err(format!(\"duplicate item {item}\"));",
+                "impl<'de> ::serde::Deserialize<'de> for SurveyScores {",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_map_keys() {
+        let doc = "https://example.com/inventory.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "Inventory": {
+                    "type": "object",
+                    "properties": {
+                        "by_id": {
+                            "type": "object",
+                            "propertyNames": { "format": "uuid" },
+                            "additionalProperties": { "type": "integer" }
+                        },
+                        "by_region": {
+                            "type": "object",
+                            "propertyNames": { "$ref": "#/$defs/Region" },
+                            "additionalProperties": { "type": "integer" }
+                        },
+                        "by_sku": {
+                            "type": "object",
+                            "patternProperties": {
+                                "^[A-Z]{3}-[0-9]{4}$": { "type": "integer" }
+                            },
+                            "additionalProperties": false
+                        },
+                        "by_name": {
+                            "type": "object",
+                            "additionalProperties": { "type": "integer" }
+                        }
+                    },
+                    "required": ["by_id", "by_region", "by_sku", "by_name"]
+                },
+                "Region": {
+                    "type": "string",
+                    "enum": ["north", "south"]
+                }
+            }
+        });
+
+        let code = generate(&schema, &["Inventory"], |_| ()).unwrap();
+
+        assert_contains(
+            &code,
+            &[
+                "pub by_id: ::std::collections::BTreeMap<::uuid::Uuid, i64>,",
+                "pub by_region: ::std::collections::BTreeMap<Region, i64>,",
+                "pub by_sku: ::std::collections::BTreeMap<InventoryBySkuKey, i64>,",
+                "pub by_name: ::std::collections::BTreeMap<String, i64>,",
+                "pub struct InventoryBySkuKey(String);",
+                "impl ::std::convert::TryFrom<String> for InventoryBySkuKey {",
+                "^[A-Z]{3}-[0-9]{4}$",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_additional_properties_map() {
+        let doc = "https://example.com/labels.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "Labels": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" }
+                    },
+                    "required": ["name"],
+                    "additionalProperties": { "type": "string" }
+                },
+                "Extras": {
+                    "type": "object",
+                    "properties": {
+                        "extra": { "type": "boolean" }
+                    },
+                    "additionalProperties": { "type": "integer" }
+                }
+            }
+        });
+
+        let code = generate(&schema, &["Labels", "Extras"], |_| ()).unwrap();
+
+        // The map's field name steers clear of the fixed properties.
+        assert_contains(
+            &code,
+            &[
+                "pub struct Labels {\n    pub name: String,\n    #[serde(flatten)]\n    pub extra: ::std::collections::BTreeMap<String, String>,\n}",
+                "pub extra: ::std::option::Option<bool>,\n    #[serde(flatten)]\n    pub extra_: ::std::collections::BTreeMap<String, i64>,",
+                "extra_: ::std::default::Default::default(),",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_deny_unknown_fields() {
+        let doc = "https://example.com/records.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "Closed": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" }
+                    },
+                    "additionalProperties": false
+                },
+                "Combined": {
+                    "allOf": [
+                        {
+                            "type": "object",
+                            "properties": {
+                                "name": { "type": "string" }
+                            },
+                            "required": ["name"],
+                            "unevaluatedProperties": false
+                        },
+                        {
+                            "type": "object",
+                            "properties": {
+                                "id": { "type": "integer" }
+                            },
+                            "required": ["id"],
+                            "unevaluatedProperties": { "type": "string" }
+                        }
+                    ]
+                },
+                "Open": {
+                    "allOf": [
+                        {
+                            "type": "object",
+                            "properties": {
+                                "name": { "type": "string" }
+                            },
+                            "required": ["name"],
+                            "unevaluatedProperties": { "type": "string" }
+                        },
+                        {
+                            "type": "object",
+                            "properties": {
+                                "id": { "type": "integer" }
+                            },
+                            "required": ["id"],
+                            "unevaluatedProperties": { "type": "string", "maxLength": 8 }
+                        }
+                    ]
+                }
+            }
+        });
+
+        let code = generate(&schema, &["Closed", "Combined", "Open"], |_| ()).unwrap();
+
+        // Across the allOf, properties that neither subschema names must
+        // satisfy both `unevaluatedProperties` schemas.
+        assert_contains(
+            &code,
+            &[
+                "#[serde(deny_unknown_fields)]\npub struct Closed {",
+                "#[serde(deny_unknown_fields)]\npub struct Combined {",
+                "pub extra: ::std::collections::BTreeMap<String, OpenExtraValue>,",
+                "if value.chars().count() > 8usize {",
+            ],
+        );
+        assert!(
+            !code.contains("#[serde(deny_unknown_fields)]\npub struct Open {"),
+            "{code}"
+        );
+    }
+
+    #[test]
+    fn test_constants() {
+        let doc = "https://example.com/widget.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "Widget": {
+                    "type": "object",
+                    "properties": {
+                        "kind": { "const": "widget" },
+                        "color": { "type": "string", "enum": ["red", "light-green"] },
+                        "nothing": { "type": "null" },
+                        "never": false
+                    },
+                    "required": ["kind", "color", "nothing"]
+                }
+            }
+        });
+
+        let code = generate(&schema, &["Widget"], |_| ()).unwrap();
+
+        assert_contains(
+            &code,
+            &[
+                "pub kind: WidgetKind,",
+                "pub struct WidgetKind;",
+                "serializer.serialize_str(\"widget\")",
+                "if value == \"widget\" {",
+                "pub color: WidgetColor,",
+                "#[serde(rename = \"light-green\")]\n    LightGreen,",
+                "pub nothing: (),",
+                "pub never: ::std::option::Option<WidgetNever>,",
+                "pub enum WidgetNever {}",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_nullable() {
+        let doc = "https://example.com/contact.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "Contact": {
+                    "type": "object",
+                    "properties": {
+                        "email": { "type": ["string", "null"] },
+                        "address": {
+                            "oneOf": [
+                                { "type": "null" },
+                                { "$ref": "#/$defs/Address" }
+                            ]
+                        },
+                        "age": { "type": "integer", "minimum": 0, "nullable": true },
+                        "phone": { "type": "string", "nullable": true }
+                    },
+                    "required": ["email", "address", "age"]
+                },
+                "Address": {
+                    "type": "object",
+                    "properties": {
+                        "street": { "type": "string" }
+                    },
+                    "required": ["street"]
+                }
+            }
+        });
+
+        let code = generate(&schema, &["Contact"], |_| ()).unwrap();
+
+        // A type array, a oneOf with null, and OpenAPI's `nullable` all
+        // become an Option; an optional nullable property isn't doubly so.
+        assert_contains(
+            &code,
+            &[
+                "pub email: ::std::option::Option<String>,",
+                "pub address: ::std::option::Option<Address>,",
+                "pub age: ::std::option::Option<u64>,",
+                "#[serde(default, skip_serializing_if = \"::std::option::Option::is_none\")]\n    pub phone: ::std::option::Option<String>,",
+            ],
+        );
+        assert!(!code.contains("Null"), "{code}");
+    }
+
+    #[test]
+    fn test_render_struct() {
+        let doc = "https://example.com/library.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "Library": {
+                    "description": "A collection of books.",
+                    "type": "object",
+                    "properties": {
+                        "books": {
+                            "type": "array",
+                            "items": { "$ref": "#/$defs/Book" }
+                        },
+                        "featured": { "$ref": "#/$defs/Book" },
+                        "by-isbn": {
+                            "type": "object",
+                            "additionalProperties": { "$ref": "#/$defs/Book" }
+                        }
+                    },
+                    "required": ["books", "by-isbn"]
+                },
+                "Book": {
+                    "type": "object",
+                    "properties": {
+                        "title": {
+                            "description": "The title on the cover.",
+                            "type": "string"
+                        },
+                        "pageCount": { "type": "integer", "minimum": 1 }
+                    },
+                    "required": ["title"]
+                }
+            }
+        });
+
+        let code = generate(&schema, &["Library"], |_| ()).unwrap();
+
+        assert_contains(
+            &code,
+            &[
+                "///A collection of books.\n#[derive(",
+                "pub struct Library {",
+                "pub books: ::std::vec::Vec<Book>,",
+                "#[serde(rename = \"by-isbn\")]\n    pub by_isbn: ::std::collections::BTreeMap<String, Book>,",
+                "pub featured: ::std::option::Option<Book>,",
+                "pub struct Book {",
+                "rename = \"pageCount\",",
+                "pub page_count: ::std::option::Option<::std::num::NonZeroU64>,",
+                "///The title on the cover.\n    pub title: String,",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_replacements() {
+        let doc = "https://example.com/orders.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "Order": {
+                    "type": "object",
+                    "properties": {
+                        "total": { "$ref": "#/$defs/Money" },
+                        "placed": { "$ref": "#/$defs/Timestamp" },
+                        "customer": { "$ref": "#/$defs/CustomerId" }
+                    },
+                    "required": ["total", "placed", "customer"]
+                },
+                "Money": {
+                    "type": "object",
+                    "properties": {
+                        "amount": { "type": "integer" },
+                        "currency": { "type": "string" }
+                    }
+                },
+                "Timestamp": {
+                    "title": "Timestamp",
+                    "type": "string"
+                },
+                "CustomerId": {
+                    "type": "string",
+                    "x-rust-type": "::ids::CustomerId"
+                }
+            }
+        });
+
+        let code = generate(&schema, &["Order"], |typify| {
+            typify
+                .replacements_mut()
+                .insert_id(
+                    format!("{doc}#/$defs/Money"),
+                    UserType::new(
+                        "::money::Money",
+                        [
+                            typespace::TypeTrait::Clone,
+                            typespace::TypeTrait::Debug,
+                            typespace::TypeTrait::Serialize,
+                            typespace::TypeTrait::Deserialize,
+                        ],
+                    ),
+                )
+                .insert_title(
+                    "Timestamp",
+                    UserType::new("::time::Timestamp", typespace::TypeTrait::ALL),
+                )
+                .insert_rust_type(UserType::new(
+                    "::ids::CustomerId",
+                    typespace::TypeTrait::ALL,
+                ));
+        })
+        .unwrap();
+
+        // Money isn't PartialEq etc. so neither is the Order that holds it.
+        assert_contains(
+            &code,
+            &[
+                "#[derive(Clone, Debug, ::serde::Deserialize, ::serde::Serialize)]\npub struct Order {",
+                "pub total: ::money::Money,",
+                "pub placed: ::time::Timestamp,",
+                "pub customer: ::ids::CustomerId,",
+            ],
+        );
+        assert!(!code.contains("pub struct Money"), "{code}");
+    }
+
+    #[test]
+    fn test_builders() {
+        let doc = "https://example.com/pets.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "$defs": {
+                "Pet": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "legs": { "type": "integer", "minimum": 0, "maximum": 8, "default": 4 },
+                        "tag": { "type": "string" }
+                    },
+                    "required": ["name"]
+                },
+                "PetBuilder": {
+                    "type": "object",
+                    "properties": {
+                        "shop": { "type": "string" }
+                    },
+                    "required": ["shop"]
+                },
+                "Shape": {
+                    "oneOf": [
+                        {
+                            "type": "object",
+                            "properties": {
+                                "kind": { "const": "circle" },
+                                "radius": { "type": "number" }
+                            },
+                            "required": ["kind", "radius"]
+                        },
+                        {
+                            "type": "object",
+                            "properties": {
+                                "kind": { "const": "square" },
+                                "side": { "type": "number" }
+                            },
+                            "required": ["kind", "side"]
+                        }
+                    ]
+                }
+            }
+        });
+
+        let code = generate(&schema, &["Pet", "PetBuilder", "Shape"], |typify| {
+            typify.set_builders(true)
+        })
+        .unwrap();
+
+        // The builder for Pet steers clear of the PetBuilder from $defs.
+        assert_output("builders", &code);
+
+        use output::builders::{Pet, PetBuilder, PetBuilder2, Shape, ShapeCircleBuilder};
+
+        // Defaulted fields start with the default; optional ones start empty.
+        let pet = Pet::builder().name("Rex").build().unwrap();
+        assert_eq!(
+            pet,
+            Pet {
+                legs: 4,
+                name: "Rex".to_string(),
+                tag: None,
+            }
+        );
+        let pet = Pet::builder()
+            .name("Polly")
+            .legs(2)
+            .tag(Some("parrot".to_string()))
+            .build()
+            .unwrap();
+        assert_eq!((pet.legs, pet.tag.as_deref()), (2, Some("parrot")));
+
+        // Missing required fields and values that don't convert are both
+        // reported.
+        assert_eq!(
+            Pet::builder().build().unwrap_err(),
+            "no value supplied for name"
+        );
+        let error = Pet::builder().legs(300).build().unwrap_err();
+        assert!(
+            error.starts_with("error converting supplied value for legs: ")
+                && error.ends_with("; no value supplied for name"),
+            "{error}"
+        );
+
+        // Builders round-trip through the values they build.
+        let pet = Pet::builder().name("Rex").build().unwrap();
+        let renamed = PetBuilder2::from(pet).name("Max").build().unwrap();
+        assert_eq!((renamed.legs, renamed.name.as_str()), (4, "Max"));
+
+        assert_eq!(
+            PetBuilder::builder().shop("Pets R Us").build().unwrap(),
+            PetBuilder {
+                shop: "Pets R Us".to_string()
+            }
+        );
+        assert_eq!(
+            ShapeCircleBuilder::default().radius(1.5).build().unwrap(),
+            Shape::Circle { radius: 1.5 }
+        );
+        assert_eq!(
+            ShapeCircleBuilder::default().build().unwrap_err(),
+            "no value supplied for radius"
+        );
+    }
+
+    #[test]
+    fn test_render_files() {
+        let doc = "https://example.com/catalog.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "title": "Catalog",
+            "type": "object",
+            "properties": {
+                "orders": { "type": "array", "items": { "$ref": "#/$defs/Order" } },
+                "contact": { "type": "string", "format": "email" }
+            },
+            "required": ["orders"],
+            "$defs": {
+                "Order": {
+                    "type": "object",
+                    "properties": {
+                        "lines": { "type": "array", "items": { "$ref": "#/$defs/Line" } },
+                        "note": { "type": ["string", "null"] },
+                        "contact": { "type": "string", "format": "email" }
+                    },
+                    "required": ["lines"]
+                },
+                "Line": {
+                    "type": "object",
+                    "properties": {
+                        "sku": { "type": "string" },
+                        "quantity": { "type": "integer", "minimum": 1, "default": 1 }
+                    },
+                    "required": ["sku"]
+                }
+            }
+        });
+
+        let typespace =
+            generate_typespace(&schema, &[], |typify| typify.set_distinguish_null(true)).unwrap();
+        let files = typespace.render_files(&typespace::ModuleSplit::Defs);
+        assert_eq!(
+            files
+                .keys()
+                .map(|path| path.to_str().unwrap())
+                .collect::<Vec<_>>(),
+            ["line.rs", "mod.rs", "order.rs"]
+        );
+
+        // Each file imports the types it mentions from the others and holds
+        // the helpers that its own types need; validated strings live in
+        // the root.
+        let root = &files[std::path::Path::new("mod.rs")];
+        assert!(
+            root.starts_with("use self::order::Order;\npub mod line;\npub mod order;\n#[derive("),
+            "{root}"
+        );
+        assert_contains(root, &["pub struct Catalog {", "pub struct Email(String);"]);
+        assert!(!root.contains("mod defaults") && !root.contains("fn deserialize_some"));
+
+        let order = &files[std::path::Path::new("order.rs")];
+        assert!(
+            order.starts_with("use super::Email;\nuse super::line::Line;\n#[derive("),
+            "{order}"
+        );
+        assert_contains(
+            order,
+            &[
+                "pub contact: ::std::option::Option<Email>,",
+                "pub lines: ::std::vec::Vec<Line>,",
+                "fn deserialize_some<'de, T, D>(",
+            ],
+        );
+        assert!(!order.contains("mod defaults") && !order.contains("pub struct Email"));
+
+        let line = &files[std::path::Path::new("line.rs")];
+        assert!(line.starts_with("#[derive("), "{line}");
+        assert_contains(
+            line,
+            &[
+                r#"#[serde(default = "defaults::line_quantity")]"#,
+                "mod defaults {\n    use super::*;\n    pub(super) fn line_quantity()",
+            ],
+        );
+        assert!(!line.contains("fn deserialize_some"));
+
+        // Nested modules reach the root through each of their parents.
+        let split = typespace::ModuleSplit::Mapping(
+            [
+                (format!("{doc}#/$defs/Order"), "shop::orders".to_string()),
+                (format!("{doc}#/$defs/Line"), "shop".to_string()),
+            ]
+            .into(),
+        );
+        let files = typespace.render_files(&split);
+        assert_eq!(
+            files
+                .keys()
+                .map(|path| path.to_str().unwrap())
+                .collect::<Vec<_>>(),
+            ["mod.rs", "shop/orders.rs", "shop.rs"]
+        );
+        let root = &files[std::path::Path::new("mod.rs")];
+        assert!(
+            root.starts_with("use self::shop::orders::Order;\npub mod shop;\n#[derive("),
+            "{root}"
+        );
+        let shop = &files[std::path::Path::new("shop.rs")];
+        assert!(shop.starts_with("pub mod orders;\n#[derive("), "{shop}");
+        assert_contains(shop, &["pub struct Line {", "mod defaults {"]);
+        let orders = &files[std::path::Path::new("shop/orders.rs")];
+        assert!(
+            orders.starts_with("use super::super::Email;\nuse super::super::shop::Line;\n"),
+            "{orders}"
+        );
+
+        // Without a split, everything is in the root module.
+        let files = typespace.render_files(&typespace::ModuleSplit::None);
+        assert_eq!(
+            files
+                .keys()
+                .map(|path| path.to_str().unwrap())
+                .collect::<Vec<_>>(),
+            ["mod.rs"]
+        );
+        assert_eq!(files[std::path::Path::new("mod.rs")], typespace.render());
+
+        let dir = std::env::temp_dir().join(format!("bundler-render-files-{}", std::process::id()));
+        typespace
+            .write_files(&dir, &typespace::ModuleSplit::Defs)
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("order.rs")).unwrap(),
+            *order
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_root_and_wrapper_names() {
+        let doc = "https://example.com/catalog.json";
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": doc,
+            "title": "Catalog",
+            "type": "object",
+            "properties": {
+                "featured": { "$ref": "#/$defs/Featured" },
+                "by_code": { "$ref": "#/$defs/ByCode" }
+            },
+            "required": ["by_code"],
+            "$defs": {
+                "Featured": {
+                    "type": ["object", "null"],
+                    "properties": { "code": { "type": "string" } },
+                    "required": ["code"]
+                },
+                "ByCode": {
+                    "type": "object",
+                    "additionalProperties": {
+                        "type": "object",
+                        "properties": { "price": { "type": "integer" } },
+                        "required": ["price"]
+                    }
+                }
+            }
+        });
+
+        let code = generate(&schema, &[], |_| ()).unwrap();
+
+        assert_contains(
+            &code,
+            &[
+                // The root's title is preferred to a generic name.
+                "pub struct Catalog {",
+                // Option and map types have no names of their own; they pass
+                // theirs on to the types they contain.
+                "pub featured: ::std::option::Option<Featured>,",
+                "pub struct Featured {",
+                "pub by_code: ::std::collections::BTreeMap<String, ByCode>,",
+                "pub struct ByCode {",
+            ],
+        );
+        assert!(!code.contains("SchemaRoot"), "{code}");
     }
 }
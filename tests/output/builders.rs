@@ -0,0 +1,283 @@
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ::serde::Deserialize,
+    ::serde::Serialize
+)]
+pub struct Pet {
+    #[serde(default = "defaults::pet_legs")]
+    pub legs: u8,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub tag: ::std::option::Option<String>,
+}
+/// A builder for [`Pet`].
+#[derive(Clone, Debug)]
+pub struct PetBuilder2 {
+    legs: ::std::result::Result<u8, String>,
+    name: ::std::result::Result<String, String>,
+    tag: ::std::result::Result<::std::option::Option<String>, String>,
+}
+impl ::std::default::Default for PetBuilder2 {
+    fn default() -> Self {
+        Self {
+            legs: Ok(defaults::pet_legs()),
+            name: Err("no value supplied for name".to_string()),
+            tag: Ok(::std::default::Default::default()),
+        }
+    }
+}
+impl PetBuilder2 {
+    pub fn legs<T>(mut self, value: T) -> Self
+    where
+        T: ::std::convert::TryInto<u8>,
+        T::Error: ::std::fmt::Display,
+    {
+        self.legs = value
+            .try_into()
+            .map_err(|e| format!("error converting supplied value for legs: {e}"));
+        self
+    }
+    pub fn name<T>(mut self, value: T) -> Self
+    where
+        T: ::std::convert::TryInto<String>,
+        T::Error: ::std::fmt::Display,
+    {
+        self.name = value
+            .try_into()
+            .map_err(|e| format!("error converting supplied value for name: {e}"));
+        self
+    }
+    pub fn tag<T>(mut self, value: T) -> Self
+    where
+        T: ::std::convert::TryInto<::std::option::Option<String>>,
+        T::Error: ::std::fmt::Display,
+    {
+        self.tag = value
+            .try_into()
+            .map_err(|e| format!("error converting supplied value for tag: {e}"));
+        self
+    }
+    pub fn build(self) -> ::std::result::Result<Pet, String> {
+        self.try_into()
+    }
+}
+impl ::std::convert::TryFrom<PetBuilder2> for Pet {
+    type Error = String;
+    fn try_from(value: PetBuilder2) -> ::std::result::Result<Self, Self::Error> {
+        let PetBuilder2 { legs, name, tag } = value;
+        match (legs, name, tag) {
+            (Ok(legs), Ok(name), Ok(tag)) => Ok(Pet { legs, name, tag }),
+            (legs, name, tag) => {
+                Err(
+                    [legs.err(), name.err(), tag.err()]
+                        .into_iter()
+                        .flatten()
+                        .collect::<::std::vec::Vec<_>>()
+                        .join("; "),
+                )
+            }
+        }
+    }
+}
+impl Pet {
+    pub fn builder() -> PetBuilder2 {
+        ::std::default::Default::default()
+    }
+}
+impl ::std::convert::From<Pet> for PetBuilder2 {
+    fn from(value: Pet) -> Self {
+        Self {
+            legs: Ok(value.legs),
+            name: Ok(value.name),
+            tag: Ok(value.tag),
+        }
+    }
+}
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ::serde::Deserialize,
+    ::serde::Serialize
+)]
+pub struct PetBuilder {
+    pub shop: String,
+}
+/// A builder for [`PetBuilder`].
+#[derive(Clone, Debug)]
+pub struct PetBuilderBuilder {
+    shop: ::std::result::Result<String, String>,
+}
+impl ::std::default::Default for PetBuilderBuilder {
+    fn default() -> Self {
+        Self {
+            shop: Err("no value supplied for shop".to_string()),
+        }
+    }
+}
+impl PetBuilderBuilder {
+    pub fn shop<T>(mut self, value: T) -> Self
+    where
+        T: ::std::convert::TryInto<String>,
+        T::Error: ::std::fmt::Display,
+    {
+        self.shop = value
+            .try_into()
+            .map_err(|e| format!("error converting supplied value for shop: {e}"));
+        self
+    }
+    pub fn build(self) -> ::std::result::Result<PetBuilder, String> {
+        self.try_into()
+    }
+}
+impl ::std::convert::TryFrom<PetBuilderBuilder> for PetBuilder {
+    type Error = String;
+    fn try_from(value: PetBuilderBuilder) -> ::std::result::Result<Self, Self::Error> {
+        let PetBuilderBuilder { shop } = value;
+        match (shop,) {
+            (Ok(shop),) => Ok(PetBuilder { shop }),
+            (shop,) => {
+                Err(
+                    [shop.err()]
+                        .into_iter()
+                        .flatten()
+                        .collect::<::std::vec::Vec<_>>()
+                        .join("; "),
+                )
+            }
+        }
+    }
+}
+impl PetBuilder {
+    pub fn builder() -> PetBuilderBuilder {
+        ::std::default::Default::default()
+    }
+}
+impl ::std::convert::From<PetBuilder> for PetBuilderBuilder {
+    fn from(value: PetBuilder) -> Self {
+        Self { shop: Ok(value.shop) }
+    }
+}
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    PartialOrd,
+    ::serde::Deserialize,
+    ::serde::Serialize
+)]
+#[serde(tag = "kind")]
+pub enum Shape {
+    #[serde(rename = "circle")]
+    Circle { radius: f64 },
+    #[serde(rename = "square")]
+    Square { side: f64 },
+}
+/// A builder for [`Shape::Circle`].
+#[derive(Clone, Debug)]
+pub struct ShapeCircleBuilder {
+    radius: ::std::result::Result<f64, String>,
+}
+impl ::std::default::Default for ShapeCircleBuilder {
+    fn default() -> Self {
+        Self {
+            radius: Err("no value supplied for radius".to_string()),
+        }
+    }
+}
+impl ShapeCircleBuilder {
+    pub fn radius<T>(mut self, value: T) -> Self
+    where
+        T: ::std::convert::TryInto<f64>,
+        T::Error: ::std::fmt::Display,
+    {
+        self.radius = value
+            .try_into()
+            .map_err(|e| format!("error converting supplied value for radius: {e}"));
+        self
+    }
+    pub fn build(self) -> ::std::result::Result<Shape, String> {
+        self.try_into()
+    }
+}
+impl ::std::convert::TryFrom<ShapeCircleBuilder> for Shape {
+    type Error = String;
+    fn try_from(value: ShapeCircleBuilder) -> ::std::result::Result<Self, Self::Error> {
+        let ShapeCircleBuilder { radius } = value;
+        match (radius,) {
+            (Ok(radius),) => Ok(Shape::Circle { radius }),
+            (radius,) => {
+                Err(
+                    [radius.err()]
+                        .into_iter()
+                        .flatten()
+                        .collect::<::std::vec::Vec<_>>()
+                        .join("; "),
+                )
+            }
+        }
+    }
+}
+/// A builder for [`Shape::Square`].
+#[derive(Clone, Debug)]
+pub struct ShapeSquareBuilder {
+    side: ::std::result::Result<f64, String>,
+}
+impl ::std::default::Default for ShapeSquareBuilder {
+    fn default() -> Self {
+        Self {
+            side: Err("no value supplied for side".to_string()),
+        }
+    }
+}
+impl ShapeSquareBuilder {
+    pub fn side<T>(mut self, value: T) -> Self
+    where
+        T: ::std::convert::TryInto<f64>,
+        T::Error: ::std::fmt::Display,
+    {
+        self.side = value
+            .try_into()
+            .map_err(|e| format!("error converting supplied value for side: {e}"));
+        self
+    }
+    pub fn build(self) -> ::std::result::Result<Shape, String> {
+        self.try_into()
+    }
+}
+impl ::std::convert::TryFrom<ShapeSquareBuilder> for Shape {
+    type Error = String;
+    fn try_from(value: ShapeSquareBuilder) -> ::std::result::Result<Self, Self::Error> {
+        let ShapeSquareBuilder { side } = value;
+        match (side,) {
+            (Ok(side),) => Ok(Shape::Square { side }),
+            (side,) => {
+                Err(
+                    [side.err()]
+                        .into_iter()
+                        .flatten()
+                        .collect::<::std::vec::Vec<_>>()
+                        .join("; "),
+                )
+            }
+        }
+    }
+}
+/// Default values for struct properties.
+mod defaults {
+    use super::*;
+    pub(super) fn pet_legs() -> u8 {
+        4u8
+    }
+}
@@ -0,0 +1,56 @@
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ::serde::Deserialize,
+    ::serde::Serialize
+)]
+pub struct Foo {
+    #[serde(default = "defaults::foo_bar_baz")]
+    pub bar_baz: i64,
+}
+impl ::std::default::Default for Foo {
+    fn default() -> Self {
+        Self {
+            bar_baz: defaults::foo_bar_baz(),
+        }
+    }
+}
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ::serde::Deserialize,
+    ::serde::Serialize
+)]
+pub struct FooBar {
+    #[serde(default = "defaults::foo_bar_baz_2")]
+    pub baz: i64,
+}
+impl ::std::default::Default for FooBar {
+    fn default() -> Self {
+        Self {
+            baz: defaults::foo_bar_baz_2(),
+        }
+    }
+}
+/// Default values for struct properties.
+mod defaults {
+    use super::*;
+    pub(super) fn foo_bar_baz() -> i64 {
+        1i64
+    }
+    pub(super) fn foo_bar_baz_2() -> i64 {
+        2i64
+    }
+}
@@ -0,0 +1,144 @@
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    PartialOrd,
+    ::serde::Deserialize,
+    ::serde::Serialize
+)]
+#[serde(tag = "kind", content = "data")]
+pub enum Adjacent {
+    #[serde(rename = "circle")]
+    Circle(f64),
+    #[serde(rename = "square")]
+    Square(i64),
+    #[serde(rename = "point")]
+    Point,
+}
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    PartialOrd,
+    ::serde::Deserialize,
+    ::serde::Serialize
+)]
+#[serde(tag = "kind")]
+pub enum Internal {
+    #[serde(rename = "circle")]
+    Circle { radius: f64 },
+    #[serde(rename = "square")]
+    Square { side: f64 },
+}
+///A value that's valid for more than one variant is taken as the first of them, though the schema rejects it.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ::serde::Deserialize,
+    ::serde::Serialize
+)]
+#[serde(untagged)]
+pub enum Name {
+    Variant0(String),
+    Variant1(NameVariant1),
+}
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, ::serde::Serialize)]
+#[serde(transparent)]
+pub struct NameVariant1(String);
+impl ::std::ops::Deref for NameVariant1 {
+    type Target = str;
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+impl ::std::fmt::Display for NameVariant1 {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+impl ::std::convert::From<NameVariant1> for String {
+    fn from(value: NameVariant1) -> Self {
+        value.0
+    }
+}
+impl ::std::convert::TryFrom<String> for NameVariant1 {
+    type Error = String;
+    fn try_from(value: String) -> ::std::result::Result<Self, Self::Error> {
+        let valid: ::std::result::Result<(), String> = {
+            let value: &str = &value;
+            if value.chars().count() < 3usize {
+                return Err("must be at least 3 characters".to_string());
+            }
+            Ok(())
+        };
+        valid?;
+        Ok(Self(value))
+    }
+}
+impl ::std::str::FromStr for NameVariant1 {
+    type Err = String;
+    fn from_str(value: &str) -> ::std::result::Result<Self, Self::Err> {
+        Self::try_from(value.to_string())
+    }
+}
+impl ::std::convert::TryFrom<&str> for NameVariant1 {
+    type Error = String;
+    fn try_from(value: &str) -> ::std::result::Result<Self, Self::Error> {
+        Self::try_from(value.to_string())
+    }
+}
+impl<'de> ::serde::Deserialize<'de> for NameVariant1 {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        Self::try_from(<String as ::serde::Deserialize>::deserialize(deserializer)?)
+            .map_err(<D::Error as ::serde::de::Error>::custom)
+    }
+}
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ::serde::Deserialize,
+    ::serde::Serialize
+)]
+#[serde(untagged)]
+pub enum Scalar {
+    String(String),
+    Integer(i64),
+}
+///A value that's valid for more than one variant is taken as the first of them, though the schema rejects it.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ::serde::Deserialize,
+    ::serde::Serialize
+)]
+#[serde(untagged)]
+pub enum Untagged {
+    ByName {
+        #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+        name: ::std::option::Option<String>,
+    },
+    ById {
+        #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+        id: ::std::option::Option<i64>,
+    },
+}
@@ -0,0 +1,20 @@
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ::serde::Deserialize,
+    ::serde::Serialize
+)]
+pub struct Person {
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub middle: ::std::option::Option<String>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub nickname: ::std::option::Option<String>,
+    #[serde(deserialize_with = "::serde::Deserialize::deserialize")]
+    pub spouse: ::std::option::Option<String>,
+}
@@ -0,0 +1,35 @@
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ::serde::Deserialize,
+    ::serde::Serialize
+)]
+pub struct Person {
+    #[serde(
+        default,
+        skip_serializing_if = "::std::option::Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub middle: ::std::option::Option<::std::option::Option<String>>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub nickname: ::std::option::Option<String>,
+    #[serde(deserialize_with = "::serde::Deserialize::deserialize")]
+    pub spouse: ::std::option::Option<String>,
+}
+/// Deserialize a value that's present (even if it's null) as
+/// `Some`; used for `Option<Option<T>>` properties.
+fn deserialize_some<'de, T, D>(
+    deserializer: D,
+) -> ::std::result::Result<::std::option::Option<T>, D::Error>
+where
+    T: ::serde::Deserialize<'de>,
+    D: ::serde::Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}